@@ -12,7 +12,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     for i in 0..10_000 {
         expressions.push((
             i,
-            chert::parse(&format!("nick == 'meow' and host == 'meow'")).unwrap(),
+            chert::parse("nick == 'meow' and host == 'meow'").unwrap(),
         ));
     }
     let engine = chert::compile(expressions).unwrap();
@@ -0,0 +1,406 @@
+//! `expr!`, which checks expressions as they're compiled.
+//!
+//! A proc macro only sees tokens, not what type each variable is, so the expression is only
+//! parsed into its operators and operands here. Their types are checked by a `const` that
+//! walks them with `chert::parse::shape`, against the types `#[derive(Variables)]` declares,
+//! so an expression that compiles can't fail to parse when it's built at runtime.
+
+use chert_parse::parse::shape::Shape;
+use chert_parse::parse::syntax::Syntax;
+use chert_parse::ParseError;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned, ToTokens as _};
+use std::ops::Range;
+use syn::{parse::Parse, LitByteStr, LitStr, Token};
+
+/// The longest name `Shape::type_name` gives
+const LONGEST_NAME: usize = "timestamp".len();
+
+/// The arguments of `expr!`, `Type, "expression"`
+pub struct ExprInput {
+    variables: syn::Type,
+    _comma: Token![,],
+    expression: LitStr,
+}
+
+impl Parse for ExprInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let parsed = Self {
+            variables: input.parse()?,
+            _comma: input.parse()?,
+            expression: input.parse()?,
+        };
+        // allow a trailing comma
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// A rendered error, without the `error: ` the compiler will already have said
+fn diagnostic(rendered: String) -> String {
+    match rendered.strip_prefix("error: ") {
+        Some(rendered) => rendered.to_owned(),
+        None => rendered,
+    }
+}
+
+fn shape_tokens(shape: Shape) -> TokenStream2 {
+    let type_ = |type_: chert_parse::Type| {
+        let variant = Ident::new(&format!("{type_:?}"), Span::call_site());
+        quote! { chert::Type::#variant }
+    };
+    match shape {
+        Shape::Null => quote! { chert::parse::shape::Shape::Null },
+        Shape::Variable(variable) => {
+            let variable = type_(variable);
+            quote! { chert::parse::shape::Shape::Variable(#variable) }
+        }
+        Shape::ListLiteral(list) => {
+            let list = type_(list);
+            quote! { chert::parse::shape::Shape::ListLiteral(#list) }
+        }
+        Shape::Value(value) => {
+            let value = type_(value);
+            quote! { chert::parse::shape::Shape::Value(#value) }
+        }
+    }
+}
+
+/// Part of an error message, which can name the type of an operand only known in the `const`
+enum Part {
+    Text(String),
+    TypeName(TokenStream2),
+}
+
+/// The statements of the `const` that checks an expression's types, which names the shape of
+/// each operand as it's checked
+struct Check<'a> {
+    expression: &'a str,
+    variables: &'a syn::Type,
+    span: Span,
+    statements: Vec<TokenStream2>,
+    /// How many shapes and elements have been named
+    named: usize,
+    /// The elements the quantifiers being checked have named, innermost last, and whether
+    /// their predicates used them
+    elements: Vec<(String, Ident, bool)>,
+    /// Whether any variable has been looked up in `Variables::DECLARED`
+    declared: bool,
+}
+
+impl Check<'_> {
+    fn name(&mut self, prefix: &str) -> Ident {
+        self.named += 1;
+        Ident::new(&format!("{prefix}_{}", self.named), Span::mixed_site())
+    }
+
+    fn render(&self, message: &str, primary: &Range<usize>, secondary: &[Range<usize>]) -> String {
+        diagnostic(chert_parse::diagnostic::render(
+            self.expression,
+            message,
+            primary,
+            secondary,
+        ))
+    }
+
+    /// Panic with `error`, as parsing the expression at runtime would have failed with it
+    fn error(&self, error: chert_parse::parse::Error) -> TokenStream2 {
+        let message = diagnostic(ParseError::from(error).render(self.expression));
+        quote_spanned! {self.span=> panic!("{}", #message) }
+    }
+
+    /// Panic with a message joined together in the `const`, from `parts`
+    fn fail(
+        &self,
+        parts: Vec<Part>,
+        primary: &Range<usize>,
+        secondary: &[Range<usize>],
+    ) -> TokenStream2 {
+        // everything after the message, that doesn't depend on any types
+        let rest = self.render("", primary, secondary);
+        let mut length = rest.len();
+        let parts = parts
+            .into_iter()
+            .map(|part| match part {
+                Part::Text(text) => {
+                    length += text.len();
+                    quote! { #text }
+                }
+                Part::TypeName(shape) => {
+                    length += LONGEST_NAME;
+                    quote! { #shape.type_name() }
+                }
+            })
+            .collect::<Vec<_>>();
+        quote_spanned! {self.span=>
+            {
+                let message = chert::parse::shape::Message::<#length>::new(&[#(#parts,)* #rest]);
+                panic!("{}", message.as_str())
+            }
+        }
+    }
+
+    /// Check `syntax`, returning an expression for its shape
+    fn operand(&mut self, (syntax, span): &(Syntax, Range<usize>)) -> TokenStream2 {
+        match syntax {
+            Syntax::Constant(node) => shape_tokens(Shape::of(node)),
+            Syntax::Element(name) => {
+                let (_, element, used) = self
+                    .elements
+                    .iter_mut()
+                    .rfind(|(element, _, _)| element == name)
+                    .expect("the parser only gives names of elements in scope");
+                *used = true;
+                quote! { chert::parse::shape::Shape::Variable(#element) }
+            }
+            Syntax::Variable(name) => {
+                self.declared = true;
+                let shape = self.name("shape");
+                let bytes = LitByteStr::new(name.as_bytes(), self.span);
+                let variables = self.variables.to_token_stream().to_string();
+                let missing = self.render(
+                    &format!("`{name}` isn't a variable of `{variables}`"),
+                    span,
+                    &[],
+                );
+                let unknown = self.render(
+                    &format!(
+                        "`{name}` is nested in a type that doesn't declare the types of its \
+                         variables, so it can't be checked as it's compiled"
+                    ),
+                    span,
+                    &[],
+                );
+                self.statements.push(quote_spanned! {self.span=>
+                    let #shape = match chert::variables::declaration(declared, #bytes) {
+                        chert::variables::Declaration::Variable(type_) => {
+                            chert::parse::shape::Shape::Variable(type_)
+                        }
+                        chert::variables::Declaration::Missing => panic!("{}", #missing),
+                        chert::variables::Declaration::Unknown => panic!("{}", #unknown),
+                    };
+                });
+                quote! { #shape }
+            }
+            Syntax::Unary {
+                operator,
+                span,
+                operand,
+            } => {
+                let operand_shape = self.operand(operand);
+                let shape = self.name("shape");
+                let variant = Ident::new(&format!("{operator:?}"), Span::call_site());
+                let fail = self.fail(
+                    Vec::from([
+                        Part::Text(format!("`{operator}` cannot be applied to ")),
+                        Part::TypeName(operand_shape.clone()),
+                    ]),
+                    span,
+                    std::slice::from_ref(&operand.1),
+                );
+                self.statements.push(quote_spanned! {self.span=>
+                    let #shape = match chert::parse::shape::unary(
+                        &chert::parse::operators::UnaryOperator::#variant,
+                        #operand_shape,
+                    ) {
+                        Some(shape) => shape,
+                        None => #fail,
+                    };
+                });
+                quote! { #shape }
+            }
+            Syntax::Binary {
+                operator,
+                span,
+                left,
+                right,
+            } => {
+                let left_shape = self.operand(left);
+                let right_shape = self.operand(right);
+                let shape = self.name("shape");
+                let variant = Ident::new(&format!("{operator:?}"), Span::call_site());
+                let fail = self.fail(
+                    Vec::from([
+                        Part::Text(format!("`{operator}` cannot be applied to ")),
+                        Part::TypeName(left_shape.clone()),
+                        Part::Text(String::from(" and ")),
+                        Part::TypeName(right_shape.clone()),
+                    ]),
+                    span,
+                    &[left.1.clone(), right.1.clone()],
+                );
+                self.statements.push(quote_spanned! {self.span=>
+                    let #shape = match chert::parse::shape::binary(
+                        &chert::parse::operators::BinaryOperator::#variant,
+                        #left_shape,
+                        #right_shape,
+                    ) {
+                        Some(shape) => shape,
+                        None => #fail,
+                    };
+                });
+                quote! { #shape }
+            }
+            Syntax::List(elements) => {
+                let shapes = elements
+                    .iter()
+                    .map(|element| self.operand(element))
+                    .collect::<Vec<_>>();
+                let shape = self.name("shape");
+                // the first element that doesn't fit, where the first not fitting is that
+                // it can't be in a list at all
+                let mut arms = Vec::new();
+                for (index, (_, span)) in elements.iter().enumerate() {
+                    let fail = if index == 0 {
+                        self.fail(
+                            Vec::from([
+                                Part::Text(String::from("lists cannot contain ")),
+                                Part::TypeName(shapes[0].clone()),
+                            ]),
+                            span,
+                            &[],
+                        )
+                    } else {
+                        self.fail(
+                            Vec::from([
+                                Part::Text(String::from("lists cannot mix ")),
+                                Part::TypeName(shapes[0].clone()),
+                                Part::Text(String::from(" and ")),
+                                Part::TypeName(shapes[index].clone()),
+                            ]),
+                            span,
+                            &[],
+                        )
+                    };
+                    arms.push(if index + 1 == elements.len() {
+                        quote_spanned! {self.span=> _ => #fail }
+                    } else {
+                        quote_spanned! {self.span=> #index => #fail }
+                    });
+                }
+                self.statements.push(quote_spanned! {self.span=>
+                    let #shape = match chert::parse::shape::list(&[#(#shapes,)*]) {
+                        Ok(shape) => shape,
+                        Err(index) => match index {
+                            #(#arms,)*
+                        },
+                    };
+                });
+                quote! { #shape }
+            }
+            Syntax::Quantifier {
+                list,
+                element,
+                predicate,
+                ..
+            } => {
+                let list_shape = self.operand(list);
+                let not_list = self.error(chert_parse::parse::Error::BadQuantifier {
+                    span: list.1.clone(),
+                });
+                // whether the element is named depends on whether the predicate uses it
+                let statement = self.statements.len();
+                let element_type = self.name("element");
+                self.elements
+                    .push((element.clone(), element_type.clone(), false));
+                let predicate_shape = self.operand(predicate);
+                let (_, _, used) = self.elements.pop().unwrap();
+                self.statements.insert(
+                    statement,
+                    if used {
+                        quote_spanned! {self.span=>
+                            let #element_type = match chert::parse::shape::element(#list_shape) {
+                                Some(type_) => type_,
+                                None => #not_list,
+                            };
+                        }
+                    } else {
+                        quote_spanned! {self.span=>
+                            if chert::parse::shape::element(#list_shape).is_none() {
+                                #not_list
+                            }
+                        }
+                    },
+                );
+
+                let shape = self.name("shape");
+                let not_boolean = self.error(chert_parse::parse::Error::NotBoolean {
+                    span: predicate.1.clone(),
+                });
+                self.statements.push(quote_spanned! {self.span=>
+                    let #shape = match chert::parse::shape::quantifier(#predicate_shape) {
+                        Some(shape) => shape,
+                        None => #not_boolean,
+                    };
+                });
+                quote! { #shape }
+            }
+        }
+    }
+}
+
+pub fn expr_inner(input: ExprInput) -> syn::Result<TokenStream2> {
+    let ExprInput {
+        variables,
+        expression: literal,
+        ..
+    } = input;
+    let expression = literal.value();
+    let span = literal.span();
+
+    let invalid = |error: ParseError| syn::Error::new(span, diagnostic(error.render(&expression)));
+    let tokens = chert_parse::lex::lex(&expression).map_err(|error| invalid(error.into()))?;
+    // anything that fails to parse whatever the types of its variables
+    let root = chert_parse::parse::syntax::parse(tokens).map_err(|error| invalid(error.into()))?;
+
+    let mut check = Check {
+        expression: &expression,
+        variables: &variables,
+        span,
+        statements: Vec::new(),
+        named: 0,
+        elements: Vec::new(),
+        declared: false,
+    };
+    let root_shape = check.operand(&root);
+    let not_boolean = check.error(chert_parse::parse::Error::NotBoolean { span: root.1 });
+    let statements = check.statements;
+
+    let declared = if check.declared {
+        let message = format!(
+            "`{}` doesn't declare the types of its variables, so expressions can't be checked \
+             against it as they're compiled; derive `Variables` for it, or parse them with \
+             `chert::parse`",
+            variables.to_token_stream()
+        );
+        quote_spanned! {span=>
+            let declared = <#variables as chert::variables::Variables>::DECLARED;
+            if declared.is_none() {
+                panic!("{}", #message);
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // the `const` is evaluated as the expression is compiled, and fails to compile if the
+    // expression wouldn't parse, but the variables can only be looked up to build the `Ast`
+    // at runtime
+    Ok(quote_spanned! {span=>
+        {
+            const _: () = {
+                #declared
+                #(#statements)*
+                if !#root_shape.is_boolean() {
+                    #not_boolean
+                }
+            };
+            match chert::parse::<#variables>(#literal) {
+                Ok(ast) => ast,
+                Err(_) => unreachable!("the expression was checked as it was compiled"),
+            }
+        }
+    })
+}
@@ -1,21 +1,47 @@
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
-use quote::{quote, ToTokens};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens as _};
+use std::collections::HashSet;
 use syn::{
-    parse::Parse, parse_macro_input, Data::Struct, DataStruct, DeriveInput, Fields::Named,
-    FieldsNamed, Token, Type,
+    ext::IdentExt as _, parse::Parse, parse_macro_input, punctuated::Punctuated, Data, DataEnum,
+    DataStruct, DataUnion, DeriveInput, Field, Fields, GenericArgument, Generics, Index, LitStr,
+    Member, Path, PathArguments, Token, Type, Variant,
 };
 
+mod expr;
+
 mod kw {
     syn::custom_keyword!(as_ref);
+    syn::custom_keyword!(nested);
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(rename_all);
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(with);
 }
 
 enum ChertAttribute {
     AsRef {
-        _as_ref: kw::as_ref,
+        as_ref: kw::as_ref,
         _equals: Token![=],
         as_type: Type,
     },
+    /// The field is `Variables` itself, and its variables are exposed as `field.variable`
+    Nested { nested: kw::nested },
+    /// Expose the field under another name
+    Rename {
+        rename: kw::rename,
+        _equals: Token![=],
+        name: LitStr,
+    },
+    /// Don't expose the field at all
+    Skip { skip: kw::skip },
+    /// Read the field with a function, `fn(&Self) -> &T` (or `-> Option<&T>` for an `Option`
+    /// field), rather than directly, e.g. to compute it from other fields
+    With {
+        with: kw::with,
+        _equals: Token![=],
+        path: Path,
+    },
 }
 
 impl Parse for ChertAttribute {
@@ -23,96 +49,608 @@ impl Parse for ChertAttribute {
         let lookahead = input.lookahead1();
         if lookahead.peek(kw::as_ref) {
             Ok(Self::AsRef {
-                _as_ref: input.parse()?,
+                as_ref: input.parse()?,
                 _equals: input.parse()?,
                 as_type: input.parse()?,
             })
+        } else if lookahead.peek(kw::nested) {
+            Ok(Self::Nested {
+                nested: input.parse()?,
+            })
+        } else if lookahead.peek(kw::rename) {
+            Ok(Self::Rename {
+                rename: input.parse()?,
+                _equals: input.parse()?,
+                name: input.parse()?,
+            })
+        } else if lookahead.peek(kw::skip) {
+            Ok(Self::Skip {
+                skip: input.parse()?,
+            })
+        } else if lookahead.peek(kw::with) {
+            Ok(Self::With {
+                with: input.parse()?,
+                _equals: input.parse()?,
+                path: input.parse()?,
+            })
         } else {
             Err(lookahead.error())
         }
     }
 }
 
-#[proc_macro_derive(Variables, attributes(chert))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let DeriveInput {
-        ident: struct_name,
-        data,
-        ..
-    } = parse_macro_input!(input as DeriveInput);
+/// Attributes on the struct or enum itself
+enum ContainerAttribute {
+    /// Rename every field from snake_case, or every variant from PascalCase, e.g.
+    /// `rename_all = "camelCase"`
+    RenameAll {
+        _rename_all: kw::rename_all,
+        _equals: Token![=],
+        case: LitStr,
+    },
+}
 
-    let Struct(DataStruct {
-        fields: Named(FieldsNamed {
-            named: ref named_fields,
-            ..
-        }),
-        ..
-    }) = data
-    else {
-        panic!("must be a struct with named fields");
+impl Parse for ContainerAttribute {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(kw::rename_all) {
+            Ok(Self::RenameAll {
+                _rename_all: input.parse()?,
+                _equals: input.parse()?,
+                case: input.parse()?,
+            })
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+/// The casings of `rename_all`, as in serde
+#[derive(Clone, Copy)]
+enum Case {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl Case {
+    fn parse(case: &LitStr) -> syn::Result<Self> {
+        Ok(match case.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                return Err(syn::Error::new(
+                    case.span(),
+                    "expected one of `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, \
+                     `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` or \
+                     `SCREAMING-KEBAB-CASE`",
+                ))
+            }
+        })
+    }
+
+    /// Field names become variable names, so they have to stay identifiers
+    fn parse_for_fields(case: &LitStr) -> syn::Result<Self> {
+        match Self::parse(case)? {
+            case @ (Self::Lower | Self::Camel | Self::Snake) => Ok(case),
+            _ => Err(syn::Error::new(
+                case.span(),
+                "expected `camelCase`, `lowercase` or `snake_case`; variable names must start \
+                 with a lowercase letter",
+            )),
+        }
+    }
+
+    /// Convert a snake_case field name
+    fn field(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Snake => name.to_owned(),
+            Self::Upper | Self::ScreamingSnake => name.to_ascii_uppercase(),
+            Self::Kebab => name.replace('_', "-"),
+            Self::ScreamingKebab => name.replace('_', "-").to_ascii_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut renamed = String::new();
+                let mut upper = matches!(self, Self::Pascal);
+                for c in name.chars() {
+                    if c == '_' && !renamed.is_empty() {
+                        upper = true;
+                    } else if upper {
+                        renamed.push(c.to_ascii_uppercase());
+                        upper = false;
+                    } else {
+                        renamed.push(c);
+                    }
+                }
+                renamed
+            }
+        }
+    }
+
+    /// Convert a PascalCase variant name
+    fn variant(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_ascii_lowercase(),
+            Self::Upper => name.to_ascii_uppercase(),
+            Self::Pascal => name.to_owned(),
+            Self::Camel => {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_lowercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::Snake | Self::ScreamingSnake | Self::Kebab | Self::ScreamingKebab => {
+                let separator = match self {
+                    Self::Snake | Self::ScreamingSnake => '_',
+                    _ => '-',
+                };
+                let mut renamed = String::new();
+                for (i, c) in name.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        renamed.push(separator);
+                    }
+                    renamed.push(c.to_ascii_lowercase());
+                }
+                match self {
+                    Self::ScreamingSnake | Self::ScreamingKebab => renamed.to_ascii_uppercase(),
+                    _ => renamed,
+                }
+            }
+        }
+    }
+}
+
+/// Whether `name` lexes as a single identifier, so expressions can refer to it
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some('a'..='z')) && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `T` if `ty` is `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) if arguments.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    as_ref: Option<Type>,
+    nested: bool,
+    rename: Option<LitStr>,
+    skip: bool,
+    with: Option<Path>,
+}
+
+impl FieldOptions {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut options = Self::default();
+        // the first attribute that makes `skip` meaningless, to report if there's also a `skip`
+        let mut conflict = None;
+        let mut skip = None;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("chert") {
+                continue;
+            }
+            let attributes =
+                attr.parse_args_with(Punctuated::<ChertAttribute, Token![,]>::parse_terminated)?;
+            for attribute in attributes {
+                match attribute {
+                    ChertAttribute::AsRef {
+                        as_ref, as_type, ..
+                    } => {
+                        if options.as_ref.replace(as_type).is_some() {
+                            return Err(syn::Error::new(as_ref.span, "duplicate `as_ref`"));
+                        }
+                        conflict.get_or_insert(as_ref.span);
+                    }
+                    ChertAttribute::Nested { nested } => {
+                        if options.nested {
+                            return Err(syn::Error::new(nested.span, "duplicate `nested`"));
+                        }
+                        options.nested = true;
+                        conflict.get_or_insert(nested.span);
+                    }
+                    ChertAttribute::Rename { rename, name, .. } => {
+                        if !is_identifier(&name.value()) {
+                            return Err(syn::Error::new(
+                                name.span(),
+                                "variable names must start with a lowercase letter, then only \
+                                 contain letters, digits and `_`",
+                            ));
+                        }
+                        if options.rename.replace(name).is_some() {
+                            return Err(syn::Error::new(rename.span, "duplicate `rename`"));
+                        }
+                        conflict.get_or_insert(rename.span);
+                    }
+                    ChertAttribute::Skip { skip: keyword } => {
+                        if options.skip {
+                            return Err(syn::Error::new(keyword.span, "duplicate `skip`"));
+                        }
+                        options.skip = true;
+                        skip = Some(keyword.span);
+                    }
+                    ChertAttribute::With { with, path, .. } => {
+                        if options.with.replace(path).is_some() {
+                            return Err(syn::Error::new(with.span, "duplicate `with`"));
+                        }
+                        conflict.get_or_insert(with.span);
+                    }
+                }
+            }
+        }
+
+        if let (Some(skip), Some(_)) = (skip, conflict) {
+            return Err(syn::Error::new(
+                skip,
+                "`skip` can't be combined with other attributes",
+            ));
+        }
+        if let (true, Some(as_type)) = (options.nested, &options.as_ref) {
+            return Err(syn::Error::new_spanned(
+                as_type,
+                "`as_ref` can't be combined with `nested`",
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// Whether `tokens` mention any of `params`, so a bound on them has to be added to the impl
+fn mentions(tokens: TokenStream2, params: &HashSet<Ident>) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => params.contains(&ident),
+        TokenTree::Group(group) => mentions(group.stream(), params),
+        _ => false,
+    })
+}
+
+fn derive_struct(
+    struct_name: &Ident,
+    mut generics: Generics,
+    fields: &Fields,
+    rename_all: Option<LitStr>,
+) -> syn::Result<TokenStream2> {
+    let case = match &rename_all {
+        Some(case) => Case::parse_for_fields(case)?,
+        None => Case::Snake,
     };
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<HashSet<_>>();
 
-    let mut fields = Vec::new();
+    let mut variables = Vec::new();
+    let mut declared = Vec::new();
     let mut accessor_functions = Vec::new();
+    let mut bounds = Vec::new();
+    let mut names = HashSet::new();
 
-    for field in named_fields.iter() {
-        let Some(field_name) = &field.ident else {
+    for (index, field) in fields.iter().enumerate() {
+        let options = FieldOptions::parse(field)?;
+        if options.skip {
             continue;
-        };
-        let mut field_type = field.ty.clone();
-        let mut use_as_ref = false;
+        }
 
-        for attr in &field.attrs {
-            if attr.path().is_ident("chert") {
-                let Ok(chert_attr) = attr.parse_args::<ChertAttribute>() else {
-                    panic!("Invalid chert attribute: {}", attr.to_token_stream())
+        let (member, ident_str, accessor_name) = match &field.ident {
+            Some(field_name) => {
+                let unraw = field_name.unraw().to_string();
+                let ident_str = match &options.rename {
+                    Some(rename) => rename.value(),
+                    None => case.field(&unraw),
+                };
+                (
+                    Member::Named(field_name.clone()),
+                    ident_str,
+                    Ident::new(
+                        &format!("_chert_get_{}", unraw.to_ascii_lowercase()),
+                        field_name.span(),
+                    ),
+                )
+            }
+            None => {
+                let Some(rename) = &options.rename else {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "tuple struct fields need a name, e.g. `#[chert(rename = \"x\")]`",
+                    ));
                 };
+                (
+                    Member::Unnamed(Index::from(index)),
+                    rename.value(),
+                    Ident::new(&format!("_chert_get_{index}"), Span::call_site()),
+                )
+            }
+        };
+        if !names.insert(ident_str.clone()) {
+            return Err(syn::Error::new(
+                options.rename.as_ref().map_or_else(
+                    || field.ident.as_ref().map_or(Span::call_site(), Ident::span),
+                    LitStr::span,
+                ),
+                format!("variable `{ident_str}` is defined twice"),
+            ));
+        }
+
+        let optional = option_inner(&field.ty);
+        let field_type = options
+            .as_ref
+            .clone()
+            .unwrap_or_else(|| optional.unwrap_or(&field.ty).clone());
+        let use_as_ref = options.as_ref.is_some();
 
-                let ChertAttribute::AsRef { as_type, .. } = chert_attr;
-                field_type = as_type;
-                use_as_ref = true;
+        let stored = optional.unwrap_or(&field.ty);
+        if options.nested {
+            if mentions(field_type.to_token_stream(), &params) {
+                bounds.push(quote! { #field_type: chert::variables::Variables + 'static });
+            }
+        } else if mentions(quote! { #field_type #stored }, &params) {
+            bounds.push(quote! { #field_type: chert::variables::VariableType });
+            bounds.push(quote! {
+                <#field_type as chert::variables::VariableType>::AccessedAs: chert::variables::AccessedType
+            });
+            if options.with.is_none() && use_as_ref {
+                bounds.push(quote! {
+                    #stored: std::convert::AsRef<<#field_type as chert::variables::VariableType>::AccessedAs>
+                });
             }
         }
 
-        let accessor_name = Ident::new(
-            &format!("_chert_get_{}", field_name.to_string().to_ascii_lowercase()),
-            field_name.span(),
-        );
+        let accessor = match &options.with {
+            Some(path) => quote! { #path },
+            None => quote! { Self::#accessor_name },
+        };
 
-        let ident_str = field_name.to_string();
+        if options.nested {
+            declared.push(quote! {
+                chert::variables::Declared::Nested(
+                    #ident_str,
+                    <#field_type as chert::variables::Variables>::DECLARED,
+                )
+            });
+            variables.push(quote! {
+                for (name, variable) in <#field_type as chert::variables::Variables>::variables() {
+                    variables.insert(format!("{}.{}", #ident_str, name), variable.nest(#accessor));
+                }
+            });
+            if options.with.is_none() {
+                accessor_functions.push(quote! {
+                    #[allow(non_snake_case)]
+                    fn #accessor_name(object: &Self) -> &#field_type {
+                        &object.#member
+                    }
+                });
+            }
+            continue;
+        }
 
-        fields.push(quote! {
-            (#ident_str, <#field_type as chert::variables::VariableType>::from_field(Self::#accessor_name))
+        declared.push(quote! {
+            chert::variables::Declared::Variable(
+                #ident_str,
+                <<#field_type as chert::variables::VariableType>::AccessedAs as chert::variables::AccessedType>::TYPE,
+            )
         });
 
-        if use_as_ref {
-            accessor_functions.push(quote! {
-                #[allow(non_snake_case)]
-                fn #accessor_name(object: &#struct_name) -> &<#field_type as chert::variables::VariableType>::AccessedAs {
-                    use std::convert::AsRef;
-                    object.#field_name.as_ref()
-                }
+        if optional.is_some() {
+            variables.push(quote! {
+                variables.insert(
+                    String::from(#ident_str),
+                    <#field_type as chert::variables::VariableType>::from_optional_field(#accessor),
+                );
             });
-        } else {
+            if options.with.is_some() {
+                continue;
+            }
+            let value = if use_as_ref {
+                quote! { std::convert::AsRef::as_ref }
+            } else {
+                quote! { <#field_type as chert::variables::VariableType>::access }
+            };
             accessor_functions.push(quote! {
                 #[allow(non_snake_case)]
-                fn #accessor_name(object: &#struct_name) -> &<#field_type as chert::variables::VariableType>::AccessedAs {
-                    &object.#field_name
+                fn #accessor_name(object: &Self) -> Option<&<#field_type as chert::variables::VariableType>::AccessedAs> {
+                    object.#member.as_ref().map(#value)
                 }
             });
+            continue;
+        }
+
+        variables.push(quote! {
+            variables.insert(
+                String::from(#ident_str),
+                <#field_type as chert::variables::VariableType>::from_field(#accessor),
+            );
+        });
+        if options.with.is_some() {
+            continue;
         }
+
+        let value = if use_as_ref {
+            quote! { std::convert::AsRef::as_ref }
+        } else {
+            quote! { <#field_type as chert::variables::VariableType>::access }
+        };
+        accessor_functions.push(quote! {
+            #[allow(non_snake_case)]
+            fn #accessor_name(object: &Self) -> &<#field_type as chert::variables::VariableType>::AccessedAs {
+                #value(&object.#member)
+            }
+        });
     }
 
-    quote! {
-        impl #struct_name {
+    if !bounds.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for bound in bounds {
+            where_clause.predicates.push(syn::parse2(bound)?);
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
             #(#accessor_functions)*
         }
 
-        impl chert::variables::Variables for #struct_name {
-            fn variables() -> std::collections::HashMap<&'static str, chert::variables::Variable<Self>> {
-                std::collections::HashMap::from([#(#fields),*])
+        impl #impl_generics chert::variables::Variables for #struct_name #ty_generics #where_clause {
+            fn variables() -> std::collections::HashMap<String, chert::variables::Variable<Self>> {
+                let mut variables = std::collections::HashMap::new();
+                #(#variables)*
+                variables
             }
+
+            const DECLARED: Option<&'static [chert::variables::Declared]> = Some(&[#(#declared,)*]);
         }
+    })
+}
+
+/// C-like enums are string variables, each variant being its name
+fn derive_enum(
+    enum_name: &Ident,
+    generics: Generics,
+    variants: &Punctuated<Variant, Token![,]>,
+    rename_all: Option<LitStr>,
+) -> syn::Result<TokenStream2> {
+    let case = rename_all.as_ref().map(Case::parse).transpose()?;
+
+    let mut arms = Vec::new();
+    let mut names = HashSet::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                "only enums without fields can derive Variables, as string variables",
+            ));
+        }
+
+        let mut rename = None;
+        for attr in &variant.attrs {
+            if !attr.path().is_ident("chert") {
+                continue;
+            }
+            let attributes =
+                attr.parse_args_with(Punctuated::<ChertAttribute, Token![,]>::parse_terminated)?;
+            for attribute in attributes {
+                let ChertAttribute::Rename {
+                    rename: keyword,
+                    name,
+                    ..
+                } = attribute
+                else {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "enum variants only support `rename`",
+                    ));
+                };
+                if rename.replace(name).is_some() {
+                    return Err(syn::Error::new(keyword.span, "duplicate `rename`"));
+                }
+            }
+        }
+
+        let variant_name = &variant.ident;
+        let value = match (&rename, case) {
+            (Some(rename), _) => rename.value(),
+            (None, Some(case)) => case.variant(&variant_name.unraw().to_string()),
+            (None, None) => variant_name.unraw().to_string(),
+        };
+        if !names.insert(value.clone()) {
+            return Err(syn::Error::new(
+                rename.as_ref().map_or(variant_name.span(), LitStr::span),
+                format!("`{value}` is the value of more than one variant"),
+            ));
+        }
+        arms.push(quote! { Self::#variant_name => #value });
     }
-    .into()
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics chert::variables::VariableType for #enum_name #ty_generics #where_clause {
+            type AccessedAs = str;
+            fn access(&self) -> &str {
+                match self {
+                    #(#arms,)*
+                }
+            }
+            fn from_accessor<T>(
+                accessor: chert::variables::Accessor<T, Self::AccessedAs>,
+            ) -> chert::variables::Variable<T> {
+                chert::variables::Variable::String(accessor)
+            }
+        }
+    })
+}
+
+fn derive_inner(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+
+    let mut rename_all = None;
+    for attr in &attrs {
+        if attr.path().is_ident("chert") {
+            match attr.parse_args::<ContainerAttribute>()? {
+                ContainerAttribute::RenameAll { case, .. } => rename_all = Some(case),
+            }
+        }
+    }
+
+    match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            derive_struct(&ident, generics, &fields, rename_all)
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            derive_enum(&ident, generics, &variants, rename_all)
+        }
+        Data::Union(DataUnion { union_token, .. }) => Err(syn::Error::new(
+            union_token.span,
+            "unions can't derive Variables",
+        )),
+    }
+}
+
+#[proc_macro_derive(Variables, attributes(chert))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    derive_inner(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Parse an expression written in code, e.g. `chert::expr!(Variables, "nick == 'x'")`,
+/// into an `Ast<Variables, NodeBoolean>`. An expression that doesn't parse, or that doesn't
+/// fit the types `#[derive(Variables)]` declares, fails to compile, so it can't fail at
+/// runtime. `Variables` implemented by hand doesn't declare its types, so can't be used
+#[proc_macro]
+pub fn expr(input: TokenStream) -> TokenStream {
+    expr::expr_inner(parse_macro_input!(input as expr::ExprInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,74 @@
+use std::ops::Range;
+
+fn marks(line: &str, offset: usize, span: &Range<usize>) -> Vec<usize> {
+    let mut columns = Vec::new();
+    let mut end_column = 0;
+    for (column, (index, _char)) in line.char_indices().enumerate() {
+        let index = offset + index;
+        if span.contains(&index) || (span.is_empty() && span.start == index) {
+            columns.push(column);
+        }
+        end_column = column + 1;
+    }
+    // zero-width spans at the end of a line (e.g. "expected more input")
+    if span.is_empty() && span.start == offset + line.len() {
+        columns.push(end_column);
+    }
+    columns
+}
+
+/// Render `message` followed by each line of `expression` touched by `primary` or
+/// `secondary`, with `primary` underlined by `^` and `secondary` underlined by `-`
+pub fn render(
+    expression: &str,
+    message: &str,
+    primary: &Range<usize>,
+    secondary: &[Range<usize>],
+) -> String {
+    let mut lines = Vec::new();
+    let mut location = None;
+    let mut offset = 0;
+
+    for (number, line) in expression.split('\n').enumerate() {
+        let mut underline = Vec::new();
+        for span in secondary {
+            for column in marks(line, offset, span) {
+                if underline.len() <= column {
+                    underline.resize(column + 1, ' ');
+                }
+                underline[column] = '-';
+            }
+        }
+        let primary_marks = marks(line, offset, primary);
+        if let (None, Some(column)) = (location, primary_marks.first()) {
+            location = Some((number + 1, column + 1));
+        }
+        for column in primary_marks {
+            if underline.len() <= column {
+                underline.resize(column + 1, ' ');
+            }
+            underline[column] = '^';
+        }
+
+        if !underline.is_empty() {
+            lines.push((number + 1, line, underline.into_iter().collect::<String>()));
+        }
+        offset += line.len() + 1;
+    }
+
+    let gutter = lines
+        .last()
+        .map_or(1, |(number, _, _)| number.to_string().len());
+    let padding = " ".repeat(gutter);
+
+    let mut output = format!("error: {message}\n");
+    if let Some((line, column)) = location {
+        output.push_str(&format!("{padding}--> {line}:{column}\n"));
+    }
+    output.push_str(&format!("{padding} |\n"));
+    for (number, line, underline) in lines {
+        output.push_str(&format!("{number:>gutter$} | {line}\n"));
+        output.push_str(&format!("{padding} | {underline}\n"));
+    }
+    output
+}
@@ -0,0 +1,265 @@
+mod util;
+
+pub use self::util::escape;
+
+use cidr::errors::NetworkParseError;
+use cidr::IpCidr;
+use logos::Logos;
+use regex::{Error as RegexError, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::net::{AddrParseError, IpAddr};
+use std::ops::Range;
+use std::str::FromStr as _;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Error {
+    #[default]
+    BadSyntax,
+    AddrParseError(AddrParseError),
+    NetworkParseError(NetworkParseError),
+    Custom(String),
+    Unfinished,
+    Regex(regex::Error),
+    /// `offset` is in bytes from the start of the regex literal
+    UnknownRegexFlag {
+        flag: char,
+        offset: usize,
+    },
+    /// `offset` is in bytes from the start of the string literal
+    InvalidEscape {
+        escape: String,
+        offset: usize,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadSyntax => write!(f, "unrecognised token"),
+            Self::AddrParseError(_) => write!(f, "invalid IP address"),
+            Self::NetworkParseError(_) => write!(f, "invalid CIDR"),
+            Self::Custom(message) => write!(f, "{message}"),
+            Self::Unfinished => write!(f, "unterminated literal"),
+            Self::Regex(_) => write!(f, "invalid regex"),
+            Self::UnknownRegexFlag { flag, .. } => write!(f, "unknown regex flag `{flag}`"),
+            Self::InvalidEscape { escape, .. } => write!(f, "invalid escape `{escape}`"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::AddrParseError(error) => Some(error),
+            Self::NetworkParseError(error) => Some(error),
+            Self::Regex(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<AddrParseError> for Error {
+    fn from(error: AddrParseError) -> Self {
+        Self::AddrParseError(error)
+    }
+}
+
+impl From<RegexError> for Error {
+    fn from(error: RegexError) -> Self {
+        Self::Regex(error)
+    }
+}
+
+impl From<NetworkParseError> for Error {
+    fn from(error: NetworkParseError) -> Self {
+        Self::NetworkParseError(error)
+    }
+}
+
+#[derive(Debug, Logos)]
+#[logos(error = Error)]
+pub enum Token {
+    #[token("(")]
+    ParenthesisOpen,
+    #[token(")")]
+    ParenthesisClose,
+    #[token("[")]
+    BracketOpen,
+    #[token("]")]
+    BracketClose,
+    #[token(",")]
+    Comma,
+    /// Between a quantifier's element and its predicate, e.g. `any(ports, p => p > 1024)`
+    #[token("=>")]
+    Arrow,
+    #[regex(r"(\d+(w|d|h|m|s|ms|us|ns))+", util::parse_duration)]
+    Duration(Duration),
+    /// `.` separates the fields of nested structs, e.g. `request.ip`
+    #[regex(r"[a-z][a-zA-Z0-9_]*(\.[a-z][a-zA-Z0-9_]*)*", |lex| lex.slice().to_owned())]
+    Identifier(String),
+    #[regex(r"(\d{1,3}\.){3}\d{1,3}|:?[0-9a-f]+:[0-9a-f:]+", |lex| IpAddr::from_str(lex.slice()))]
+    Ip(IpAddr),
+    #[regex(r"(\d{1,3}\.){3}\d{1,3}/\d{1,2}|:?[0-9a-f]+:[0-9a-f:]+/\d{1,3}", |lex| IpCidr::from_str(lex.slice()))]
+    Cidr(IpCidr),
+    #[regex(r"(\d+)?\.?\d+", |lex| lex.slice().to_owned())]
+    Number(String),
+    #[regex(r"&&|[||]{2}|==|!=?|<=?|>=?|[+]|-|[*]{1,2}|/|%|~", |lex| lex.slice().to_owned())]
+    Operator(String),
+    /// `m` then a delimiter. `,`, `)` and `]` aren't delimiters, so `[m, n]` and `len(m)` are
+    /// identifiers
+    #[regex(r"m[^\w\s,)\]]", |lex| util::compile_regex(lex))]
+    Regex(RegexLiteral),
+    #[regex(r"[ \n]+", |lex| lex.slice().to_owned())]
+    Space(String),
+    /// Quoted with `'` or `"`. A backslash escapes the next character: `\\`, `\'`, `\"`, `\n`,
+    /// `\r`, `\t` and `\0` are what they are in Rust, `\x7f` is an ASCII character from two hex
+    /// digits and `\u{1f980}` is a unicode character from 1 to 6 hex digits. Any other escape is
+    /// an error. Raw strings, `r'...'` or `r"..."`, have no escapes and end at the first closing
+    /// quote
+    #[regex("'|\"", util::find_closing)]
+    #[regex("r'|r\"", util::find_closing_raw)]
+    String(String),
+    #[regex(
+        r"\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})",
+        util::parse_timestamp
+    )]
+    Timestamp(SystemTime),
+}
+
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::ParenthesisOpen, Self::ParenthesisOpen)
+            | (Self::ParenthesisClose, Self::ParenthesisClose)
+            | (Self::BracketOpen, Self::BracketOpen)
+            | (Self::BracketClose, Self::BracketClose)
+            | (Self::Comma, Self::Comma)
+            | (Self::Arrow, Self::Arrow) => true,
+            (Self::Duration(left), Self::Duration(right)) => left == right,
+            (Self::Identifier(left), Self::Identifier(right)) => left == right,
+            (Self::Ip(left), Self::Ip(right)) => left == right,
+            (Self::Cidr(left), Self::Cidr(right)) => left == right,
+            (Self::Number(left), Self::Number(right)) => left == right,
+            (Self::Operator(left), Self::Operator(right)) => left == right,
+            (Self::Regex(left), Self::Regex(right)) => left == right,
+            (Self::Space(left), Self::Space(right)) => left == right,
+            (Self::String(left), Self::String(right)) => left == right,
+            (Self::Timestamp(left), Self::Timestamp(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+/// A compiled regex literal, kept with the flags it was compiled with. `Regex::as_str` is
+/// only the pattern, so the flags are needed to format the literal back or to tell `m/a/` and
+/// `m/a/i` apart
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "RegexParts", into = "RegexParts")]
+pub struct RegexLiteral {
+    regex: Regex,
+    flags: String,
+}
+
+impl RegexLiteral {
+    /// `flags` are any of `i`, `m`, `s`, `x` and `U`, which turn on what they do in an inline
+    /// group, and `u`, which turns unicode off
+    pub fn new(pattern: &str, flags: &str) -> Result<Self, Error> {
+        let mut builder = RegexBuilder::new(pattern);
+        for (offset, flag) in flags.char_indices() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'x' => builder.ignore_whitespace(true),
+                'U' => builder.swap_greed(true),
+                'u' => builder.unicode(false),
+                _ => return Err(Error::UnknownRegexFlag { flag, offset }),
+            };
+        }
+        Ok(Self {
+            regex: builder.build()?,
+            flags: flags.to_owned(),
+        })
+    }
+
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn flags(&self) -> &str {
+        &self.flags
+    }
+}
+
+impl PartialEq for RegexLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        // `Regex` has no equality of its own, so compare what it was compiled from
+        self.as_str() == other.as_str() && self.flags == other.flags
+    }
+}
+
+impl Eq for RegexLiteral {}
+
+impl std::hash::Hash for RegexLiteral {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+        self.flags.hash(state);
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct RegexParts {
+    pattern: String,
+    flags: String,
+}
+
+impl TryFrom<RegexParts> for RegexLiteral {
+    type Error = Error;
+
+    fn try_from(parts: RegexParts) -> Result<Self, Error> {
+        Self::new(&parts.pattern, &parts.flags)
+    }
+}
+
+impl From<RegexLiteral> for RegexParts {
+    fn from(literal: RegexLiteral) -> Self {
+        Self {
+            pattern: literal.as_str().to_owned(),
+            flags: literal.flags,
+        }
+    }
+}
+
+/// A value paired with the byte range of the expression it came from
+pub type Spanned<T> = (T, Range<usize>);
+
+pub fn lex(expression: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
+    let lexer = Token::lexer(expression);
+    let mut tokens = Vec::new();
+
+    for (token, span) in lexer.spanned() {
+        match token {
+            Ok(token) => tokens.push((token, span)),
+            Err(error) => {
+                let span = match error {
+                    // point at the offending part rather than the whole literal
+                    Error::UnknownRegexFlag { flag, offset } => {
+                        span.start + offset..span.start + offset + flag.len_utf8()
+                    }
+                    Error::InvalidEscape { ref escape, offset } => {
+                        span.start + offset..span.start + offset + escape.len()
+                    }
+                    _ => span,
+                };
+                return Err((error, span));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,298 @@
+use super::{Error, RegexLiteral, Token};
+use logos::Lexer;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) fn parse_duration(lexer: &mut Lexer<Token>) -> Result<Duration, Error> {
+    let mut overall = 0u128;
+    let mut current = 0u128;
+    // units have to be in descending order and can't repeat, e.g. `1h30m` but not `30m1h`
+    let mut previous = u128::MAX;
+    let mut chars = lexer.slice().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(digit) = c.to_digit(10) {
+            current = current * 10 + digit as u128;
+            if current > u64::MAX as u128 {
+                return Err(Error::Custom(String::from("duration is too large")));
+            }
+        } else {
+            // in nanoseconds
+            let multiplier = match (c, chars.peek()) {
+                ('m', Some('s')) => {
+                    chars.next();
+                    1_000_000
+                }
+                ('u', Some('s')) => {
+                    chars.next();
+                    1_000
+                }
+                ('n', Some('s')) => {
+                    chars.next();
+                    1
+                }
+                ('w', _) => 604_800_000_000_000,
+                ('d', _) => 86_400_000_000_000,
+                ('h', _) => 3_600_000_000_000,
+                ('m', _) => 60_000_000_000,
+                ('s', _) => 1_000_000_000,
+                _ => {
+                    return Err(Error::BadSyntax);
+                }
+            };
+            if multiplier >= previous {
+                return Err(Error::Custom(String::from(
+                    "duration units must be in descending order",
+                )));
+            }
+            previous = multiplier;
+            overall += current * multiplier;
+            current = 0;
+        }
+    }
+
+    u64::try_from(overall / 1_000_000_000)
+        .map(|seconds| Duration::new(seconds, (overall % 1_000_000_000) as u32))
+        .map_err(|_| Error::Custom(String::from("duration is too large")))
+}
+
+// days between 1970-01-01 and the given date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    // counting from March, so the leap day is at the end of the year
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse an RFC 3339 timestamp, e.g. `2023-08-01T12:30:00Z` or `2023-08-01T13:30:00.5+01:00`
+pub(super) fn parse_timestamp(lexer: &mut Lexer<Token>) -> Result<SystemTime, Error> {
+    let slice = lexer.slice();
+    let invalid = || Error::Custom(String::from("invalid timestamp"));
+    // the regex has already checked where the digits are
+    let number = |range: std::ops::Range<usize>| slice[range].parse::<i64>().unwrap();
+
+    let (year, month, day) = (number(0..4), number(5..7), number(8..10));
+    let (hour, minute, second) = (number(11..13), number(14..16), number(17..19));
+
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    // leap seconds aren't representable by `SystemTime`
+    if day < 1 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+
+    let mut rest = &slice[19..];
+    let mut nanos = 0u32;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.find(|c: char| !c.is_ascii_digit()).unwrap();
+        // anything past nanoseconds is truncated
+        for (i, digit) in fraction[..digits].chars().take(9).enumerate() {
+            nanos += digit.to_digit(10).unwrap() * 10u32.pow(8 - i as u32);
+        }
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let (hours, minutes) = (
+                number(slice.len() - 5..slice.len() - 3),
+                number(slice.len() - 2..slice.len()),
+            );
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if rest.starts_with('-') {
+                -offset
+            } else {
+                offset
+            }
+        }
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    let time = if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    };
+    time.and_then(|time| time.checked_add(Duration::from_nanos(nanos as u64)))
+        .ok_or_else(invalid)
+}
+
+fn find_closing_inner<'a>(
+    lexer: &'a mut Lexer<Token>,
+    find_tail: bool,
+    raw: bool,
+) -> Option<(&'a str, char, String, String)> {
+    let opening = lexer.slice().chars().last()?;
+    let mut body = String::new();
+    let mut tail = String::new();
+    let mut escaped = false;
+    let mut closed = false;
+    let mut chars = lexer.remainder().chars();
+
+    for char in &mut chars {
+        if escaped {
+            escaped = false;
+        } else if char == '\\' && !raw {
+            escaped = true;
+        } else if char == opening {
+            closed = true;
+            break;
+        }
+        body.push(char);
+    }
+
+    if !closed {
+        // consume the rest so the error span covers the unfinished literal
+        lexer.bump(body.len());
+        return None;
+    }
+
+    if find_tail {
+        for char in chars {
+            if char.is_ascii_lowercase() || char.is_ascii_uppercase() {
+                tail.push(char)
+            } else {
+                break;
+            }
+        }
+    }
+
+    // `+ 1` because neither `body` nor `tail` contains the middle delim
+    lexer.bump(body.len() + 1 + tail.len());
+
+    Some((lexer.slice(), opening, body, tail))
+}
+
+pub(super) fn find_closing(lexer: &mut Lexer<Token>) -> Result<String, Error> {
+    match find_closing_inner(lexer, false, false) {
+        // `+ 1` for the opening quote
+        Some((_, _, body, _)) => unescape(&body, 1),
+        None => Err(Error::Unfinished),
+    }
+}
+
+pub(super) fn find_closing_raw(lexer: &mut Lexer<Token>) -> Result<String, Error> {
+    match find_closing_inner(lexer, false, true) {
+        Some((_, _, body, _)) => Ok(body),
+        None => Err(Error::Unfinished),
+    }
+}
+
+// `offset` is where `body` starts in the literal, so errors can point at the bad escape
+fn unescape(body: &str, offset: usize) -> Result<String, Error> {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((start, char)) = chars.next() {
+        if char != '\\' {
+            value.push(char);
+            continue;
+        }
+
+        let mut end = start + 1;
+        let mut next = || {
+            let (i, char) = chars.next()?;
+            end = i + char.len_utf8();
+            Some(char)
+        };
+        let escaped = match next() {
+            Some('\\') => Some('\\'),
+            Some('\'') => Some('\''),
+            Some('"') => Some('"'),
+            Some('n') => Some('\n'),
+            Some('r') => Some('\r'),
+            Some('t') => Some('\t'),
+            Some('0') => Some('\0'),
+            Some('x') => {
+                let digits = [next(), next()];
+                digits
+                    .iter()
+                    .map(|digit| digit.and_then(|digit| digit.to_digit(16)))
+                    .try_fold(0, |value, digit| Some(value * 16 + digit?))
+                    // anything higher wouldn't be a whole UTF-8 character
+                    .filter(|value| *value <= 0x7f)
+                    .and_then(char::from_u32)
+            }
+            Some('u') if next() == Some('{') => {
+                let mut codepoint = Some(0);
+                let mut digits = 0;
+                loop {
+                    match next() {
+                        Some('}') if digits > 0 => break,
+                        Some(digit) if digit.is_ascii_hexdigit() && digits < 6 => {
+                            digits += 1;
+                            codepoint =
+                                codepoint.map(|value| value * 16 + digit.to_digit(16).unwrap());
+                        }
+                        _ => {
+                            codepoint = None;
+                            break;
+                        }
+                    }
+                }
+                codepoint.and_then(char::from_u32)
+            }
+            _ => None,
+        };
+
+        match escaped {
+            Some(escaped) => value.push(escaped),
+            None => {
+                return Err(Error::InvalidEscape {
+                    escape: body[start..end].to_owned(),
+                    offset: offset + start,
+                })
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+/// Quote and escape `value` so that lexing the result gives back `value`
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('\'');
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\0' => escaped.push_str("\\0"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('\'');
+    escaped
+}
+
+pub(super) fn compile_regex(lexer: &mut Lexer<Token>) -> Result<RegexLiteral, Error> {
+    match find_closing_inner(lexer, true, false) {
+        Some((slice, _, body, tail)) => {
+            RegexLiteral::new(&body, &tail).map_err(|error| match error {
+                // point at the flag from the start of the literal, not the start of the flags
+                Error::UnknownRegexFlag { flag, offset } => Error::UnknownRegexFlag {
+                    flag,
+                    offset: slice.len() - tail.len() + offset,
+                },
+                error => error,
+            })
+        }
+        None => Err(Error::Unfinished),
+    }
+}
@@ -0,0 +1,167 @@
+//! The lexer and parser of chert expressions, separate from `chert` so that `chert_derive`
+//! can parse expressions as it expands `expr!`
+
+pub mod diagnostic;
+pub mod lex;
+pub mod parse;
+
+use crate::parse::nodes::boolean::NodeBoolean;
+use crate::parse::Ast;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// The type of a variable, named as it is in expressions and errors
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Type {
+    Boolean,
+    Cidr,
+    Duration,
+    Float64,
+    Int64,
+    Ip,
+    Regex,
+    String,
+    Timestamp,
+    Uint64,
+    CidrList,
+    Int64List,
+    IpList,
+    StringList,
+    Uint64List,
+}
+
+impl Type {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Cidr => "cidr",
+            Self::Duration => "duration",
+            Self::Float64 => "float64",
+            Self::Int64 => "int64",
+            Self::Ip => "ip",
+            Self::Regex => "regex",
+            Self::String => "string",
+            Self::Timestamp => "timestamp",
+            Self::Uint64 => "uint64",
+            Self::CidrList => "[cidr]",
+            Self::Int64List => "[int64]",
+            Self::IpList => "[ip]",
+            Self::StringList => "[string]",
+            Self::Uint64List => "[uint64]",
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnknownType(pub String);
+
+impl std::fmt::Display for UnknownType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown type `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownType {}
+
+impl FromStr for Type {
+    type Err = UnknownType;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name {
+            "boolean" => Self::Boolean,
+            "cidr" => Self::Cidr,
+            "duration" => Self::Duration,
+            "float64" => Self::Float64,
+            "int64" => Self::Int64,
+            "ip" => Self::Ip,
+            "regex" => Self::Regex,
+            "string" => Self::String,
+            "timestamp" => Self::Timestamp,
+            "uint64" => Self::Uint64,
+            "[cidr]" => Self::CidrList,
+            "[int64]" => Self::Int64List,
+            "[ip]" => Self::IpList,
+            "[string]" => Self::StringList,
+            "[uint64]" => Self::Uint64List,
+            _ => return Err(UnknownType(name.to_owned())),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Lex(crate::lex::Error, Range<usize>),
+    Parse(crate::parse::Error),
+}
+
+impl ParseError {
+    /// The span of `expression` most responsible for this error
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Lex(_, span) => span.clone(),
+            Self::Parse(error) => error.span(),
+        }
+    }
+
+    /// Render this error against the expression it came from, with the offending
+    /// token(s) underlined
+    pub fn render(&self, expression: &str) -> String {
+        let secondary = match self {
+            Self::Lex(_, _) => Vec::new(),
+            Self::Parse(error) => error.secondary_spans(),
+        };
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            message.push_str(&format!(": {error}"));
+            source = error.source();
+        }
+        crate::diagnostic::render(expression, &message, &self.span(), &secondary)
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lex(error, _) => write!(f, "{error}"),
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        // transparent; `Display` already shows the inner error
+        match self {
+            Self::Lex(error, _) => error.source(),
+            Self::Parse(error) => error.source(),
+        }
+    }
+}
+
+impl From<(crate::lex::Error, Range<usize>)> for ParseError {
+    fn from((error, span): (crate::lex::Error, Range<usize>)) -> Self {
+        Self::Lex(error, span)
+    }
+}
+
+impl From<crate::parse::Error> for ParseError {
+    fn from(value: crate::parse::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// Lex and parse `expression`, looking the type of each variable up by name in `types`
+pub fn parse<T>(
+    expression: &str,
+    types: impl Fn(&str) -> Option<Type>,
+) -> Result<Ast<T, NodeBoolean>, ParseError> {
+    let tokens = crate::lex::lex(expression)?;
+    Ok(crate::parse::parse_boolean(tokens, types)?)
+}
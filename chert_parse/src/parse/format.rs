@@ -0,0 +1,776 @@
+use super::nodes::boolean::{
+    NodeBoolean, NodeBooleanAll, NodeBooleanAny, NodeBooleanBoth, NodeBooleanEither,
+    NodeBooleanEquals, NodeBooleanGreaterThan, NodeBooleanGreaterThanOrEquals, NodeBooleanLessThan,
+    NodeBooleanLessThanOrEquals, NodeBooleanMatches, NodeBooleanNot, NodeBooleanNotEquals,
+    NodeBooleanWithin,
+};
+use super::nodes::cidr::NodeCidr;
+use super::nodes::duration::{
+    NodeDuration, NodeDurationAdd, NodeDurationDivide, NodeDurationMultiply, NodeDurationSubtract,
+};
+use super::nodes::float64::{
+    NodeFloat64, NodeFloat64Add, NodeFloat64Cast, NodeFloat64Divide, NodeFloat64Exponent,
+    NodeFloat64Modulo, NodeFloat64Multiply, NodeFloat64Negative, NodeFloat64Subtract,
+};
+use super::nodes::int64::{
+    NodeInt64, NodeInt64Add, NodeInt64Cast, NodeInt64Divide, NodeInt64Exponent, NodeInt64Modulo,
+    NodeInt64Multiply, NodeInt64Negative, NodeInt64Subtract,
+};
+use super::nodes::ip::NodeIp;
+use super::nodes::list::{NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64};
+use super::nodes::regex::NodeRegex;
+use super::nodes::string::{NodeString, NodeStringAdd};
+use super::nodes::timestamp::{NodeTimestamp, NodeTimestampAdd, NodeTimestampSubtract};
+use super::nodes::uint64::{
+    NodeUint64, NodeUint64Add, NodeUint64Divide, NodeUint64Exponent, NodeUint64Length,
+    NodeUint64Modulo, NodeUint64Multiply, NodeUint64Subtract,
+};
+use super::operators::{Associativity, BinaryOperator, Operator, UnaryOperator};
+use crate::lex::RegexLiteral;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Specificity of something that never needs parentheses, higher than any operator
+const OPERAND: u16 = u8::MAX as u16 + 1;
+
+/// Expression text and the specificity of its outermost operator
+struct Formatted {
+    text: String,
+    specificity: u16,
+}
+
+impl Formatted {
+    fn operand(text: String) -> Self {
+        Self {
+            text,
+            specificity: OPERAND,
+        }
+    }
+
+    /// Wrap in parentheses if the outermost operator is less specific than `specificity`
+    fn parenthesised_below(self, specificity: u16) -> String {
+        if self.specificity < specificity {
+            format!("({})", self.text)
+        } else {
+            self.text
+        }
+    }
+}
+
+fn unary(operator: UnaryOperator, node: Formatted) -> Formatted {
+    let specificity = Operator::Unary(operator.clone()).specificity() as u16;
+    Formatted {
+        text: format!("{operator}{}", node.parenthesised_below(specificity)),
+        specificity,
+    }
+}
+
+fn binary(operator: BinaryOperator, left: Formatted, right: Formatted) -> Formatted {
+    let wrapped = Operator::Binary(operator.clone());
+    let specificity = wrapped.specificity() as u16;
+    // an operand with the same specificity only goes without parentheses on the side the
+    // operator associates towards, e.g. `a - b - c` but `a - (b - c)`
+    let (left, right) = match wrapped.associativity() {
+        Associativity::Left => (
+            left.parenthesised_below(specificity),
+            right.parenthesised_below(specificity + 1),
+        ),
+        Associativity::Right => (
+            left.parenthesised_below(specificity + 1),
+            right.parenthesised_below(specificity),
+        ),
+    };
+    Formatted {
+        text: format!("{left} {operator} {right}"),
+        specificity,
+    }
+}
+
+fn format_float64_constant(value: f64) -> Formatted {
+    // `Display` for `f64` never uses an exponent, but does leave off `.0`, which would make
+    // this a uint64 when parsed again. NaN and infinities have no literal
+    let mut text = value.to_string();
+    if value.is_finite() && !text.contains('.') {
+        text.push_str(".0");
+    }
+    if value.is_sign_negative() {
+        Formatted {
+            text,
+            specificity: Operator::Unary(UnaryOperator::Negative).specificity() as u16,
+        }
+    } else {
+        Formatted::operand(text)
+    }
+}
+
+fn format_duration_constant(value: &Duration) -> String {
+    const UNITS: [(&str, u128); 8] = [
+        ("w", 604_800_000_000_000),
+        ("d", 86_400_000_000_000),
+        ("h", 3_600_000_000_000),
+        ("m", 60_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ];
+
+    let mut nanos = value.as_nanos();
+    if nanos == 0 {
+        return String::from("0s");
+    }
+    let mut text = String::new();
+    for (unit, size) in UNITS {
+        if nanos >= size {
+            text.push_str(&format!("{}{unit}", nanos / size));
+            nanos %= size;
+        }
+    }
+    text
+}
+
+// the inverse of `days_from_civil` in the lexer
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // counting from March
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn format_timestamp_constant(value: &SystemTime) -> String {
+    let (seconds, nanos) = match value.duration_since(UNIX_EPOCH) {
+        Ok(duration) => (duration.as_secs() as i64, duration.subsec_nanos()),
+        Err(error) => {
+            let duration = error.duration();
+            let seconds = -(duration.as_secs() as i64);
+            match duration.subsec_nanos() {
+                0 => (seconds, 0),
+                nanos => (seconds - 1, 1_000_000_000 - nanos),
+            }
+        }
+    };
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let seconds = seconds.rem_euclid(86_400);
+    let mut text = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if nanos > 0 {
+        text.push_str(format!(".{nanos:09}").trim_end_matches('0'));
+    }
+    text.push('Z');
+    text
+}
+
+// IPv6 addresses are written out in full, as the lexer doesn't accept `::` at the start
+fn format_ip(value: &IpAddr) -> String {
+    match value {
+        IpAddr::V4(value) => value.to_string(),
+        IpAddr::V6(value) => value
+            .segments()
+            .iter()
+            .map(|segment| format!("{segment:x}"))
+            .collect::<Vec<_>>()
+            .join(":"),
+    }
+}
+
+fn format_regex_constant(value: &RegexLiteral) -> String {
+    let pattern = value.as_str();
+    let flags = value.flags();
+    // `/` is conventional, but any delimiter the pattern doesn't use will do
+    match ['/', '|', '#', '!', '%', ';', '@', '=']
+        .iter()
+        .find(|delimiter| !pattern.contains(**delimiter))
+    {
+        Some(delimiter) => format!("m{delimiter}{pattern}{delimiter}{flags}"),
+        // a backslash before the delimiter is kept in the pattern, but it still means the same
+        None => format!("m/{}/{flags}", pattern.replace('/', "\\/")),
+    }
+}
+
+fn format_boolean(node: &NodeBoolean) -> Formatted {
+    match node {
+        NodeBoolean::Variable { name } => Formatted::operand(name.clone()),
+        NodeBoolean::Constant(value) => Formatted::operand(value.to_string()),
+        NodeBoolean::Exists { name } => Formatted::operand(format!("exists({name})")),
+        NodeBoolean::Not(NodeBooleanNot::Boolean(node)) => {
+            unary(UnaryOperator::Not, format_boolean(node))
+        }
+        NodeBoolean::Both(NodeBooleanBoth::BooleanBoolean { left, right }) => binary(
+            BinaryOperator::Both,
+            format_boolean(left),
+            format_boolean(right),
+        ),
+        NodeBoolean::Either(NodeBooleanEither::BooleanBoolean { left, right }) => binary(
+            BinaryOperator::Either,
+            format_boolean(left),
+            format_boolean(right),
+        ),
+        NodeBoolean::Within(node) => {
+            let (left, right) = match node {
+                NodeBooleanWithin::IpCidr { left, right } => {
+                    (format_ip_node(left), format_cidr(right))
+                }
+                NodeBooleanWithin::IpCidrList { left, right } => {
+                    (format_ip_node(left), format_cidr_list(right))
+                }
+                NodeBooleanWithin::IpIpList { left, right } => {
+                    (format_ip_node(left), format_ip_list(right))
+                }
+                NodeBooleanWithin::Int64Int64List { left, right } => {
+                    (format_int64(left), format_int64_list(right))
+                }
+                NodeBooleanWithin::StringStringList { left, right } => {
+                    (format_string(left), format_string_list(right))
+                }
+                NodeBooleanWithin::Uint64Uint64List { left, right } => {
+                    (format_uint64(left), format_uint64_list(right))
+                }
+            };
+            binary(BinaryOperator::Within, left, right)
+        }
+        NodeBoolean::Any(node) => {
+            let (list, element, predicate) = match node {
+                NodeBooleanAny::CidrList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_cidr_list(list), element, predicate),
+                NodeBooleanAny::Int64List {
+                    list,
+                    element,
+                    predicate,
+                } => (format_int64_list(list), element, predicate),
+                NodeBooleanAny::IpList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_ip_list(list), element, predicate),
+                NodeBooleanAny::StringList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_string_list(list), element, predicate),
+                NodeBooleanAny::Uint64List {
+                    list,
+                    element,
+                    predicate,
+                } => (format_uint64_list(list), element, predicate),
+            };
+            quantifier("any", list, element, predicate)
+        }
+        NodeBoolean::All(node) => {
+            let (list, element, predicate) = match node {
+                NodeBooleanAll::CidrList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_cidr_list(list), element, predicate),
+                NodeBooleanAll::Int64List {
+                    list,
+                    element,
+                    predicate,
+                } => (format_int64_list(list), element, predicate),
+                NodeBooleanAll::IpList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_ip_list(list), element, predicate),
+                NodeBooleanAll::StringList {
+                    list,
+                    element,
+                    predicate,
+                } => (format_string_list(list), element, predicate),
+                NodeBooleanAll::Uint64List {
+                    list,
+                    element,
+                    predicate,
+                } => (format_uint64_list(list), element, predicate),
+            };
+            quantifier("all", list, element, predicate)
+        }
+        NodeBoolean::Matches(NodeBooleanMatches::StringRegex { left, right }) => binary(
+            BinaryOperator::Matches,
+            format_string(left),
+            format_regex(right),
+        ),
+        NodeBoolean::Equals(node) => {
+            let (left, right) = match node {
+                NodeBooleanEquals::BooleanBoolean { left, right } => {
+                    (format_boolean(left), format_boolean(right))
+                }
+                NodeBooleanEquals::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanEquals::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanEquals::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanEquals::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanEquals::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanEquals::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanEquals::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::Equals, left, right)
+        }
+        NodeBoolean::NotEquals(node) => {
+            let (left, right) = match node {
+                NodeBooleanNotEquals::BooleanBoolean { left, right } => {
+                    (format_boolean(left), format_boolean(right))
+                }
+                NodeBooleanNotEquals::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanNotEquals::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanNotEquals::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanNotEquals::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanNotEquals::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanNotEquals::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanNotEquals::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::NotEquals, left, right)
+        }
+        NodeBoolean::LessThan(node) => {
+            let (left, right) = match node {
+                NodeBooleanLessThan::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanLessThan::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanLessThan::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanLessThan::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanLessThan::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanLessThan::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanLessThan::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::LessThan, left, right)
+        }
+        NodeBoolean::LessThanOrEquals(node) => {
+            let (left, right) = match node {
+                NodeBooleanLessThanOrEquals::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanLessThanOrEquals::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanLessThanOrEquals::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanLessThanOrEquals::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanLessThanOrEquals::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanLessThanOrEquals::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanLessThanOrEquals::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::LessThanOrEquals, left, right)
+        }
+        NodeBoolean::GreaterThan(node) => {
+            let (left, right) = match node {
+                NodeBooleanGreaterThan::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanGreaterThan::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanGreaterThan::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanGreaterThan::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanGreaterThan::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanGreaterThan::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanGreaterThan::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::GreaterThan, left, right)
+        }
+        NodeBoolean::GreaterThanOrEquals(node) => {
+            let (left, right) = match node {
+                NodeBooleanGreaterThanOrEquals::StringString { left, right } => {
+                    (format_string(left), format_string(right))
+                }
+                NodeBooleanGreaterThanOrEquals::Uint64Uint64 { left, right } => {
+                    (format_uint64(left), format_uint64(right))
+                }
+                NodeBooleanGreaterThanOrEquals::Int64Int64 { left, right } => {
+                    (format_int64(left), format_int64(right))
+                }
+                NodeBooleanGreaterThanOrEquals::IpIp { left, right } => {
+                    (format_ip_node(left), format_ip_node(right))
+                }
+                NodeBooleanGreaterThanOrEquals::Float64Float64 { left, right } => {
+                    (format_float64(left), format_float64(right))
+                }
+                NodeBooleanGreaterThanOrEquals::DurationDuration { left, right } => {
+                    (format_duration(left), format_duration(right))
+                }
+                NodeBooleanGreaterThanOrEquals::TimestampTimestamp { left, right } => {
+                    (format_timestamp(left), format_timestamp(right))
+                }
+            };
+            binary(BinaryOperator::GreaterThanOrEquals, left, right)
+        }
+    }
+}
+
+fn format_uint64(node: &NodeUint64) -> Formatted {
+    match node {
+        NodeUint64::Variable { name } => Formatted::operand(name.clone()),
+        NodeUint64::Constant(value) => Formatted::operand(value.to_string()),
+        NodeUint64::Add(NodeUint64Add::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Add,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Subtract(NodeUint64Subtract::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Subtract,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Multiply(NodeUint64Multiply::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Multiply,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Divide(NodeUint64Divide::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Divide,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Modulo(NodeUint64Modulo::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Modulo,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Exponent(NodeUint64Exponent::Uint64Uint64 { left, right }) => binary(
+            BinaryOperator::Exponent,
+            format_uint64(left),
+            format_uint64(right),
+        ),
+        NodeUint64::Length(node) => {
+            let list = match node {
+                NodeUint64Length::CidrList(node) => format_cidr_list(node),
+                NodeUint64Length::Int64List(node) => format_int64_list(node),
+                NodeUint64Length::IpList(node) => format_ip_list(node),
+                NodeUint64Length::StringList(node) => format_string_list(node),
+                NodeUint64Length::Uint64List(node) => format_uint64_list(node),
+            };
+            Formatted::operand(format!("len({})", list.text))
+        }
+    }
+}
+
+fn format_int64(node: &NodeInt64) -> Formatted {
+    match node {
+        NodeInt64::Variable { name } => Formatted::operand(name.clone()),
+        NodeInt64::Constant(value) if *value < 0 => Formatted {
+            text: value.to_string(),
+            specificity: Operator::Unary(UnaryOperator::Negative).specificity() as u16,
+        },
+        NodeInt64::Constant(value) => Formatted::operand(value.to_string()),
+        NodeInt64::Negative(NodeInt64Negative::Uint64(node)) => {
+            unary(UnaryOperator::Negative, format_uint64(node))
+        }
+        NodeInt64::Negative(NodeInt64Negative::Int64(node)) => {
+            unary(UnaryOperator::Negative, format_int64(node))
+        }
+        // promotion happens again when this is parsed
+        NodeInt64::Cast(NodeInt64Cast::Uint64(node)) => format_uint64(node),
+        NodeInt64::Add(NodeInt64Add::Int64Int64 { left, right }) => {
+            binary(BinaryOperator::Add, format_int64(left), format_int64(right))
+        }
+        NodeInt64::Subtract(NodeInt64Subtract::Int64Int64 { left, right }) => binary(
+            BinaryOperator::Subtract,
+            format_int64(left),
+            format_int64(right),
+        ),
+        NodeInt64::Multiply(NodeInt64Multiply::Int64Int64 { left, right }) => binary(
+            BinaryOperator::Multiply,
+            format_int64(left),
+            format_int64(right),
+        ),
+        NodeInt64::Divide(NodeInt64Divide::Int64Int64 { left, right }) => binary(
+            BinaryOperator::Divide,
+            format_int64(left),
+            format_int64(right),
+        ),
+        NodeInt64::Modulo(NodeInt64Modulo::Int64Int64 { left, right }) => binary(
+            BinaryOperator::Modulo,
+            format_int64(left),
+            format_int64(right),
+        ),
+        NodeInt64::Exponent(NodeInt64Exponent::Int64Uint64 { left, right }) => binary(
+            BinaryOperator::Exponent,
+            format_int64(left),
+            format_uint64(right),
+        ),
+    }
+}
+
+fn format_float64(node: &NodeFloat64) -> Formatted {
+    match node {
+        NodeFloat64::Variable { name } => Formatted::operand(name.clone()),
+        NodeFloat64::Constant(value) => format_float64_constant(*value),
+        NodeFloat64::Negative(NodeFloat64Negative::Float64(node)) => {
+            unary(UnaryOperator::Negative, format_float64(node))
+        }
+        // promotion happens again when this is parsed
+        NodeFloat64::Cast(NodeFloat64Cast::Uint64(node)) => format_uint64(node),
+        NodeFloat64::Cast(NodeFloat64Cast::Int64(node)) => format_int64(node),
+        NodeFloat64::Add(NodeFloat64Add::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Add,
+            format_float64(left),
+            format_float64(right),
+        ),
+        NodeFloat64::Subtract(NodeFloat64Subtract::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Subtract,
+            format_float64(left),
+            format_float64(right),
+        ),
+        NodeFloat64::Multiply(NodeFloat64Multiply::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Multiply,
+            format_float64(left),
+            format_float64(right),
+        ),
+        NodeFloat64::Divide(NodeFloat64Divide::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Divide,
+            format_float64(left),
+            format_float64(right),
+        ),
+        NodeFloat64::Modulo(NodeFloat64Modulo::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Modulo,
+            format_float64(left),
+            format_float64(right),
+        ),
+        NodeFloat64::Exponent(NodeFloat64Exponent::Float64Float64 { left, right }) => binary(
+            BinaryOperator::Exponent,
+            format_float64(left),
+            format_float64(right),
+        ),
+    }
+}
+
+fn format_duration(node: &NodeDuration) -> Formatted {
+    match node {
+        NodeDuration::Variable { name } => Formatted::operand(name.clone()),
+        NodeDuration::Constant(value) => Formatted::operand(format_duration_constant(value)),
+        NodeDuration::Add(NodeDurationAdd::DurationDuration { left, right }) => binary(
+            BinaryOperator::Add,
+            format_duration(left),
+            format_duration(right),
+        ),
+        NodeDuration::Subtract(NodeDurationSubtract::DurationDuration { left, right }) => binary(
+            BinaryOperator::Subtract,
+            format_duration(left),
+            format_duration(right),
+        ),
+        NodeDuration::Subtract(NodeDurationSubtract::TimestampTimestamp { left, right }) => binary(
+            BinaryOperator::Subtract,
+            format_timestamp(left),
+            format_timestamp(right),
+        ),
+        NodeDuration::Multiply(NodeDurationMultiply::DurationUint64 { left, right }) => binary(
+            BinaryOperator::Multiply,
+            format_duration(left),
+            format_uint64(right),
+        ),
+        NodeDuration::Multiply(NodeDurationMultiply::Uint64Duration { left, right }) => binary(
+            BinaryOperator::Multiply,
+            format_uint64(left),
+            format_duration(right),
+        ),
+        NodeDuration::Divide(NodeDurationDivide::DurationUint64 { left, right }) => binary(
+            BinaryOperator::Divide,
+            format_duration(left),
+            format_uint64(right),
+        ),
+    }
+}
+
+fn format_timestamp(node: &NodeTimestamp) -> Formatted {
+    match node {
+        NodeTimestamp::Variable { name } => Formatted::operand(name.clone()),
+        NodeTimestamp::Constant(value) => Formatted::operand(format_timestamp_constant(value)),
+        NodeTimestamp::Now => Formatted::operand(String::from("now")),
+        NodeTimestamp::Add(NodeTimestampAdd::TimestampDuration { left, right }) => binary(
+            BinaryOperator::Add,
+            format_timestamp(left),
+            format_duration(right),
+        ),
+        NodeTimestamp::Add(NodeTimestampAdd::DurationTimestamp { left, right }) => binary(
+            BinaryOperator::Add,
+            format_duration(left),
+            format_timestamp(right),
+        ),
+        NodeTimestamp::Subtract(NodeTimestampSubtract::TimestampDuration { left, right }) => {
+            binary(
+                BinaryOperator::Subtract,
+                format_timestamp(left),
+                format_duration(right),
+            )
+        }
+    }
+}
+
+fn format_string(node: &NodeString) -> Formatted {
+    match node {
+        NodeString::Variable { name } => Formatted::operand(name.clone()),
+        NodeString::Constant(value) => Formatted::operand(crate::lex::escape(value)),
+        NodeString::Add(NodeStringAdd::StringString { left, right }) => binary(
+            BinaryOperator::Add,
+            format_string(left),
+            format_string(right),
+        ),
+    }
+}
+
+fn format_ip_node(node: &NodeIp) -> Formatted {
+    match node {
+        NodeIp::Variable { name } => Formatted::operand(name.clone()),
+        NodeIp::Constant(value) => Formatted::operand(format_ip(value)),
+    }
+}
+
+fn format_cidr(node: &NodeCidr) -> Formatted {
+    match node {
+        NodeCidr::Variable { name } => Formatted::operand(name.clone()),
+        // always with the length, or it would be parsed as an IP address
+        NodeCidr::Constant(value) => Formatted::operand(format!(
+            "{}/{}",
+            format_ip(&value.first_address()),
+            value.network_length()
+        )),
+    }
+}
+
+fn format_regex(node: &NodeRegex) -> Formatted {
+    match node {
+        NodeRegex::Variable { name } => Formatted::operand(name.clone()),
+        NodeRegex::Constant(value) => Formatted::operand(format_regex_constant(value)),
+    }
+}
+
+/// `[a, b]`, where elements are separated by commas and so never need parentheses
+fn list(elements: impl Iterator<Item = Formatted>) -> Formatted {
+    Formatted::operand(format!(
+        "[{}]",
+        elements
+            .map(|element| element.text)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+fn format_cidr_list(node: &NodeListCidr) -> Formatted {
+    match node {
+        NodeListCidr::Variable { name } => Formatted::operand(name.clone()),
+        NodeListCidr::Literal(elements) => list(elements.iter().map(format_cidr)),
+    }
+}
+
+fn format_int64_list(node: &NodeListInt64) -> Formatted {
+    match node {
+        NodeListInt64::Variable { name } => Formatted::operand(name.clone()),
+        NodeListInt64::Literal(elements) => list(elements.iter().map(format_int64)),
+    }
+}
+
+fn format_ip_list(node: &NodeListIp) -> Formatted {
+    match node {
+        NodeListIp::Variable { name } => Formatted::operand(name.clone()),
+        NodeListIp::Literal(elements) => list(elements.iter().map(format_ip_node)),
+    }
+}
+
+fn format_string_list(node: &NodeListString) -> Formatted {
+    match node {
+        NodeListString::Variable { name } => Formatted::operand(name.clone()),
+        NodeListString::Literal(elements) => list(elements.iter().map(format_string)),
+    }
+}
+
+fn format_uint64_list(node: &NodeListUint64) -> Formatted {
+    match node {
+        NodeListUint64::Variable { name } => Formatted::operand(name.clone()),
+        NodeListUint64::Literal(elements) => list(elements.iter().map(format_uint64)),
+    }
+}
+
+/// `any(list, element => predicate)` or `all(list, element => predicate)`
+fn quantifier(keyword: &str, list: Formatted, element: &str, predicate: &NodeBoolean) -> Formatted {
+    Formatted::operand(format!(
+        "{keyword}({}, {element} => {})",
+        list.text,
+        format_boolean(predicate).text
+    ))
+}
+
+/// Turn a node back in to expression text, with only the parentheses needed to parse back to
+/// the same node
+pub fn format(node: &NodeBoolean) -> String {
+    format_boolean(node).text
+}
+
+impl std::fmt::Display for NodeBoolean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format(self))
+    }
+}
@@ -0,0 +1,892 @@
+pub mod format;
+pub mod nodes;
+pub mod operators;
+pub mod shape;
+pub mod syntax;
+
+use self::nodes::boolean::{NodeBoolean, NodeBooleanAll, NodeBooleanAny};
+use self::nodes::cidr::NodeCidr;
+use self::nodes::duration::NodeDuration;
+use self::nodes::float64::NodeFloat64;
+use self::nodes::int64::NodeInt64;
+use self::nodes::ip::NodeIp;
+use self::nodes::list::{
+    NodeList, NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64,
+};
+use self::nodes::regex::NodeRegex;
+use self::nodes::string::NodeString;
+use self::nodes::timestamp::NodeTimestamp;
+use self::nodes::uint64::NodeUint64;
+use self::nodes::Node;
+use self::operators::{Associativity, BinaryOperator, Operator, ScopeOperator, UnaryOperator};
+use crate::lex::Token;
+use crate::Type;
+use std::ops::Range;
+
+enum Keyword {
+    Operand(Node),
+    Operator(Operator),
+    /// `any(list, element => predicate)` if false, `all(list, element => predicate)` if true
+    Quantifier(bool),
+}
+
+fn get_keyword(name: &str) -> Option<Keyword> {
+    Some(match name {
+        "true" => Keyword::Operand(Node::Boolean(NodeBoolean::Constant(true))),
+        "false" => Keyword::Operand(Node::Boolean(NodeBoolean::Constant(false))),
+        "now" => Keyword::Operand(Node::Timestamp(NodeTimestamp::Now)),
+        "null" => Keyword::Operand(Node::Null),
+        "exists" => Keyword::Operator(Operator::Unary(UnaryOperator::Exists)),
+        "len" => Keyword::Operator(Operator::Unary(UnaryOperator::Length)),
+        "any" => Keyword::Quantifier(false),
+        "all" => Keyword::Quantifier(true),
+        "and" => Keyword::Operator(Operator::Binary(BinaryOperator::Both)),
+        "or" => Keyword::Operator(Operator::Binary(BinaryOperator::Either)),
+        "in" => Keyword::Operator(Operator::Binary(BinaryOperator::Within)),
+        _ => {
+            return None;
+        }
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    UnknownIdentifier {
+        name: String,
+        span: Range<usize>,
+    },
+    BadBinaryOperands {
+        operator: BinaryOperator,
+        span: Range<usize>,
+        left: Box<Node>,
+        left_span: Range<usize>,
+        right: Box<Node>,
+        right_span: Range<usize>,
+    },
+    BadUnaryOperands {
+        operator: UnaryOperator,
+        span: Range<usize>,
+        node: Box<Node>,
+        node_span: Range<usize>,
+    },
+    UnknownBinaryOperator {
+        operator: String,
+        span: Range<usize>,
+    },
+    UnknownUnaryOperator {
+        operator: String,
+        span: Range<usize>,
+    },
+    InvalidNumber {
+        number: String,
+        span: Range<usize>,
+    },
+    MissingOperand {
+        span: Range<usize>,
+    },
+    Unfinished {
+        span: Range<usize>,
+    },
+    Empty {
+        span: Range<usize>,
+    },
+    NonexistentScopeClose {
+        span: Range<usize>,
+    },
+    UnclosedScope {
+        span: Range<usize>,
+    },
+    NotBoolean {
+        span: Range<usize>,
+    },
+    UnexpectedComma {
+        span: Range<usize>,
+    },
+    EmptyList {
+        span: Range<usize>,
+    },
+    BadListElement {
+        node: Box<Node>,
+        span: Range<usize>,
+    },
+    MixedList {
+        first: &'static str,
+        node: &'static str,
+        span: Range<usize>,
+    },
+    BadQuantifier {
+        span: Range<usize>,
+    },
+}
+
+impl Error {
+    /// The span of the token most responsible for this error
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::UnknownIdentifier { span, .. }
+            | Self::BadBinaryOperands { span, .. }
+            | Self::BadUnaryOperands { span, .. }
+            | Self::UnknownBinaryOperator { span, .. }
+            | Self::UnknownUnaryOperator { span, .. }
+            | Self::InvalidNumber { span, .. }
+            | Self::MissingOperand { span }
+            | Self::Unfinished { span }
+            | Self::Empty { span }
+            | Self::NonexistentScopeClose { span }
+            | Self::UnclosedScope { span }
+            | Self::NotBoolean { span }
+            | Self::UnexpectedComma { span }
+            | Self::EmptyList { span }
+            | Self::BadListElement { span, .. }
+            | Self::MixedList { span, .. }
+            | Self::BadQuantifier { span } => span.clone(),
+        }
+    }
+
+    /// Spans of any operands that contributed to this error
+    pub fn secondary_spans(&self) -> Vec<Range<usize>> {
+        match self {
+            Self::BadBinaryOperands {
+                left_span,
+                right_span,
+                ..
+            } => Vec::from([left_span.clone(), right_span.clone()]),
+            Self::BadUnaryOperands { node_span, .. } => Vec::from([node_span.clone()]),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownIdentifier { name, .. } => write!(f, "unknown identifier `{name}`"),
+            Self::BadBinaryOperands {
+                operator,
+                left,
+                right,
+                ..
+            } => write!(
+                f,
+                "`{operator}` cannot be applied to {} and {}",
+                left.type_name(),
+                right.type_name()
+            ),
+            Self::BadUnaryOperands { operator, node, .. } => {
+                write!(f, "`{operator}` cannot be applied to {}", node.type_name())
+            }
+            Self::UnknownBinaryOperator { operator, .. } => {
+                write!(f, "`{operator}` is not a binary operator")
+            }
+            Self::UnknownUnaryOperator { operator, .. } => {
+                write!(f, "`{operator}` is not a unary operator")
+            }
+            Self::InvalidNumber { number, .. } => write!(f, "`{number}` is not a valid number"),
+            Self::MissingOperand { .. } => write!(f, "operator is missing an operand"),
+            Self::Unfinished { .. } => write!(f, "operand is not joined by an operator"),
+            Self::Empty { .. } => write!(f, "expression is empty"),
+            Self::NonexistentScopeClose { .. } => write!(f, "unmatched closing parenthesis"),
+            Self::UnclosedScope { .. } => write!(f, "unclosed parenthesis"),
+            Self::NotBoolean { .. } => write!(f, "expression does not result in a boolean"),
+            Self::UnexpectedComma { .. } => write!(f, "comma outside of a list"),
+            Self::EmptyList { .. } => write!(f, "list is empty"),
+            Self::BadListElement { node, .. } => {
+                write!(f, "lists cannot contain {}", node.type_name())
+            }
+            Self::MixedList { first, node, .. } => {
+                write!(f, "lists cannot mix {first} and {node}")
+            }
+            Self::BadQuantifier { .. } => {
+                write!(
+                    f,
+                    "expected `any(list, element => predicate)` or `all(list, element => predicate)`"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// shunting yard time baby
+fn pop_ops<B: Builder>(
+    builder: &B,
+    new_operator: &Operator,
+    operators: &mut Vec<(Operator, Range<usize>)>,
+    operands: &mut Vec<(B::Operand, Range<usize>)>,
+) -> Result<(), Error> {
+    while let Some((operator, span)) = operators.pop() {
+        if match operator.associativity() {
+            Associativity::Left => operator.specificity() >= new_operator.specificity(),
+            Associativity::Right => operator.specificity() > new_operator.specificity(),
+        } {
+            match operator {
+                Operator::Scope(scope) => match scope {
+                    ScopeOperator::Open(_char) => {
+                        // leave it for the caller to match against a close
+                        operators.push((Operator::Scope(scope), span));
+                        break;
+                    }
+                    ScopeOperator::Close => {
+                        return Err(Error::NonexistentScopeClose { span });
+                    }
+                },
+                Operator::Binary(operator) => {
+                    let right = operands
+                        .pop()
+                        .ok_or_else(|| Error::MissingOperand { span: span.clone() })?;
+                    let left = operands
+                        .pop()
+                        .ok_or_else(|| Error::MissingOperand { span: span.clone() })?;
+                    let node_span = left.1.start..right.1.end;
+                    let node = builder.binary(operator, span, left, right)?;
+                    operands.push((node, node_span));
+                }
+                Operator::Unary(operator) => {
+                    let operand = operands
+                        .pop()
+                        .ok_or_else(|| Error::MissingOperand { span: span.clone() })?;
+                    let node_span = span.start..operand.1.end;
+                    let node = builder.unary(operator, span, operand)?;
+                    operands.push((node, node_span));
+                }
+            };
+        } else {
+            operators.push((operator, span));
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// A node for the variable called `name`
+fn variable(name: String, type_: Type) -> Node {
+    match type_ {
+        Type::Boolean => Node::Boolean(NodeBoolean::Variable { name }),
+        Type::Cidr => Node::Cidr(NodeCidr::Variable { name }),
+        Type::Duration => Node::Duration(NodeDuration::Variable { name }),
+        Type::Float64 => Node::Float64(NodeFloat64::Variable { name }),
+        Type::Int64 => Node::Int64(NodeInt64::Variable { name }),
+        Type::Ip => Node::Ip(NodeIp::Variable { name }),
+        Type::String => Node::String(NodeString::Variable { name }),
+        Type::Timestamp => Node::Timestamp(NodeTimestamp::Variable { name }),
+        Type::Uint64 => Node::Uint64(NodeUint64::Variable { name }),
+        Type::Regex => Node::Regex(NodeRegex::Variable { name }),
+        Type::CidrList => Node::List(NodeList::Cidr(NodeListCidr::Variable { name })),
+        Type::Int64List => Node::List(NodeList::Int64(NodeListInt64::Variable { name })),
+        Type::IpList => Node::List(NodeList::Ip(NodeListIp::Variable { name })),
+        Type::StringList => Node::List(NodeList::String(NodeListString::Variable { name })),
+        Type::Uint64List => Node::List(NodeList::Uint64(NodeListUint64::Variable { name })),
+    }
+}
+
+/// A list literal out of `elements`, which aren't empty, where uint64 elements are promoted to
+/// int64 if any element is int64
+fn list(elements: Vec<(Node, Range<usize>)>) -> Result<NodeList, Error> {
+    let (first, _) = &elements[0];
+    let int64 = elements
+        .iter()
+        .any(|(node, _)| matches!(node, Node::Int64(_)));
+    let first_type = first.type_name();
+    let mut list = match first {
+        Node::Cidr(_) => NodeList::Cidr(NodeListCidr::Literal(Vec::new())),
+        Node::Ip(_) => NodeList::Ip(NodeListIp::Literal(Vec::new())),
+        Node::String(_) => NodeList::String(NodeListString::Literal(Vec::new())),
+        Node::Uint64(_) if !int64 => NodeList::Uint64(NodeListUint64::Literal(Vec::new())),
+        Node::Int64(_) | Node::Uint64(_) => NodeList::Int64(NodeListInt64::Literal(Vec::new())),
+        _ => {
+            let (node, span) = elements.into_iter().next().unwrap();
+            return Err(Error::BadListElement {
+                node: Box::new(node),
+                span,
+            });
+        }
+    };
+    for (node, span) in elements {
+        match (&mut list, node) {
+            (NodeList::Cidr(NodeListCidr::Literal(list)), Node::Cidr(node)) => list.push(node),
+            (NodeList::Ip(NodeListIp::Literal(list)), Node::Ip(node)) => list.push(node),
+            (NodeList::String(NodeListString::Literal(list)), Node::String(node)) => {
+                list.push(node)
+            }
+            (NodeList::Uint64(NodeListUint64::Literal(list)), Node::Uint64(node)) => {
+                list.push(node)
+            }
+            (NodeList::Int64(NodeListInt64::Literal(list)), Node::Int64(node)) => list.push(node),
+            (NodeList::Int64(NodeListInt64::Literal(list)), Node::Uint64(node)) => {
+                list.push(NodeInt64::from_uint64(node))
+            }
+            (_, node) => {
+                return Err(Error::MixedList {
+                    first: first_type,
+                    node: node.type_name(),
+                    span,
+                });
+            }
+        }
+    }
+    Ok(list)
+}
+
+/// What `parse_inner` builds its operands with. Which operators apply to which operands
+/// doesn't depend on the types of an expression's variables, only whether they can is, so
+/// that's all that's left to the builder
+trait Builder {
+    type Operand;
+    /// What a quantifier knows of its list's elements, for when its predicate names them
+    type Element;
+
+    fn constant(&self, node: Node) -> Self::Operand;
+    fn variable(&self, name: String, span: Range<usize>) -> Result<Self::Operand, Error>;
+    /// The element of a quantifier's list, named `name` by its predicate
+    fn element(&self, name: String, element: &Self::Element) -> Self::Operand;
+    fn unary(
+        &self,
+        operator: UnaryOperator,
+        span: Range<usize>,
+        operand: (Self::Operand, Range<usize>),
+    ) -> Result<Self::Operand, Error>;
+    fn binary(
+        &self,
+        operator: BinaryOperator,
+        span: Range<usize>,
+        left: (Self::Operand, Range<usize>),
+        right: (Self::Operand, Range<usize>),
+    ) -> Result<Self::Operand, Error>;
+    /// A list literal out of `elements`, which aren't empty
+    fn list(&self, elements: Vec<(Self::Operand, Range<usize>)>) -> Result<Self::Operand, Error>;
+    /// The elements of the list a quantifier is over, once its comma is reached
+    fn elements(&self, list: &(Self::Operand, Range<usize>)) -> Result<Self::Element, Error>;
+    fn quantifier(
+        &self,
+        all: bool,
+        list: (Self::Operand, Range<usize>),
+        element: String,
+        predicate: (Self::Operand, Range<usize>),
+    ) -> Result<Self::Operand, Error>;
+}
+
+/// Builds typed nodes, with the type of each variable looked up by name
+struct Typed<F>(F);
+
+impl<F: Fn(&str) -> Option<Type>> Builder for Typed<F> {
+    type Operand = Node;
+    type Element = Type;
+
+    fn constant(&self, node: Node) -> Node {
+        node
+    }
+
+    fn variable(&self, name: String, span: Range<usize>) -> Result<Node, Error> {
+        match (self.0)(&name) {
+            Some(type_) => Ok(variable(name, type_)),
+            None => Err(Error::UnknownIdentifier { name, span }),
+        }
+    }
+
+    fn element(&self, name: String, element: &Type) -> Node {
+        variable(name, *element)
+    }
+
+    fn unary(
+        &self,
+        operator: UnaryOperator,
+        span: Range<usize>,
+        (node, node_span): (Node, Range<usize>),
+    ) -> Result<Node, Error> {
+        operator
+            .to_node(node)
+            .map_err(|node| Error::BadUnaryOperands {
+                operator,
+                span,
+                node: Box::new(node),
+                node_span,
+            })
+    }
+
+    fn binary(
+        &self,
+        operator: BinaryOperator,
+        span: Range<usize>,
+        (left, left_span): (Node, Range<usize>),
+        (right, right_span): (Node, Range<usize>),
+    ) -> Result<Node, Error> {
+        operator.to_node(left, right).map_err(|operands| {
+            let (left, right) = *operands;
+            Error::BadBinaryOperands {
+                operator,
+                span,
+                left: Box::new(left),
+                left_span,
+                right: Box::new(right),
+                right_span,
+            }
+        })
+    }
+
+    fn list(&self, elements: Vec<(Node, Range<usize>)>) -> Result<Node, Error> {
+        list(elements).map(Node::List)
+    }
+
+    fn elements(&self, (list, span): &(Node, Range<usize>)) -> Result<Type, Error> {
+        match list {
+            Node::List(NodeList::Cidr(_)) => Ok(Type::Cidr),
+            Node::List(NodeList::Int64(_)) => Ok(Type::Int64),
+            Node::List(NodeList::Ip(_)) => Ok(Type::Ip),
+            Node::List(NodeList::String(_)) => Ok(Type::String),
+            Node::List(NodeList::Uint64(_)) => Ok(Type::Uint64),
+            _ => Err(Error::BadQuantifier { span: span.clone() }),
+        }
+    }
+
+    fn quantifier(
+        &self,
+        all: bool,
+        (list, _): (Node, Range<usize>),
+        element: String,
+        (predicate, predicate_span): (Node, Range<usize>),
+    ) -> Result<Node, Error> {
+        let Node::Boolean(predicate) = predicate else {
+            return Err(Error::NotBoolean {
+                span: predicate_span,
+            });
+        };
+        let Node::List(list) = list else {
+            unreachable!("the list was checked at the comma");
+        };
+        let predicate = Box::new(predicate);
+        Ok(Node::Boolean(if all {
+            NodeBoolean::All(match list {
+                NodeList::Cidr(list) => NodeBooleanAll::CidrList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Int64(list) => NodeBooleanAll::Int64List {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Ip(list) => NodeBooleanAll::IpList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::String(list) => NodeBooleanAll::StringList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Uint64(list) => NodeBooleanAll::Uint64List {
+                    list,
+                    element,
+                    predicate,
+                },
+            })
+        } else {
+            NodeBoolean::Any(match list {
+                NodeList::Cidr(list) => NodeBooleanAny::CidrList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Int64(list) => NodeBooleanAny::Int64List {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Ip(list) => NodeBooleanAny::IpList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::String(list) => NodeBooleanAny::StringList {
+                    list,
+                    element,
+                    predicate,
+                },
+                NodeList::Uint64(list) => NodeBooleanAny::Uint64List {
+                    list,
+                    element,
+                    predicate,
+                },
+            })
+        }))
+    }
+}
+
+/// What an open `(` or `[` belongs to
+enum Bracket<E> {
+    Group,
+    List {
+        /// How many operands there were before the list
+        height: usize,
+        /// How many elements have been separated by commas so far
+        count: usize,
+    },
+    Quantifier {
+        all: bool,
+        start: usize,
+        height: usize,
+        /// The list's elements, once the list has been parsed
+        element: Option<E>,
+        /// The name the predicate gives to each element, once it's been bound with `=>`
+        name: Option<String>,
+    },
+}
+
+/// How far through `element =>` a quantifier is, after the comma that ends its list
+enum Binder {
+    Name,
+    Arrow(String),
+}
+
+fn parse_inner<B: Builder>(
+    tokens: Vec<(Token, Range<usize>)>,
+    builder: &B,
+) -> Result<(B::Operand, Range<usize>), Error> {
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    let mut brackets = Vec::<Bracket<B::Element>>::new();
+    // an `any` or `all` that's waiting for its `(`
+    let mut quantifier: Option<(bool, Range<usize>)> = None;
+    // a quantifier that's naming its element
+    let mut binder = None;
+    let mut last_was_operand = false;
+    let end = tokens.last().map_or(0, |(_, span)| span.end);
+
+    for (token, span) in tokens {
+        if let Some((all, keyword_span)) = quantifier.take() {
+            match token {
+                Token::Space(_) => {
+                    quantifier = Some((all, keyword_span));
+                }
+                Token::ParenthesisOpen => {
+                    operators.push((Operator::Scope(ScopeOperator::Open('(')), span));
+                    brackets.push(Bracket::Quantifier {
+                        all,
+                        start: keyword_span.start,
+                        height: operands.len(),
+                        element: None,
+                        name: None,
+                    });
+                    last_was_operand = false;
+                }
+                _ => {
+                    return Err(Error::BadQuantifier { span: keyword_span });
+                }
+            }
+            continue;
+        }
+
+        if let Some(state) = binder.take() {
+            let Some(Bracket::Quantifier { start, name, .. }) = brackets.last_mut() else {
+                unreachable!("only a quantifier's comma starts a binder");
+            };
+            match (state, token) {
+                (state, Token::Space(_)) => binder = Some(state),
+                (Binder::Name, Token::Identifier(element))
+                    if !element.contains('.') && get_keyword(&element).is_none() =>
+                {
+                    binder = Some(Binder::Arrow(element));
+                }
+                (Binder::Arrow(element), Token::Arrow) => *name = Some(element),
+                _ => {
+                    return Err(Error::BadQuantifier {
+                        span: *start..span.end,
+                    })
+                }
+            }
+            continue;
+        }
+
+        let operand = match token {
+            Token::String(value) => {
+                Some(builder.constant(Node::String(NodeString::Constant(value))))
+            }
+            Token::Number(value) => Some(builder.constant(if value.contains('.') {
+                Node::Float64(NodeFloat64::Constant(value.parse().map_err(|_| {
+                    Error::InvalidNumber {
+                        number: value.clone(),
+                        span: span.clone(),
+                    }
+                })?))
+            } else {
+                Node::Uint64(NodeUint64::Constant(value.parse().map_err(|_| {
+                    Error::InvalidNumber {
+                        number: value.clone(),
+                        span: span.clone(),
+                    }
+                })?))
+            })),
+            Token::Duration(value) => {
+                Some(builder.constant(Node::Duration(NodeDuration::Constant(value))))
+            }
+            Token::Timestamp(value) => {
+                Some(builder.constant(Node::Timestamp(NodeTimestamp::Constant(value))))
+            }
+            Token::Ip(value) => Some(builder.constant(Node::Ip(NodeIp::Constant(value)))),
+            Token::Cidr(value) => Some(builder.constant(Node::Cidr(NodeCidr::Constant(value)))),
+            Token::Regex(value) => Some(builder.constant(Node::Regex(NodeRegex::Constant(value)))),
+            Token::Identifier(ref name) => {
+                let name = name.clone();
+                // the element a quantifier has named, shadowing any variable
+                let bound = brackets.iter().rev().find_map(|bracket| match bracket {
+                    Bracket::Quantifier {
+                        element: Some(element),
+                        name: Some(bound),
+                        ..
+                    } if *bound == name => Some(element),
+                    _ => None,
+                });
+                if let Some(keyword) = get_keyword(&name) {
+                    match keyword {
+                        Keyword::Operand(operand) => Some(builder.constant(operand)),
+                        Keyword::Operator(operator) => {
+                            pop_ops(builder, &operator, &mut operators, &mut operands)?;
+                            operators.push((operator, span.clone()));
+                            last_was_operand = false;
+                            None
+                        }
+                        Keyword::Quantifier(all) => {
+                            quantifier = Some((all, span.clone()));
+                            None
+                        }
+                    }
+                } else if let Some(element) = bound {
+                    Some(builder.element(name, element))
+                } else {
+                    Some(builder.variable(name, span.clone())?)
+                }
+            }
+            Token::ParenthesisOpen => {
+                last_was_operand = false;
+                operators.push((Operator::Scope(ScopeOperator::Open('(')), span.clone()));
+                brackets.push(Bracket::Group);
+                None
+            }
+            Token::BracketOpen => {
+                last_was_operand = false;
+                operators.push((Operator::Scope(ScopeOperator::Open('[')), span.clone()));
+                brackets.push(Bracket::List {
+                    height: operands.len(),
+                    count: 0,
+                });
+                None
+            }
+            Token::Comma => {
+                pop_ops(
+                    builder,
+                    &Operator::Scope(ScopeOperator::Close),
+                    &mut operators,
+                    &mut operands,
+                )?;
+                if !last_was_operand {
+                    return Err(Error::MissingOperand { span });
+                }
+                match brackets.last_mut() {
+                    Some(Bracket::List { height, count }) => {
+                        if operands.len() != *height + *count + 1 {
+                            return Err(Error::Unfinished { span });
+                        }
+                        *count += 1;
+                    }
+                    Some(Bracket::Quantifier {
+                        height,
+                        element: element @ None,
+                        ..
+                    }) => {
+                        if operands.len() != *height + 1 {
+                            return Err(Error::Unfinished { span });
+                        }
+                        *element = Some(builder.elements(operands.last().unwrap())?);
+                        binder = Some(Binder::Name);
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedComma { span });
+                    }
+                }
+                last_was_operand = false;
+                None
+            }
+            Token::ParenthesisClose => {
+                pop_ops(
+                    builder,
+                    &Operator::Scope(ScopeOperator::Close),
+                    &mut operators,
+                    &mut operands,
+                )?;
+                let (Some((Operator::Scope(ScopeOperator::Open('(')), open_span)), Some(bracket)) =
+                    (operators.pop(), brackets.pop())
+                else {
+                    return Err(Error::NonexistentScopeClose { span });
+                };
+                match bracket {
+                    Bracket::Quantifier {
+                        all,
+                        start,
+                        height,
+                        element: Some(_),
+                        name: Some(element),
+                    } if last_was_operand && operands.len() == height + 2 => {
+                        let predicate = operands.pop().unwrap();
+                        let list = operands.pop().unwrap();
+                        let node = builder.quantifier(all, list, element, predicate)?;
+                        operands.push((node, start..span.end));
+                    }
+                    Bracket::Quantifier { start, .. } => {
+                        return Err(Error::BadQuantifier {
+                            span: start..span.end,
+                        });
+                    }
+                    Bracket::Group => {
+                        // widen the enclosed operand's span to include the parentheses
+                        if let Some((_, operand_span)) = operands.last_mut() {
+                            if operand_span.start >= open_span.end {
+                                *operand_span = open_span.start..span.end;
+                            }
+                        }
+                    }
+                    Bracket::List { .. } => {
+                        return Err(Error::NonexistentScopeClose { span });
+                    }
+                }
+                last_was_operand = true;
+                None
+            }
+            Token::BracketClose => {
+                pop_ops(
+                    builder,
+                    &Operator::Scope(ScopeOperator::Close),
+                    &mut operators,
+                    &mut operands,
+                )?;
+                let (
+                    Some((Operator::Scope(ScopeOperator::Open('[')), open_span)),
+                    Some(Bracket::List { height, count }),
+                ) = (operators.pop(), brackets.pop())
+                else {
+                    return Err(Error::NonexistentScopeClose { span });
+                };
+                if last_was_operand {
+                    if operands.len() != height + count + 1 {
+                        return Err(Error::Unfinished { span });
+                    }
+                } else if count > 0 {
+                    return Err(Error::MissingOperand { span });
+                }
+                let span = open_span.start..span.end;
+                let elements = operands.split_off(height);
+                if elements.is_empty() {
+                    return Err(Error::EmptyList { span });
+                }
+                last_was_operand = true;
+                operands.push((builder.list(elements)?, span));
+                None
+            }
+            Token::Operator(operator) => {
+                let operator = if last_was_operand {
+                    Operator::Binary(BinaryOperator::parse(operator.as_str()).ok_or_else(|| {
+                        Error::UnknownBinaryOperator {
+                            operator,
+                            span: span.clone(),
+                        }
+                    })?)
+                } else {
+                    Operator::Unary(UnaryOperator::parse(operator.as_str()).ok_or_else(|| {
+                        Error::UnknownUnaryOperator {
+                            operator,
+                            span: span.clone(),
+                        }
+                    })?)
+                };
+                pop_ops(builder, &operator, &mut operators, &mut operands)?;
+                operators.push((operator, span.clone()));
+                last_was_operand = false;
+                None
+            }
+            Token::Arrow => {
+                return Err(Error::UnknownBinaryOperator {
+                    operator: String::from("=>"),
+                    span,
+                });
+            }
+            Token::Space(_) => None,
+        };
+        if let Some(operand) = operand {
+            last_was_operand = true;
+            operands.push((operand, span));
+        }
+    }
+
+    if let Some((_, span)) = quantifier {
+        return Err(Error::BadQuantifier { span });
+    }
+    if binder.is_some() {
+        let Some(Bracket::Quantifier { start, .. }) = brackets.last() else {
+            unreachable!("only a quantifier's comma starts a binder");
+        };
+        return Err(Error::BadQuantifier { span: *start..end });
+    }
+
+    pop_ops(
+        builder,
+        &Operator::Scope(ScopeOperator::Close),
+        &mut operators,
+        &mut operands,
+    )?;
+
+    if let Some((_, span)) = operators.pop() {
+        return Err(Error::UnclosedScope { span });
+    }
+
+    if let Some((root, span)) = operands.pop() {
+        if !operands.is_empty() {
+            Err(Error::Unfinished { span })
+        } else {
+            Ok((root, span))
+        }
+    } else {
+        Err(Error::Empty { span: end..end })
+    }
+}
+
+#[derive(Debug)]
+pub struct Ast<T, R> {
+    root: R,
+    _type: Option<T>,
+}
+
+impl<T, R> Ast<T, R> {
+    pub fn get_root(&self) -> &R {
+        &self.root
+    }
+    pub fn into_root(self) -> R {
+        self.root
+    }
+}
+
+fn boolean_root<T>((root, span): (Node, Range<usize>)) -> Result<Ast<T, NodeBoolean>, Error> {
+    if let Node::Boolean(root) = root {
+        Ok(Ast { root, _type: None })
+    } else {
+        Err(Error::NotBoolean { span })
+    }
+}
+
+/// Parse `tokens`, looking the type of each variable up by name in `types`
+pub fn parse<T>(
+    tokens: Vec<(Token, Range<usize>)>,
+    types: impl Fn(&str) -> Option<Type>,
+) -> Result<Ast<T, Node>, Error> {
+    let (root, _span) = parse_inner(tokens, &Typed(types))?;
+    Ok(Ast { root, _type: None })
+}
+
+/// Like `parse`, but the expression has to be boolean
+pub fn parse_boolean<T>(
+    tokens: Vec<(Token, Range<usize>)>,
+    types: impl Fn(&str) -> Option<Type>,
+) -> Result<Ast<T, NodeBoolean>, Error> {
+    boolean_root(parse_inner(tokens, &Typed(types))?)
+}
@@ -0,0 +1,351 @@
+use serde::{Deserialize, Serialize};
+
+use super::cidr::NodeCidr;
+use super::duration::NodeDuration;
+use super::float64::NodeFloat64;
+use super::int64::NodeInt64;
+use super::ip::NodeIp;
+use super::list::{NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64};
+use super::regex::NodeRegex;
+use super::string::NodeString;
+use super::timestamp::NodeTimestamp;
+use super::uint64::NodeUint64;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanWithin {
+    IpCidr {
+        left: NodeIp,
+        right: NodeCidr,
+    },
+    IpCidrList {
+        left: NodeIp,
+        right: NodeListCidr,
+    },
+    IpIpList {
+        left: NodeIp,
+        right: NodeListIp,
+    },
+    Int64Int64List {
+        left: NodeInt64,
+        right: NodeListInt64,
+    },
+    StringStringList {
+        left: NodeString,
+        right: NodeListString,
+    },
+    Uint64Uint64List {
+        left: NodeUint64,
+        right: NodeListUint64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanNot {
+    Boolean(Box<NodeBoolean>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanBoth {
+    BooleanBoolean {
+        left: Box<NodeBoolean>,
+        right: Box<NodeBoolean>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanEither {
+    BooleanBoolean {
+        left: Box<NodeBoolean>,
+        right: Box<NodeBoolean>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanEquals {
+    BooleanBoolean {
+        left: Box<NodeBoolean>,
+        right: Box<NodeBoolean>,
+    },
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanMatches {
+    StringRegex { left: NodeString, right: NodeRegex },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanNotEquals {
+    BooleanBoolean {
+        left: Box<NodeBoolean>,
+        right: Box<NodeBoolean>,
+    },
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanLessThan {
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanLessThanOrEquals {
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanGreaterThan {
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanGreaterThanOrEquals {
+    StringString {
+        left: NodeString,
+        right: NodeString,
+    },
+    Uint64Uint64 {
+        left: NodeUint64,
+        right: NodeUint64,
+    },
+    Int64Int64 {
+        left: NodeInt64,
+        right: NodeInt64,
+    },
+    IpIp {
+        left: NodeIp,
+        right: NodeIp,
+    },
+    Float64Float64 {
+        left: NodeFloat64,
+        right: NodeFloat64,
+    },
+    DurationDuration {
+        left: NodeDuration,
+        right: NodeDuration,
+    },
+    TimestampTimestamp {
+        left: NodeTimestamp,
+        right: NodeTimestamp,
+    },
+}
+
+/// `any(list, element => predicate)`, where `element` names each element of `list` in `predicate`
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanAny {
+    CidrList {
+        list: NodeListCidr,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    Int64List {
+        list: NodeListInt64,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    IpList {
+        list: NodeListIp,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    StringList {
+        list: NodeListString,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    Uint64List {
+        list: NodeListUint64,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+}
+
+/// `all(list, element => predicate)`, where `element` names each element of `list` in `predicate`
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBooleanAll {
+    CidrList {
+        list: NodeListCidr,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    Int64List {
+        list: NodeListInt64,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    IpList {
+        list: NodeListIp,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    StringList {
+        list: NodeListString,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+    Uint64List {
+        list: NodeListUint64,
+        element: String,
+        predicate: Box<NodeBoolean>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeBoolean {
+    Variable {
+        name: String,
+    },
+    Constant(bool),
+    /// Whether an optional variable has a value
+    Exists {
+        name: String,
+    },
+    Not(NodeBooleanNot),
+    Both(NodeBooleanBoth),
+    Either(NodeBooleanEither),
+    Within(NodeBooleanWithin),
+    Equals(NodeBooleanEquals),
+    Matches(NodeBooleanMatches),
+    NotEquals(NodeBooleanNotEquals),
+    LessThan(NodeBooleanLessThan),
+    LessThanOrEquals(NodeBooleanLessThanOrEquals),
+    GreaterThan(NodeBooleanGreaterThan),
+    GreaterThanOrEquals(NodeBooleanGreaterThanOrEquals),
+    Any(NodeBooleanAny),
+    All(NodeBooleanAll),
+}
@@ -1,7 +1,7 @@
 use cidr::IpCidr;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum NodeCidr {
     Variable { name: String },
     Constant(IpCidr),
@@ -0,0 +1,54 @@
+use super::timestamp::NodeTimestamp;
+use super::uint64::NodeUint64;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeDurationAdd {
+    DurationDuration {
+        left: Box<NodeDuration>,
+        right: Box<NodeDuration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeDurationSubtract {
+    DurationDuration {
+        left: Box<NodeDuration>,
+        right: Box<NodeDuration>,
+    },
+    TimestampTimestamp {
+        left: Box<NodeTimestamp>,
+        right: Box<NodeTimestamp>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeDurationMultiply {
+    DurationUint64 {
+        left: Box<NodeDuration>,
+        right: Box<NodeUint64>,
+    },
+    Uint64Duration {
+        left: Box<NodeUint64>,
+        right: Box<NodeDuration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeDurationDivide {
+    DurationUint64 {
+        left: Box<NodeDuration>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeDuration {
+    Variable { name: String },
+    Constant(Duration),
+    Add(NodeDurationAdd),
+    Subtract(NodeDurationSubtract),
+    Multiply(NodeDurationMultiply),
+    Divide(NodeDurationDivide),
+}
@@ -0,0 +1,138 @@
+use super::int64::NodeInt64;
+use super::uint64::NodeUint64;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Negative {
+    Float64(Box<NodeFloat64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Cast {
+    Uint64(Box<NodeUint64>),
+    Int64(Box<NodeInt64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Add {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Subtract {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Multiply {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Divide {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Modulo {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeFloat64Exponent {
+    Float64Float64 {
+        left: Box<NodeFloat64>,
+        right: Box<NodeFloat64>,
+    },
+}
+
+/// Arithmetic and comparisons follow IEEE 754, so division by zero is infinite (or NaN for
+/// `0.0 / 0.0`) and NaN is unequal to everything, including itself; only `!=` is true when
+/// either side of a comparison is NaN
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum NodeFloat64 {
+    Variable { name: String },
+    Constant(f64),
+    Negative(NodeFloat64Negative),
+    Cast(NodeFloat64Cast),
+    Add(NodeFloat64Add),
+    Subtract(NodeFloat64Subtract),
+    Multiply(NodeFloat64Multiply),
+    Divide(NodeFloat64Divide),
+    Modulo(NodeFloat64Modulo),
+    Exponent(NodeFloat64Exponent),
+}
+
+// constants are compared bitwise, so that a tree is equal to itself even with a NaN in it, and
+// `0.0` and `-0.0` (which divide differently) are different trees
+impl PartialEq for NodeFloat64 {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Variable { name: left }, Self::Variable { name: right }) => left == right,
+            (Self::Constant(left), Self::Constant(right)) => left.to_bits() == right.to_bits(),
+            (Self::Negative(left), Self::Negative(right)) => left == right,
+            (Self::Cast(left), Self::Cast(right)) => left == right,
+            (Self::Add(left), Self::Add(right)) => left == right,
+            (Self::Subtract(left), Self::Subtract(right)) => left == right,
+            (Self::Multiply(left), Self::Multiply(right)) => left == right,
+            (Self::Divide(left), Self::Divide(right)) => left == right,
+            (Self::Modulo(left), Self::Modulo(right)) => left == right,
+            (Self::Exponent(left), Self::Exponent(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for NodeFloat64 {}
+
+impl Hash for NodeFloat64 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Variable { name } => name.hash(state),
+            Self::Constant(value) => value.to_bits().hash(state),
+            Self::Negative(node) => node.hash(state),
+            Self::Cast(node) => node.hash(state),
+            Self::Add(node) => node.hash(state),
+            Self::Subtract(node) => node.hash(state),
+            Self::Multiply(node) => node.hash(state),
+            Self::Divide(node) => node.hash(state),
+            Self::Modulo(node) => node.hash(state),
+            Self::Exponent(node) => node.hash(state),
+        }
+    }
+}
+
+impl NodeFloat64 {
+    /// Promote a uint64 node to float64, as a constant if it is one
+    pub fn from_uint64(node: NodeUint64) -> Self {
+        match node {
+            NodeUint64::Constant(value) => Self::Constant(value as f64),
+            node => Self::Cast(NodeFloat64Cast::Uint64(Box::new(node))),
+        }
+    }
+
+    /// Promote an int64 node to float64, as a constant if it is one
+    pub fn from_int64(node: NodeInt64) -> Self {
+        match node {
+            NodeInt64::Constant(value) => Self::Constant(value as f64),
+            node => Self::Cast(NodeFloat64Cast::Int64(Box::new(node))),
+        }
+    }
+}
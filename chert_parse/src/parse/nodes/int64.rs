@@ -0,0 +1,87 @@
+use super::uint64::NodeUint64;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Negative {
+    Uint64(Box<NodeUint64>),
+    Int64(Box<NodeInt64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Cast {
+    Uint64(Box<NodeUint64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Add {
+    Int64Int64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeInt64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Subtract {
+    Int64Int64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeInt64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Multiply {
+    Int64Int64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeInt64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Divide {
+    Int64Int64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeInt64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Modulo {
+    Int64Int64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeInt64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64Exponent {
+    Int64Uint64 {
+        left: Box<NodeInt64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeInt64 {
+    Variable { name: String },
+    Constant(i64),
+    Negative(NodeInt64Negative),
+    Cast(NodeInt64Cast),
+    Add(NodeInt64Add),
+    Subtract(NodeInt64Subtract),
+    Multiply(NodeInt64Multiply),
+    Divide(NodeInt64Divide),
+    Modulo(NodeInt64Modulo),
+    Exponent(NodeInt64Exponent),
+}
+
+impl NodeInt64 {
+    /// Promote a uint64 node to int64, as a constant if it is one that fits
+    pub fn from_uint64(node: NodeUint64) -> Self {
+        if let NodeUint64::Constant(value) = node {
+            if let Ok(value) = i64::try_from(value) {
+                return Self::Constant(value);
+            }
+        }
+        Self::Cast(NodeInt64Cast::Uint64(Box::new(node)))
+    }
+}
@@ -0,0 +1,69 @@
+use super::cidr::NodeCidr;
+use super::int64::NodeInt64;
+use super::ip::NodeIp;
+use super::string::NodeString;
+use super::uint64::NodeUint64;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeListCidr {
+    Variable { name: String },
+    Literal(Vec<NodeCidr>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeListInt64 {
+    Variable { name: String },
+    Literal(Vec<NodeInt64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeListIp {
+    Variable { name: String },
+    Literal(Vec<NodeIp>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeListString {
+    Variable { name: String },
+    Literal(Vec<NodeString>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeListUint64 {
+    Variable { name: String },
+    Literal(Vec<NodeUint64>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeList {
+    Cidr(NodeListCidr),
+    Int64(NodeListInt64),
+    Ip(NodeListIp),
+    String(NodeListString),
+    Uint64(NodeListUint64),
+}
+
+impl NodeList {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Cidr(_) => "[cidr]",
+            Self::Int64(_) => "[int64]",
+            Self::Ip(_) => "[ip]",
+            Self::String(_) => "[string]",
+            Self::Uint64(_) => "[uint64]",
+        }
+    }
+}
+
+impl NodeListInt64 {
+    /// Promote a uint64 list to int64; only literals can be
+    pub fn from_uint64(node: NodeListUint64) -> Result<Self, NodeListUint64> {
+        match node {
+            NodeListUint64::Literal(elements) => Ok(Self::Literal(
+                elements.into_iter().map(NodeInt64::from_uint64).collect(),
+            )),
+            node => Err(node),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+pub mod boolean;
+pub mod cidr;
+pub mod duration;
+pub mod float64;
+pub mod int64;
+pub mod ip;
+pub mod list;
+pub mod regex;
+pub mod string;
+pub mod timestamp;
+pub mod uint64;
+
+use self::boolean::NodeBoolean;
+use self::cidr::NodeCidr;
+use self::duration::NodeDuration;
+use self::float64::NodeFloat64;
+use self::int64::NodeInt64;
+use self::ip::NodeIp;
+use self::list::{
+    NodeList, NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64,
+};
+use self::regex::NodeRegex;
+use self::string::NodeString;
+use self::timestamp::NodeTimestamp;
+use self::uint64::NodeUint64;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Node {
+    Boolean(NodeBoolean),
+    Cidr(NodeCidr),
+    Duration(NodeDuration),
+    Float64(NodeFloat64),
+    Int64(NodeInt64),
+    Ip(NodeIp),
+    List(NodeList),
+    Regex(NodeRegex),
+    String(NodeString),
+    Timestamp(NodeTimestamp),
+    Uint64(NodeUint64),
+    /// `null`, which is only meaningful compared to a variable
+    Null,
+}
+
+impl Node {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Boolean(_) => "boolean",
+            Self::Cidr(_) => "cidr",
+            Self::Duration(_) => "duration",
+            Self::Float64(_) => "float64",
+            Self::Int64(_) => "int64",
+            Self::Ip(_) => "ip",
+            Self::List(node) => node.type_name(),
+            Self::Regex(_) => "regex",
+            Self::String(_) => "string",
+            Self::Timestamp(_) => "timestamp",
+            Self::Uint64(_) => "uint64",
+            Self::Null => "null",
+        }
+    }
+
+    /// The name of the variable this node is, if it's just a variable
+    pub fn variable_name(&self) -> Option<&str> {
+        match self {
+            Self::Boolean(NodeBoolean::Variable { name })
+            | Self::Cidr(NodeCidr::Variable { name })
+            | Self::Duration(NodeDuration::Variable { name })
+            | Self::Float64(NodeFloat64::Variable { name })
+            | Self::Int64(NodeInt64::Variable { name })
+            | Self::Ip(NodeIp::Variable { name })
+            | Self::List(NodeList::Cidr(NodeListCidr::Variable { name }))
+            | Self::List(NodeList::Int64(NodeListInt64::Variable { name }))
+            | Self::List(NodeList::Ip(NodeListIp::Variable { name }))
+            | Self::List(NodeList::String(NodeListString::Variable { name }))
+            | Self::List(NodeList::Uint64(NodeListUint64::Variable { name }))
+            | Self::Regex(NodeRegex::Variable { name })
+            | Self::String(NodeString::Variable { name })
+            | Self::Timestamp(NodeTimestamp::Variable { name })
+            | Self::Uint64(NodeUint64::Variable { name }) => Some(name),
+            _ => None,
+        }
+    }
+}
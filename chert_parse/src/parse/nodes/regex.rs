@@ -0,0 +1,8 @@
+use crate::lex::RegexLiteral;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeRegex {
+    Variable { name: String },
+    Constant(RegexLiteral),
+}
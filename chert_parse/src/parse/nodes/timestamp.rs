@@ -0,0 +1,35 @@
+use super::duration::NodeDuration;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeTimestampAdd {
+    TimestampDuration {
+        left: Box<NodeTimestamp>,
+        right: Box<NodeDuration>,
+    },
+    DurationTimestamp {
+        left: Box<NodeDuration>,
+        right: Box<NodeTimestamp>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeTimestampSubtract {
+    TimestampDuration {
+        left: Box<NodeTimestamp>,
+        right: Box<NodeDuration>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeTimestamp {
+    Variable {
+        name: String,
+    },
+    Constant(SystemTime),
+    /// The time an `Engine` started evaluating, the same for every expression in one call
+    Now,
+    Add(NodeTimestampAdd),
+    Subtract(NodeTimestampSubtract),
+}
@@ -0,0 +1,72 @@
+use super::list::{NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Length {
+    CidrList(NodeListCidr),
+    Int64List(NodeListInt64),
+    IpList(NodeListIp),
+    StringList(NodeListString),
+    Uint64List(NodeListUint64),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Add {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Subtract {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Multiply {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Divide {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Modulo {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64Exponent {
+    Uint64Uint64 {
+        left: Box<NodeUint64>,
+        right: Box<NodeUint64>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum NodeUint64 {
+    Variable { name: String },
+    Constant(u64),
+    Add(NodeUint64Add),
+    Subtract(NodeUint64Subtract),
+    Multiply(NodeUint64Multiply),
+    Divide(NodeUint64Divide),
+    Modulo(NodeUint64Modulo),
+    Exponent(NodeUint64Exponent),
+    Length(NodeUint64Length),
+}
@@ -0,0 +1,888 @@
+use super::nodes::boolean::{
+    NodeBoolean, NodeBooleanBoth, NodeBooleanEither, NodeBooleanEquals, NodeBooleanGreaterThan,
+    NodeBooleanGreaterThanOrEquals, NodeBooleanLessThan, NodeBooleanLessThanOrEquals,
+    NodeBooleanMatches, NodeBooleanNot, NodeBooleanNotEquals, NodeBooleanWithin,
+};
+use super::nodes::duration::{
+    NodeDuration, NodeDurationAdd, NodeDurationDivide, NodeDurationMultiply, NodeDurationSubtract,
+};
+use super::nodes::float64::{
+    NodeFloat64, NodeFloat64Add, NodeFloat64Divide, NodeFloat64Exponent, NodeFloat64Modulo,
+    NodeFloat64Multiply, NodeFloat64Negative, NodeFloat64Subtract,
+};
+use super::nodes::int64::{
+    NodeInt64, NodeInt64Add, NodeInt64Divide, NodeInt64Exponent, NodeInt64Modulo,
+    NodeInt64Multiply, NodeInt64Negative, NodeInt64Subtract,
+};
+use super::nodes::list::{NodeList, NodeListInt64};
+use super::nodes::string::{NodeString, NodeStringAdd};
+use super::nodes::timestamp::{NodeTimestamp, NodeTimestampAdd, NodeTimestampSubtract};
+use super::nodes::uint64::{
+    NodeUint64, NodeUint64Add, NodeUint64Divide, NodeUint64Exponent, NodeUint64Length,
+    NodeUint64Modulo, NodeUint64Multiply, NodeUint64Subtract,
+};
+use super::nodes::Node;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnaryOperator {
+    Exists,
+    Length,
+    Negative,
+    Not,
+    Positive,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BinaryOperator {
+    Exponent,
+    Multiply,
+    Divide,
+    Modulo,
+    Both,
+    Either,
+    Add,
+    Subtract,
+    Within,
+    Equals,
+    NotEquals,
+    LessThan,
+    LessThanOrEquals,
+    GreaterThan,
+    GreaterThanOrEquals,
+    Matches,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScopeOperator {
+    Open(char),
+    Close,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operator {
+    Unary(UnaryOperator),
+    Binary(BinaryOperator),
+    Scope(ScopeOperator),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    UnknownOperator,
+    BadOperands,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownOperator => write!(f, "unknown operator"),
+            Self::BadOperands => write!(f, "operator cannot be applied to these operands"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Exists => "exists",
+                Self::Length => "len",
+                Self::Negative => "-",
+                Self::Not => "!",
+                Self::Positive => "+",
+            }
+        )
+    }
+}
+
+impl std::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Exponent => "**",
+                Self::Multiply => "*",
+                Self::Divide => "/",
+                Self::Modulo => "%",
+                Self::Both => "&&",
+                Self::Either => "||",
+                Self::Add => "+",
+                Self::Subtract => "-",
+                Self::Within => "in",
+                Self::Equals => "==",
+                Self::NotEquals => "!=",
+                Self::LessThan => "<",
+                Self::LessThanOrEquals => "<=",
+                Self::GreaterThan => ">",
+                Self::GreaterThanOrEquals => ">=",
+                Self::Matches => "~",
+            }
+        )
+    }
+}
+
+/// Integer operands mixed with float64 operands are promoted to float64
+fn promote_float64(left: Node, right: Node) -> (Node, Node) {
+    match (left, right) {
+        (Node::Uint64(left), Node::Float64(right)) => (
+            Node::Float64(NodeFloat64::from_uint64(left)),
+            Node::Float64(right),
+        ),
+        (Node::Int64(left), Node::Float64(right)) => (
+            Node::Float64(NodeFloat64::from_int64(left)),
+            Node::Float64(right),
+        ),
+        (Node::Float64(left), Node::Uint64(right)) => (
+            Node::Float64(left),
+            Node::Float64(NodeFloat64::from_uint64(right)),
+        ),
+        (Node::Float64(left), Node::Int64(right)) => (
+            Node::Float64(left),
+            Node::Float64(NodeFloat64::from_int64(right)),
+        ),
+        operands => operands,
+    }
+}
+
+/// Mixed uint64 and int64 operands are promoted to int64, and mixed integer and float64
+/// operands are promoted to float64
+fn promote(left: Node, right: Node) -> (Node, Node) {
+    match promote_float64(left, right) {
+        (Node::Uint64(left), Node::Int64(right)) => (
+            Node::Int64(NodeInt64::from_uint64(left)),
+            Node::Int64(right),
+        ),
+        (Node::Int64(left), Node::Uint64(right)) => (
+            Node::Int64(left),
+            Node::Int64(NodeInt64::from_uint64(right)),
+        ),
+        operands => operands,
+    }
+}
+
+impl UnaryOperator {
+    pub(crate) fn parse(token: &str) -> Option<Self> {
+        Some(match token {
+            "!" => Self::Not,
+            "+" => Self::Positive,
+            "-" => Self::Negative,
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    pub(crate) fn to_node(&self, node: Node) -> Result<Node, Node> {
+        Ok(match self {
+            Self::Exists => match node.variable_name() {
+                Some(name) => Node::Boolean(NodeBoolean::Exists {
+                    name: name.to_owned(),
+                }),
+                None => {
+                    return Err(node);
+                }
+            },
+            Self::Length => match node {
+                Node::List(node) => Node::Uint64(NodeUint64::Length(match node {
+                    NodeList::Cidr(node) => NodeUint64Length::CidrList(node),
+                    NodeList::Int64(node) => NodeUint64Length::Int64List(node),
+                    NodeList::Ip(node) => NodeUint64Length::IpList(node),
+                    NodeList::String(node) => NodeUint64Length::StringList(node),
+                    NodeList::Uint64(node) => NodeUint64Length::Uint64List(node),
+                })),
+                node => {
+                    return Err(node);
+                }
+            },
+            Self::Not => match node {
+                Node::Boolean(node) => {
+                    Node::Boolean(NodeBoolean::Not(NodeBooleanNot::Boolean(Box::new(node))))
+                }
+                node => {
+                    return Err(node);
+                }
+            },
+            Self::Positive => match node {
+                Node::Uint64(node) => Node::Uint64(node),
+                Node::Int64(node) => Node::Int64(node),
+                Node::Float64(node) => Node::Float64(node),
+                node => {
+                    return Err(node);
+                }
+            },
+            Self::Negative => match node {
+                Node::Uint64(NodeUint64::Constant(value))
+                    if 0i64.checked_sub_unsigned(value).is_some() =>
+                {
+                    Node::Int64(NodeInt64::Constant(0i64.wrapping_sub_unsigned(value)))
+                }
+                Node::Uint64(node) => Node::Int64(NodeInt64::Negative(NodeInt64Negative::Uint64(
+                    Box::new(node),
+                ))),
+                Node::Int64(node) => Node::Int64(NodeInt64::Negative(NodeInt64Negative::Int64(
+                    Box::new(node),
+                ))),
+                Node::Float64(NodeFloat64::Constant(value)) => {
+                    Node::Float64(NodeFloat64::Constant(-value))
+                }
+                Node::Float64(node) => Node::Float64(NodeFloat64::Negative(
+                    NodeFloat64Negative::Float64(Box::new(node)),
+                )),
+                node => {
+                    return Err(node);
+                }
+            },
+        })
+    }
+}
+
+impl BinaryOperator {
+    pub(crate) fn parse(token: &str) -> Option<Self> {
+        Some(match token {
+            "**" => Self::Exponent,
+            "*" => Self::Multiply,
+            "/" => Self::Divide,
+            "%" => Self::Modulo,
+            "&&" => Self::Both,
+            "||" => Self::Either,
+            "==" => Self::Equals,
+            "!=" => Self::NotEquals,
+            "<" => Self::LessThan,
+            "<=" => Self::LessThanOrEquals,
+            ">" => Self::GreaterThan,
+            ">=" => Self::GreaterThanOrEquals,
+            "+" => Self::Add,
+            "-" => Self::Subtract,
+            "~" => Self::Matches,
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    pub(crate) fn to_node(&self, left: Node, right: Node) -> Result<Node, Box<(Node, Node)>> {
+        // `x == null` and `x != null` are `!exists(x)` and `exists(x)`
+        if let (Self::Equals | Self::NotEquals, Node::Null, node)
+        | (Self::Equals | Self::NotEquals, node, Node::Null) = (self, &left, &right)
+        {
+            let Some(name) = node.variable_name() else {
+                return Err(Box::new((left, right)));
+            };
+            let exists = NodeBoolean::Exists {
+                name: name.to_owned(),
+            };
+            return Ok(Node::Boolean(if *self == Self::Equals {
+                NodeBoolean::Not(NodeBooleanNot::Boolean(Box::new(exists)))
+            } else {
+                exists
+            }));
+        }
+
+        let (left, right) = match self {
+            Self::Add
+            | Self::Subtract
+            | Self::Multiply
+            | Self::Divide
+            | Self::Modulo
+            | Self::Equals
+            | Self::NotEquals
+            | Self::LessThan
+            | Self::LessThanOrEquals
+            | Self::GreaterThan
+            | Self::GreaterThanOrEquals => promote(left, right),
+            Self::Exponent => promote_float64(left, right),
+            _ => (left, right),
+        };
+
+        Ok(match self {
+            Self::Both => match (left, right) {
+                (Node::Boolean(left), Node::Boolean(right)) => {
+                    Node::Boolean(NodeBoolean::Both(NodeBooleanBoth::BooleanBoolean {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Either => match (left, right) {
+                (Node::Boolean(left), Node::Boolean(right)) => {
+                    Node::Boolean(NodeBoolean::Either(NodeBooleanEither::BooleanBoolean {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Equals => match (left, right) {
+                (Node::Boolean(left), Node::Boolean(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::BooleanBoolean {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::Uint64Uint64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::Int64Int64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Ip(left), Node::Ip(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::IpIp { left, right }))
+                }
+                (Node::String(left), Node::String(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::StringString {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::Float64Float64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Duration(left), Node::Duration(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::DurationDuration {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Timestamp(left), Node::Timestamp(right)) => {
+                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::TimestampTimestamp {
+                        left,
+                        right,
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::NotEquals => match (left, right) {
+                (Node::Boolean(left), Node::Boolean(right)) => Node::Boolean(
+                    NodeBoolean::NotEquals(NodeBooleanNotEquals::BooleanBoolean {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (Node::String(left), Node::String(right)) => {
+                    Node::Boolean(NodeBoolean::NotEquals(NodeBooleanNotEquals::StringString {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Boolean(NodeBoolean::NotEquals(NodeBooleanNotEquals::Uint64Uint64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Boolean(NodeBoolean::NotEquals(NodeBooleanNotEquals::Int64Int64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Ip(left), Node::Ip(right)) => {
+                    Node::Boolean(NodeBoolean::NotEquals(NodeBooleanNotEquals::IpIp {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => Node::Boolean(
+                    NodeBoolean::NotEquals(NodeBooleanNotEquals::Float64Float64 { left, right }),
+                ),
+                (Node::Duration(left), Node::Duration(right)) => Node::Boolean(
+                    NodeBoolean::NotEquals(NodeBooleanNotEquals::DurationDuration { left, right }),
+                ),
+                (Node::Timestamp(left), Node::Timestamp(right)) => {
+                    Node::Boolean(NodeBoolean::NotEquals(
+                        NodeBooleanNotEquals::TimestampTimestamp { left, right },
+                    ))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::LessThan => match (left, right) {
+                (Node::String(left), Node::String(right)) => {
+                    Node::Boolean(NodeBoolean::LessThan(NodeBooleanLessThan::StringString {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThan(NodeBooleanLessThan::Uint64Uint64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThan(NodeBooleanLessThan::Int64Int64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Ip(left), Node::Ip(right)) => {
+                    Node::Boolean(NodeBoolean::LessThan(NodeBooleanLessThan::IpIp {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThan(NodeBooleanLessThan::Float64Float64 {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Duration(left), Node::Duration(right)) => Node::Boolean(
+                    NodeBoolean::LessThan(NodeBooleanLessThan::DurationDuration { left, right }),
+                ),
+                (Node::Timestamp(left), Node::Timestamp(right)) => Node::Boolean(
+                    NodeBoolean::LessThan(NodeBooleanLessThan::TimestampTimestamp { left, right }),
+                ),
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::LessThanOrEquals => match (left, right) {
+                (Node::String(left), Node::String(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::StringString { left, right },
+                    ))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::Uint64Uint64 { left, right },
+                    ))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::Int64Int64 { left, right },
+                    ))
+                }
+                (Node::Ip(left), Node::Ip(right)) => Node::Boolean(NodeBoolean::LessThanOrEquals(
+                    NodeBooleanLessThanOrEquals::IpIp { left, right },
+                )),
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::Float64Float64 { left, right },
+                    ))
+                }
+                (Node::Duration(left), Node::Duration(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::DurationDuration { left, right },
+                    ))
+                }
+                (Node::Timestamp(left), Node::Timestamp(right)) => {
+                    Node::Boolean(NodeBoolean::LessThanOrEquals(
+                        NodeBooleanLessThanOrEquals::TimestampTimestamp { left, right },
+                    ))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::GreaterThan => match (left, right) {
+                (Node::String(left), Node::String(right)) => Node::Boolean(
+                    NodeBoolean::GreaterThan(NodeBooleanGreaterThan::StringString { left, right }),
+                ),
+                (Node::Uint64(left), Node::Uint64(right)) => Node::Boolean(
+                    NodeBoolean::GreaterThan(NodeBooleanGreaterThan::Uint64Uint64 { left, right }),
+                ),
+                (Node::Int64(left), Node::Int64(right)) => Node::Boolean(NodeBoolean::GreaterThan(
+                    NodeBooleanGreaterThan::Int64Int64 { left, right },
+                )),
+                (Node::Ip(left), Node::Ip(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThan(NodeBooleanGreaterThan::IpIp {
+                        left,
+                        right,
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThan(
+                        NodeBooleanGreaterThan::Float64Float64 { left, right },
+                    ))
+                }
+                (Node::Duration(left), Node::Duration(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThan(
+                        NodeBooleanGreaterThan::DurationDuration { left, right },
+                    ))
+                }
+                (Node::Timestamp(left), Node::Timestamp(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThan(
+                        NodeBooleanGreaterThan::TimestampTimestamp { left, right },
+                    ))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::GreaterThanOrEquals => match (left, right) {
+                (Node::String(left), Node::String(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::StringString { left, right },
+                    ))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::Uint64Uint64 { left, right },
+                    ))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::Int64Int64 { left, right },
+                    ))
+                }
+                (Node::Ip(left), Node::Ip(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::IpIp { left, right },
+                    ))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::Float64Float64 { left, right },
+                    ))
+                }
+                (Node::Duration(left), Node::Duration(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::DurationDuration { left, right },
+                    ))
+                }
+                (Node::Timestamp(left), Node::Timestamp(right)) => {
+                    Node::Boolean(NodeBoolean::GreaterThanOrEquals(
+                        NodeBooleanGreaterThanOrEquals::TimestampTimestamp { left, right },
+                    ))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Add => match (left, right) {
+                (Node::String(left), Node::String(right)) => {
+                    Node::String(NodeString::Add(NodeStringAdd::StringString {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Add(NodeUint64Add::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Int64(NodeInt64::Add(NodeInt64Add::Int64Int64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Add(NodeFloat64Add::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Duration(left), Node::Duration(right)) => {
+                    Node::Duration(NodeDuration::Add(NodeDurationAdd::DurationDuration {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Timestamp(left), Node::Duration(right)) => {
+                    Node::Timestamp(NodeTimestamp::Add(NodeTimestampAdd::TimestampDuration {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Duration(left), Node::Timestamp(right)) => {
+                    Node::Timestamp(NodeTimestamp::Add(NodeTimestampAdd::DurationTimestamp {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Subtract => match (left, right) {
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Subtract(NodeUint64Subtract::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Int64(NodeInt64::Subtract(NodeInt64Subtract::Int64Int64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Subtract(NodeFloat64Subtract::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Duration(left), Node::Duration(right)) => Node::Duration(
+                    NodeDuration::Subtract(NodeDurationSubtract::DurationDuration {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (Node::Timestamp(left), Node::Duration(right)) => Node::Timestamp(
+                    NodeTimestamp::Subtract(NodeTimestampSubtract::TimestampDuration {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (Node::Timestamp(left), Node::Timestamp(right)) => Node::Duration(
+                    NodeDuration::Subtract(NodeDurationSubtract::TimestampTimestamp {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Within => {
+                match (left, right) {
+                    (Node::Ip(left), Node::Cidr(right)) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::IpCidr {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::Ip(left), Node::List(NodeList::Cidr(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::IpCidrList {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::Ip(left), Node::List(NodeList::Ip(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::IpIpList {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::String(left), Node::List(NodeList::String(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::StringStringList {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::Uint64(left), Node::List(NodeList::Uint64(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::Uint64Uint64List {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::Int64(left), Node::List(NodeList::Int64(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::Int64Int64List {
+                            left,
+                            right,
+                        }))
+                    }
+                    (Node::Uint64(left), Node::List(NodeList::Int64(right))) => {
+                        Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::Int64Int64List {
+                            left: NodeInt64::from_uint64(left),
+                            right,
+                        }))
+                    }
+                    (Node::Int64(left), Node::List(NodeList::Uint64(right))) => {
+                        match NodeListInt64::from_uint64(right) {
+                            Ok(right) => Node::Boolean(NodeBoolean::Within(
+                                NodeBooleanWithin::Int64Int64List { left, right },
+                            )),
+                            Err(right) => {
+                                return Err(Box::new((
+                                    Node::Int64(left),
+                                    Node::List(NodeList::Uint64(right)),
+                                )));
+                            }
+                        }
+                    }
+                    (left, right) => {
+                        return Err(Box::new((left, right)));
+                    }
+                }
+            }
+            Self::Matches => match (left, right) {
+                (Node::String(left), Node::Regex(right)) => {
+                    Node::Boolean(NodeBoolean::Matches(NodeBooleanMatches::StringRegex {
+                        left,
+                        right,
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Multiply => match (left, right) {
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Multiply(NodeUint64Multiply::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Int64(NodeInt64::Multiply(NodeInt64Multiply::Int64Int64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Multiply(NodeFloat64Multiply::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Duration(left), Node::Uint64(right)) => Node::Duration(
+                    NodeDuration::Multiply(NodeDurationMultiply::DurationUint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (Node::Uint64(left), Node::Duration(right)) => Node::Duration(
+                    NodeDuration::Multiply(NodeDurationMultiply::Uint64Duration {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }),
+                ),
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Divide => match (left, right) {
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Divide(NodeUint64Divide::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Int64(NodeInt64::Divide(NodeInt64Divide::Int64Int64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Divide(NodeFloat64Divide::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Duration(left), Node::Uint64(right)) => {
+                    Node::Duration(NodeDuration::Divide(NodeDurationDivide::DurationUint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Modulo => match (left, right) {
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Modulo(NodeUint64Modulo::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Int64(right)) => {
+                    Node::Int64(NodeInt64::Modulo(NodeInt64Modulo::Int64Int64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Modulo(NodeFloat64Modulo::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+            Self::Exponent => match (left, right) {
+                (Node::Uint64(left), Node::Uint64(right)) => {
+                    Node::Uint64(NodeUint64::Exponent(NodeUint64Exponent::Uint64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Int64(left), Node::Uint64(right)) => {
+                    Node::Int64(NodeInt64::Exponent(NodeInt64Exponent::Int64Uint64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (Node::Float64(left), Node::Float64(right)) => {
+                    Node::Float64(NodeFloat64::Exponent(NodeFloat64Exponent::Float64Float64 {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }))
+                }
+                (left, right) => {
+                    return Err(Box::new((left, right)));
+                }
+            },
+        })
+    }
+}
+
+#[derive(Eq, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+impl Operator {
+    pub(crate) fn associativity(&self) -> Associativity {
+        match self {
+            Self::Unary(_) => Associativity::Right,
+            Self::Scope(_) => Associativity::Left,
+            Self::Binary(operator) => match operator {
+                BinaryOperator::Exponent => Associativity::Right,
+                _ => Associativity::Left,
+            },
+        }
+    }
+
+    pub(crate) fn specificity(&self) -> u8 {
+        match self {
+            Self::Scope(_) => 0,
+            Self::Binary(operator) => match operator {
+                BinaryOperator::Either => 2,
+                BinaryOperator::Both => 3,
+                BinaryOperator::Equals
+                | BinaryOperator::NotEquals
+                | BinaryOperator::LessThan
+                | BinaryOperator::LessThanOrEquals
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterThanOrEquals
+                | BinaryOperator::Matches
+                | BinaryOperator::Within => 4,
+                BinaryOperator::Add | BinaryOperator::Subtract => 5,
+                BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 6,
+                BinaryOperator::Exponent => 7,
+            },
+            Self::Unary(_) => 255,
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! What an operand's type decides about which operators apply to it, as `const fn`s that
+//! mirror `operators`, so `expr!` can check the types of an expression as it's compiled,
+//! where nodes can't be built
+
+use super::nodes::list::NodeList;
+use super::nodes::Node;
+use super::operators::{BinaryOperator, UnaryOperator};
+use crate::Type;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shape {
+    Null,
+    /// A variable, or the element a quantifier named, which `exists` and `== null` apply to
+    Variable(Type),
+    /// A `[...]` list, whose uint64 elements can still be promoted to int64
+    ListLiteral(Type),
+    /// Any other operand
+    Value(Type),
+}
+
+impl Shape {
+    /// The shape of a node that's already been built
+    pub fn of(node: &Node) -> Self {
+        let type_ = match node {
+            Node::Null => return Self::Null,
+            Node::Boolean(_) => Type::Boolean,
+            Node::Cidr(_) => Type::Cidr,
+            Node::Duration(_) => Type::Duration,
+            Node::Float64(_) => Type::Float64,
+            Node::Int64(_) => Type::Int64,
+            Node::Ip(_) => Type::Ip,
+            Node::Regex(_) => Type::Regex,
+            Node::String(_) => Type::String,
+            Node::Timestamp(_) => Type::Timestamp,
+            Node::Uint64(_) => Type::Uint64,
+            Node::List(NodeList::Cidr(_)) => Type::CidrList,
+            Node::List(NodeList::Int64(_)) => Type::Int64List,
+            Node::List(NodeList::Ip(_)) => Type::IpList,
+            Node::List(NodeList::String(_)) => Type::StringList,
+            Node::List(NodeList::Uint64(_)) => Type::Uint64List,
+        };
+        if node.variable_name().is_some() {
+            Self::Variable(type_)
+        } else if let Node::List(_) = node {
+            Self::ListLiteral(type_)
+        } else {
+            Self::Value(type_)
+        }
+    }
+
+    pub const fn type_of(self) -> Option<Type> {
+        match self {
+            Self::Null => None,
+            Self::Variable(type_) | Self::ListLiteral(type_) | Self::Value(type_) => Some(type_),
+        }
+    }
+
+    /// The name of this shape's type, as `Node::type_name` would have it
+    pub const fn type_name(self) -> &'static str {
+        match self.type_of() {
+            Some(type_) => type_.name(),
+            None => "null",
+        }
+    }
+
+    pub const fn is_boolean(self) -> bool {
+        matches!(self.type_of(), Some(Type::Boolean))
+    }
+}
+
+const fn same(left: Type, right: Type) -> bool {
+    left as u8 == right as u8
+}
+
+/// Like `promote_float64` in `operators`
+const fn promote_float64(left: Type, right: Type) -> (Type, Type) {
+    match (left, right) {
+        (Type::Uint64 | Type::Int64, Type::Float64)
+        | (Type::Float64, Type::Uint64 | Type::Int64) => (Type::Float64, Type::Float64),
+        operands => operands,
+    }
+}
+
+/// Like `promote` in `operators`
+const fn promote(left: Type, right: Type) -> (Type, Type) {
+    match promote_float64(left, right) {
+        (Type::Uint64, Type::Int64) | (Type::Int64, Type::Uint64) => (Type::Int64, Type::Int64),
+        operands => operands,
+    }
+}
+
+/// The shape of `operator` applied to `operand`, if it applies
+pub const fn unary(operator: &UnaryOperator, operand: Shape) -> Option<Shape> {
+    let type_ = match (operator, operand) {
+        (UnaryOperator::Exists, Shape::Variable(_)) => Type::Boolean,
+        (UnaryOperator::Exists, _) => return None,
+        (UnaryOperator::Positive, _) => {
+            return match operand.type_of() {
+                Some(Type::Uint64 | Type::Int64 | Type::Float64) => Some(operand),
+                _ => None,
+            }
+        }
+        _ => match (operator, operand.type_of()) {
+            (
+                UnaryOperator::Length,
+                Some(
+                    Type::CidrList
+                    | Type::Int64List
+                    | Type::IpList
+                    | Type::StringList
+                    | Type::Uint64List,
+                ),
+            ) => Type::Uint64,
+            (UnaryOperator::Not, Some(Type::Boolean)) => Type::Boolean,
+            (UnaryOperator::Negative, Some(Type::Uint64 | Type::Int64)) => Type::Int64,
+            (UnaryOperator::Negative, Some(Type::Float64)) => Type::Float64,
+            _ => return None,
+        },
+    };
+    Some(Shape::Value(type_))
+}
+
+/// The shape of `operator` applied to `left` and `right`, if it applies
+pub const fn binary(operator: &BinaryOperator, left: Shape, right: Shape) -> Option<Shape> {
+    // `x == null` and `x != null` are `!exists(x)` and `exists(x)`
+    if let (BinaryOperator::Equals | BinaryOperator::NotEquals, Shape::Null, operand)
+    | (BinaryOperator::Equals | BinaryOperator::NotEquals, operand, Shape::Null) =
+        (operator, left, right)
+    {
+        return match operand {
+            Shape::Variable(_) => Some(Shape::Value(Type::Boolean)),
+            _ => None,
+        };
+    }
+
+    let (Some(left_type), Some(right_type)) = (left.type_of(), right.type_of()) else {
+        return None;
+    };
+    let (left_type, right_type) = match operator {
+        BinaryOperator::Add
+        | BinaryOperator::Subtract
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide
+        | BinaryOperator::Modulo
+        | BinaryOperator::Equals
+        | BinaryOperator::NotEquals
+        | BinaryOperator::LessThan
+        | BinaryOperator::LessThanOrEquals
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterThanOrEquals => promote(left_type, right_type),
+        BinaryOperator::Exponent => promote_float64(left_type, right_type),
+        _ => (left_type, right_type),
+    };
+
+    let type_ = match operator {
+        BinaryOperator::Both | BinaryOperator::Either => match (left_type, right_type) {
+            (Type::Boolean, Type::Boolean) => Type::Boolean,
+            _ => return None,
+        },
+        BinaryOperator::Equals | BinaryOperator::NotEquals => match left_type {
+            Type::Boolean
+            | Type::Uint64
+            | Type::Int64
+            | Type::Ip
+            | Type::String
+            | Type::Float64
+            | Type::Duration
+            | Type::Timestamp
+                if same(left_type, right_type) =>
+            {
+                Type::Boolean
+            }
+            _ => return None,
+        },
+        BinaryOperator::LessThan
+        | BinaryOperator::LessThanOrEquals
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterThanOrEquals => match left_type {
+            Type::Uint64
+            | Type::Int64
+            | Type::Ip
+            | Type::String
+            | Type::Float64
+            | Type::Duration
+            | Type::Timestamp
+                if same(left_type, right_type) =>
+            {
+                Type::Boolean
+            }
+            _ => return None,
+        },
+        BinaryOperator::Add => match (left_type, right_type) {
+            (Type::String, Type::String) => Type::String,
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Int64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            (Type::Duration, Type::Duration) => Type::Duration,
+            (Type::Timestamp, Type::Duration) | (Type::Duration, Type::Timestamp) => {
+                Type::Timestamp
+            }
+            _ => return None,
+        },
+        BinaryOperator::Subtract => match (left_type, right_type) {
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Int64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            (Type::Duration, Type::Duration) | (Type::Timestamp, Type::Timestamp) => Type::Duration,
+            (Type::Timestamp, Type::Duration) => Type::Timestamp,
+            _ => return None,
+        },
+        BinaryOperator::Within => match (left_type, right_type, right) {
+            (Type::Ip, Type::Cidr | Type::CidrList | Type::IpList, _)
+            | (Type::String, Type::StringList, _)
+            | (Type::Uint64, Type::Uint64List | Type::Int64List, _)
+            | (Type::Int64, Type::Int64List, _)
+            // only a literal's elements can be promoted to int64
+            | (Type::Int64, Type::Uint64List, Shape::ListLiteral(_)) => Type::Boolean,
+            _ => return None,
+        },
+        BinaryOperator::Matches => match (left_type, right_type) {
+            (Type::String, Type::Regex) => Type::Boolean,
+            _ => return None,
+        },
+        BinaryOperator::Multiply => match (left_type, right_type) {
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Int64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            (Type::Duration, Type::Uint64) | (Type::Uint64, Type::Duration) => Type::Duration,
+            _ => return None,
+        },
+        BinaryOperator::Divide => match (left_type, right_type) {
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Int64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            (Type::Duration, Type::Uint64) => Type::Duration,
+            _ => return None,
+        },
+        BinaryOperator::Modulo => match (left_type, right_type) {
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Int64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            _ => return None,
+        },
+        BinaryOperator::Exponent => match (left_type, right_type) {
+            (Type::Uint64, Type::Uint64) => Type::Uint64,
+            (Type::Int64, Type::Uint64) => Type::Int64,
+            (Type::Float64, Type::Float64) => Type::Float64,
+            _ => return None,
+        },
+    };
+    Some(Shape::Value(type_))
+}
+
+/// The shape of a list literal of `elements`, which aren't empty, or the index of the first
+/// element that can't be in it
+pub const fn list(elements: &[Shape]) -> Result<Shape, usize> {
+    let mut int64 = false;
+    let mut i = 0;
+    while i < elements.len() {
+        int64 |= matches!(elements[i].type_of(), Some(Type::Int64));
+        i += 1;
+    }
+    let list = match elements[0].type_of() {
+        Some(Type::Cidr) => Type::CidrList,
+        Some(Type::Ip) => Type::IpList,
+        Some(Type::String) => Type::StringList,
+        Some(Type::Uint64) if !int64 => Type::Uint64List,
+        Some(Type::Int64 | Type::Uint64) => Type::Int64List,
+        _ => return Err(0),
+    };
+    let mut i = 0;
+    while i < elements.len() {
+        if !matches!(
+            (list, elements[i].type_of()),
+            (Type::CidrList, Some(Type::Cidr))
+                | (Type::IpList, Some(Type::Ip))
+                | (Type::StringList, Some(Type::String))
+                | (Type::Uint64List, Some(Type::Uint64))
+                | (Type::Int64List, Some(Type::Int64 | Type::Uint64))
+        ) {
+            return Err(i);
+        }
+        i += 1;
+    }
+    Ok(Shape::ListLiteral(list))
+}
+
+/// The type of the elements of a quantifier's list, if it's a list
+pub const fn element(list: Shape) -> Option<Type> {
+    match list.type_of() {
+        Some(Type::CidrList) => Some(Type::Cidr),
+        Some(Type::Int64List) => Some(Type::Int64),
+        Some(Type::IpList) => Some(Type::Ip),
+        Some(Type::StringList) => Some(Type::String),
+        Some(Type::Uint64List) => Some(Type::Uint64),
+        _ => None,
+    }
+}
+
+/// The shape of a quantifier with `predicate`, if it's boolean
+pub const fn quantifier(predicate: Shape) -> Option<Shape> {
+    if predicate.is_boolean() {
+        Some(Shape::Value(Type::Boolean))
+    } else {
+        None
+    }
+}
+
+/// A message joined together in a `const`, where there's no `format!`, for `panic!` to show
+pub struct Message<const N: usize> {
+    buffer: [u8; N],
+    length: usize,
+}
+
+impl<const N: usize> Message<N> {
+    /// Join `parts` together, which have to fit in `N` bytes
+    pub const fn new(parts: &[&str]) -> Self {
+        let mut message = Self {
+            buffer: [0; N],
+            length: 0,
+        };
+        let mut i = 0;
+        while i < parts.len() {
+            let part = parts[i].as_bytes();
+            let mut j = 0;
+            while j < part.len() {
+                message.buffer[message.length] = part[j];
+                message.length += 1;
+                j += 1;
+            }
+            i += 1;
+        }
+        message
+    }
+
+    pub const fn as_str(&self) -> &str {
+        let (message, _) = self.buffer.split_at(self.length);
+        match std::str::from_utf8(message) {
+            Ok(message) => message,
+            Err(_) => panic!("it was joined from `str`s"),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! The operators and operands of an expression, parsed without knowing the types of its
+//! variables, for `expr!` to check those types once they're known
+
+use super::nodes::Node;
+use super::operators::{BinaryOperator, UnaryOperator};
+use super::{Builder, Error};
+use crate::lex::Token;
+use std::ops::Range;
+
+#[derive(Debug)]
+pub enum Syntax {
+    Constant(Node),
+    Variable(String),
+    /// The element of the innermost quantifier's list that its predicate named this
+    Element(String),
+    Unary {
+        operator: UnaryOperator,
+        span: Range<usize>,
+        operand: Box<(Syntax, Range<usize>)>,
+    },
+    Binary {
+        operator: BinaryOperator,
+        span: Range<usize>,
+        left: Box<(Syntax, Range<usize>)>,
+        right: Box<(Syntax, Range<usize>)>,
+    },
+    List(Vec<(Syntax, Range<usize>)>),
+    Quantifier {
+        all: bool,
+        list: Box<(Syntax, Range<usize>)>,
+        element: String,
+        predicate: Box<(Syntax, Range<usize>)>,
+    },
+}
+
+struct Structure;
+
+impl Builder for Structure {
+    type Operand = Syntax;
+    type Element = ();
+
+    fn constant(&self, node: Node) -> Syntax {
+        Syntax::Constant(node)
+    }
+
+    fn variable(&self, name: String, _span: Range<usize>) -> Result<Syntax, Error> {
+        Ok(Syntax::Variable(name))
+    }
+
+    fn element(&self, name: String, _element: &()) -> Syntax {
+        Syntax::Element(name)
+    }
+
+    fn unary(
+        &self,
+        operator: UnaryOperator,
+        span: Range<usize>,
+        operand: (Syntax, Range<usize>),
+    ) -> Result<Syntax, Error> {
+        Ok(Syntax::Unary {
+            operator,
+            span,
+            operand: Box::new(operand),
+        })
+    }
+
+    fn binary(
+        &self,
+        operator: BinaryOperator,
+        span: Range<usize>,
+        left: (Syntax, Range<usize>),
+        right: (Syntax, Range<usize>),
+    ) -> Result<Syntax, Error> {
+        Ok(Syntax::Binary {
+            operator,
+            span,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn list(&self, elements: Vec<(Syntax, Range<usize>)>) -> Result<Syntax, Error> {
+        Ok(Syntax::List(elements))
+    }
+
+    fn elements(&self, _list: &(Syntax, Range<usize>)) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn quantifier(
+        &self,
+        all: bool,
+        list: (Syntax, Range<usize>),
+        element: String,
+        predicate: (Syntax, Range<usize>),
+    ) -> Result<Syntax, Error> {
+        Ok(Syntax::Quantifier {
+            all,
+            list: Box::new(list),
+            element,
+            predicate: Box::new(predicate),
+        })
+    }
+}
+
+/// Parse `tokens` into their operators and operands. Anything that fails to parse here fails
+/// whatever the types of its variables are
+pub fn parse(tokens: Vec<(Token, Range<usize>)>) -> Result<(Syntax, Range<usize>), Error> {
+    super::parse_inner(tokens, &Structure)
+}
@@ -0,0 +1,167 @@
+mod schema;
+
+use chert::Schema;
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Arguments {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rewrite rule files to canonical form
+    Fmt {
+        /// File of `name: type` lines declaring the variables rules can use
+        #[clap(long)]
+        schema: String,
+        /// Don't rewrite anything, fail if any file isn't already in canonical form
+        #[clap(long)]
+        check: bool,
+        /// Rule files, with one expression per line
+        #[clap(required = true)]
+        files: Vec<String>,
+    },
+    /// Check rule files for errors and warnings
+    Lint {
+        /// File of `name: type` lines declaring the variables rules can use
+        #[clap(long)]
+        schema: String,
+        /// Rule files, with one expression per line
+        #[clap(required = true)]
+        files: Vec<String>,
+    },
+}
+
+/// A line of a rule file that holds an expression, rather than being blank or a `#` comment
+fn is_expression(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+fn report(path: &str, number: usize, expression: &str, message: &str) {
+    eprintln!("{path}:{number}: {message}");
+    eprintln!("  {expression}");
+}
+
+fn fmt(schema: &Schema, files: &[String], check: bool) -> bool {
+    let mut success = true;
+    for path in files {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                eprintln!("{path}: {error}");
+                success = false;
+                continue;
+            }
+        };
+
+        let mut formatted = String::with_capacity(contents.len());
+        let mut failed = false;
+        for (i, line) in contents.lines().enumerate() {
+            if is_expression(line) {
+                match chert::parse_with_schema(line, schema) {
+                    Ok(ast) => formatted.push_str(&chert::format(ast.get_root())),
+                    Err(error) => {
+                        eprint!("{path}:{}: {}", i + 1, error.render(line));
+                        failed = true;
+                    }
+                }
+            } else {
+                formatted.push_str(line.trim_end());
+            }
+            formatted.push('\n');
+        }
+
+        if failed {
+            success = false;
+        } else if formatted != contents {
+            if check {
+                eprintln!("{path}: not in canonical form");
+                for (i, (before, after)) in contents.lines().zip(formatted.lines()).enumerate() {
+                    if before != after {
+                        report(path, i + 1, before, &format!("should be `{after}`"));
+                    }
+                }
+                // differences that `lines()` doesn't show
+                if contents.contains("\r\n") {
+                    eprintln!("{path}: should have `\\n` line endings, not `\\r\\n`");
+                }
+                if !contents.ends_with('\n') {
+                    let number = contents.lines().count();
+                    eprintln!("{path}:{number}: should end with a newline");
+                }
+                success = false;
+            } else if let Err(error) = std::fs::write(path, formatted) {
+                eprintln!("{path}: {error}");
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+fn lint(schema: &Schema, files: &[String]) -> bool {
+    let mut success = true;
+    for path in files {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                eprintln!("{path}: {error}");
+                success = false;
+                continue;
+            }
+        };
+
+        for (i, line) in contents.lines().enumerate() {
+            if !is_expression(line) {
+                continue;
+            }
+            match chert::parse_with_schema(line, schema) {
+                Ok(ast) => match chert::compile_schema(schema, Vec::from([(i, ast)])) {
+                    Ok(engine) => {
+                        for (_, warning) in engine.warnings() {
+                            report(path, i + 1, line, &format!("warning: {warning}"));
+                        }
+                    }
+                    Err(error) => {
+                        report(path, i + 1, line, &format!("error: {error}"));
+                        success = false;
+                    }
+                },
+                Err(error) => {
+                    eprint!("{path}:{}: {}", i + 1, error.render(line));
+                    success = false;
+                }
+            }
+        }
+    }
+    success
+}
+
+fn main() -> ExitCode {
+    let args = Arguments::parse();
+
+    let (Command::Fmt { schema, .. } | Command::Lint { schema, .. }) = &args.command;
+    let schema = match schema::load(schema) {
+        Ok(schema) => schema,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let success = match &args.command {
+        Command::Fmt { files, check, .. } => fmt(&schema, files, *check),
+        Command::Lint { files, .. } => lint(&schema, files),
+    };
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
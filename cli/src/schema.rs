@@ -0,0 +1,41 @@
+use chert::variables::dynamic::Error;
+use chert::{Schema, Type};
+
+/// Load a schema with one `name: type` per line, where `type` is one of chert's type names
+/// (e.g. `uint64` or `ip`). Blank lines and lines starting with `#` are ignored
+pub fn load(path: &str) -> Result<Schema, String> {
+    let contents = std::fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+
+    let mut variables = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: String| format!("{path}:{}: {message}", i + 1);
+        let Some((name, type_name)) = line.split_once(':') else {
+            return Err(error(String::from("expected `name: type`")));
+        };
+        let type_ = type_name
+            .trim()
+            .parse::<Type>()
+            .map_err(|unknown| error(unknown.to_string()))?;
+        variables.push((i, name.trim(), type_));
+    }
+
+    Schema::new(variables.iter().map(|(_, name, type_)| (*name, *type_))).map_err(|error| {
+        match &error {
+            Error::DuplicateVariable { name } => {
+                // point at the second declaration
+                let (i, _, _) = variables
+                    .iter()
+                    .filter(|(_, n, _)| n == name)
+                    .nth(1)
+                    .unwrap();
+                format!("{path}:{}: `{name}` is declared more than once", i + 1)
+            }
+            _ => format!("{path}: {error}"),
+        }
+    })
+}
@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A directory of its own for a test to write a schema and rule files in to
+fn directory(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("chert-cli-{}-{test}", std::process::id()));
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// Run `chert` from `directory`, returning whether it succeeded and what it wrote to stderr
+fn chert(directory: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_chert"))
+        .current_dir(directory)
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+fn write(directory: &Path, name: &str, contents: &str) {
+    std::fs::write(directory.join(name), contents).unwrap();
+}
+
+fn read(directory: &Path, name: &str) -> String {
+    std::fs::read_to_string(directory.join(name)).unwrap()
+}
+
+const SCHEMA: &str = "# variables\na: uint64\nb: string\n";
+
+#[test]
+fn test_fmt() {
+    let directory = directory("fmt");
+    write(&directory, "schema", SCHEMA);
+    write(
+        &directory,
+        "rules",
+        "# a comment  \na==1\n\nb==\"x\" && a>1\n",
+    );
+
+    assert_eq!(
+        chert(&directory, &["fmt", "--schema", "schema", "rules"]),
+        (true, String::new())
+    );
+    assert_eq!(
+        read(&directory, "rules"),
+        "# a comment\na == 1\n\nb == 'x' && a > 1\n"
+    );
+    // already canonical, so nothing to do
+    assert_eq!(
+        chert(&directory, &["fmt", "--schema", "schema", "rules"]),
+        (true, String::new())
+    );
+    assert_eq!(
+        chert(
+            &directory,
+            &["fmt", "--check", "--schema", "schema", "rules"]
+        ),
+        (true, String::new())
+    );
+}
+
+#[test]
+fn test_fmt_check() {
+    let directory = directory("fmt-check");
+    write(&directory, "schema", SCHEMA);
+    write(&directory, "rules", "a == 1\nb==\"x\"\n");
+
+    assert_eq!(
+        chert(
+            &directory,
+            &["fmt", "--check", "--schema", "schema", "rules"]
+        ),
+        (
+            false,
+            String::from(
+                "rules: not in canonical form\nrules:2: should be `b == 'x'`\n  b==\"x\"\n"
+            )
+        )
+    );
+    // checking doesn't rewrite anything
+    assert_eq!(read(&directory, "rules"), "a == 1\nb==\"x\"\n");
+}
+
+#[test]
+fn test_fmt_check_newlines() {
+    let directory = directory("fmt-check-newlines");
+    write(&directory, "schema", SCHEMA);
+    write(&directory, "missing", "a == 1\nb == 'x'");
+    write(&directory, "crlf", "a == 1\r\nb == 'x'\r\n");
+
+    assert_eq!(
+        chert(
+            &directory,
+            &["fmt", "--check", "--schema", "schema", "missing"]
+        ),
+        (
+            false,
+            String::from("missing: not in canonical form\nmissing:2: should end with a newline\n")
+        )
+    );
+    assert_eq!(
+        chert(
+            &directory,
+            &["fmt", "--check", "--schema", "schema", "crlf"]
+        ),
+        (
+            false,
+            String::from(
+                "crlf: not in canonical form\ncrlf: should have `\\n` line endings, not `\\r\\n`\n"
+            )
+        )
+    );
+
+    let (success, _) = chert(
+        &directory,
+        &["fmt", "--schema", "schema", "missing", "crlf"],
+    );
+    assert!(success);
+    assert_eq!(read(&directory, "missing"), "a == 1\nb == 'x'\n");
+    assert_eq!(read(&directory, "crlf"), "a == 1\nb == 'x'\n");
+}
+
+#[test]
+fn test_fmt_invalid() {
+    let directory = directory("fmt-invalid");
+    write(&directory, "schema", SCHEMA);
+    write(&directory, "rules", "a==1\na ==\n");
+
+    let (success, stderr) = chert(&directory, &["fmt", "--schema", "schema", "rules"]);
+    assert!(!success);
+    assert!(stderr.starts_with("rules:2: error: operator is missing an operand\n"));
+    // nothing is rewritten when any line can't be parsed
+    assert_eq!(read(&directory, "rules"), "a==1\na ==\n");
+}
+
+#[test]
+fn test_lint() {
+    let directory = directory("lint");
+    write(&directory, "schema", SCHEMA);
+    write(&directory, "valid", "# fine\na == 1\n");
+    write(&directory, "warning", "a == 18446744073709551615 + 1\n");
+    write(&directory, "error", "a == 1\na + 1 == x\n");
+
+    assert_eq!(
+        chert(&directory, &["lint", "--schema", "schema", "valid"]),
+        (true, String::new())
+    );
+    // warnings are reported, but don't fail
+    assert_eq!(
+        chert(&directory, &["lint", "--schema", "schema", "warning"]),
+        (
+            true,
+            String::from(
+                "warning:1: warning: constant arithmetic overflows\n  a == 18446744073709551615 + 1\n"
+            )
+        )
+    );
+    let (success, stderr) = chert(&directory, &["lint", "--schema", "schema", "error"]);
+    assert!(!success);
+    assert!(stderr.starts_with("error:2: error: unknown identifier `x`\n"));
+
+    let (success, stderr) = chert(&directory, &["lint", "--schema", "schema", "missing"]);
+    assert!(!success);
+    assert!(stderr.starts_with("missing: "));
+}
+
+#[test]
+fn test_schema() {
+    let directory = directory("schema");
+    write(
+        &directory,
+        "duplicate",
+        "a: uint64\n\nb: string\na: string\n",
+    );
+    write(&directory, "unknown", "a: uint64\nb: uint128\n");
+    write(&directory, "malformed", "a uint64\n");
+    write(&directory, "rules", "a == 1\n");
+
+    assert_eq!(
+        chert(&directory, &["lint", "--schema", "duplicate", "rules"]),
+        (
+            false,
+            String::from("duplicate:4: `a` is declared more than once\n")
+        )
+    );
+    assert_eq!(
+        chert(&directory, &["lint", "--schema", "unknown", "rules"]),
+        (false, String::from("unknown:2: unknown type `uint128`\n"))
+    );
+    assert_eq!(
+        chert(&directory, &["fmt", "--schema", "malformed", "rules"]),
+        (false, String::from("malformed:1: expected `name: type`\n"))
+    );
+    let (success, stderr) = chert(&directory, &["fmt", "--schema", "missing", "rules"]);
+    assert!(!success);
+    assert!(stderr.starts_with("missing: "));
+}
@@ -1,48 +1,184 @@
 use crate::parse::nodes::boolean::{
-    NodeBoolean, NodeBooleanBoth, NodeBooleanEither, NodeBooleanEquals, NodeBooleanMatches,
-    NodeBooleanNot, NodeBooleanWithin,
+    NodeBoolean, NodeBooleanAll, NodeBooleanAny, NodeBooleanBoth, NodeBooleanEither,
+    NodeBooleanEquals, NodeBooleanGreaterThan, NodeBooleanGreaterThanOrEquals, NodeBooleanLessThan,
+    NodeBooleanLessThanOrEquals, NodeBooleanMatches, NodeBooleanNot, NodeBooleanNotEquals,
+    NodeBooleanWithin,
 };
 use crate::parse::nodes::cidr::NodeCidr;
-use crate::parse::nodes::int64::{NodeInt64, NodeInt64Negative};
+use crate::parse::nodes::duration::{
+    NodeDuration, NodeDurationAdd, NodeDurationDivide, NodeDurationMultiply, NodeDurationSubtract,
+};
+use crate::parse::nodes::float64::{
+    NodeFloat64, NodeFloat64Add, NodeFloat64Cast, NodeFloat64Divide, NodeFloat64Exponent,
+    NodeFloat64Modulo, NodeFloat64Multiply, NodeFloat64Negative, NodeFloat64Subtract,
+};
+use crate::parse::nodes::int64::{
+    NodeInt64, NodeInt64Add, NodeInt64Cast, NodeInt64Divide, NodeInt64Exponent, NodeInt64Modulo,
+    NodeInt64Multiply, NodeInt64Negative, NodeInt64Subtract,
+};
 use crate::parse::nodes::ip::NodeIp;
+use crate::parse::nodes::list::{
+    NodeListCidr, NodeListInt64, NodeListIp, NodeListString, NodeListUint64,
+};
 use crate::parse::nodes::regex::NodeRegex;
 use crate::parse::nodes::string::{NodeString, NodeStringAdd};
-use crate::parse::nodes::uint64::{NodeUint64, NodeUint64Add, NodeUint64Subtract};
+use crate::parse::nodes::timestamp::{NodeTimestamp, NodeTimestampAdd, NodeTimestampSubtract};
+use crate::parse::nodes::uint64::{
+    NodeUint64, NodeUint64Add, NodeUint64Divide, NodeUint64Exponent, NodeUint64Length,
+    NodeUint64Modulo, NodeUint64Multiply, NodeUint64Subtract,
+};
 use crate::parse::Ast;
-use crate::variables::{Variable, Variables};
+use crate::variables::dynamic::{Error as SchemaError, Record, Schema};
+use crate::variables::{Type, Variable, Variables};
+
+mod overflow;
+
+use self::overflow::Outcome;
+pub use self::overflow::Overflow;
 
 use cidr::{IpCidr, Ipv4Cidr};
 use regex::Regex;
-use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::borrow::{Borrow, Cow};
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::net::{IpAddr, Ipv4Addr};
+use std::ops::Range;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+/// Values by type. Strings, regexes and lists are borrowed from the variables being evaluated
+/// for as long as `'a`, rather than copied, and only owned when they're constants or computed
 #[derive(Clone, Debug)]
-pub struct Scratch {
+pub struct Scratch<'a> {
     boolean: Vec<bool>,
     cidr: Vec<IpCidr>,
+    duration: Vec<Duration>,
+    float64: Vec<f64>,
     int64: Vec<i64>,
     ip: Vec<IpAddr>,
-    string: Vec<String>,
+    string: Vec<Cow<'a, str>>,
+    timestamp: Vec<SystemTime>,
     uint64: Vec<u64>,
-    regex: Vec<Regex>,
+    regex: Vec<Cow<'a, Regex>>,
+    cidr_list: Vec<Cow<'a, [IpCidr]>>,
+    int64_list: Vec<Cow<'a, [i64]>>,
+    ip_list: Vec<Cow<'a, [IpAddr]>>,
+    string_list: Vec<Cow<'a, [String]>>,
+    uint64_list: Vec<Cow<'a, [u64]>>,
+}
+
+/// What a regex variable is until it's loaded, shared so that copying a `Scratch` doesn't copy a
+/// `Regex`
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new("").unwrap())
 }
 
-impl Scratch {
+impl Scratch<'_> {
     fn new() -> Self {
         Self {
             boolean: Vec::default(),
             cidr: Vec::default(),
+            duration: Vec::default(),
+            float64: Vec::default(),
             int64: Vec::default(),
             ip: Vec::default(),
             string: Vec::default(),
+            timestamp: Vec::default(),
             uint64: Vec::default(),
             regex: Vec::default(),
+            cidr_list: Vec::default(),
+            int64_list: Vec::default(),
+            ip_list: Vec::default(),
+            string_list: Vec::default(),
+            uint64_list: Vec::default(),
+        }
+    }
+
+    /// Copy `reference` in to this scratch, reusing its allocations
+    fn reset(&mut self, reference: &Scratch<'static>) {
+        self.boolean.clone_from(&reference.boolean);
+        self.cidr.clone_from(&reference.cidr);
+        self.duration.clone_from(&reference.duration);
+        self.float64.clone_from(&reference.float64);
+        self.int64.clone_from(&reference.int64);
+        self.ip.clone_from(&reference.ip);
+        self.string.clone_from(&reference.string);
+        self.timestamp.clone_from(&reference.timestamp);
+        self.uint64.clone_from(&reference.uint64);
+        self.regex.clone_from(&reference.regex);
+        self.cidr_list.clone_from(&reference.cidr_list);
+        self.int64_list.clone_from(&reference.int64_list);
+        self.ip_list.clone_from(&reference.ip_list);
+        self.string_list.clone_from(&reference.string_list);
+        self.uint64_list.clone_from(&reference.uint64_list);
+    }
+
+    /// Drop everything borrowed from the variables last evaluated, keeping the allocations
+    fn detach(self) -> Scratch<'static> {
+        /// Empty `values` out, which lets its allocation outlive what it borrowed. Collecting
+        /// in to a new `Vec` would only reuse the allocation as an optimisation std doesn't
+        /// promise, and `eval_with` promises not to allocate
+        fn detach<T: ?Sized + ToOwned>(values: Vec<Cow<T>>) -> Vec<Cow<'static, T>> {
+            let mut values = std::mem::ManuallyDrop::new(values);
+            values.clear();
+            // SAFETY: the allocation came from a `Vec` of the same capacity, of a type that only
+            // differs in lifetime so has the same layout, and there are no elements left to
+            // outlive what they borrowed
+            unsafe {
+                Vec::from_raw_parts(
+                    values.as_mut_ptr().cast::<Cow<'static, T>>(),
+                    0,
+                    values.capacity(),
+                )
+            }
+        }
+
+        Scratch {
+            boolean: self.boolean,
+            cidr: self.cidr,
+            duration: self.duration,
+            float64: self.float64,
+            int64: self.int64,
+            ip: self.ip,
+            string: detach(self.string),
+            timestamp: self.timestamp,
+            uint64: self.uint64,
+            regex: detach(self.regex),
+            cidr_list: detach(self.cidr_list),
+            int64_list: detach(self.int64_list),
+            ip_list: detach(self.ip_list),
+            string_list: detach(self.string_list),
+            uint64_list: detach(self.uint64_list),
+        }
+    }
+}
+
+/// Buffers for [`Engine::eval_with`] to evaluate in to, kept between evaluations (e.g. one per
+/// thread) so that evaluating doesn't allocate
+#[derive(Debug)]
+pub struct EvalContext<'e, H> {
+    dynamics: Scratch<'static>,
+    matched: Vec<&'e H>,
+    candidates: Vec<usize>,
+}
+
+impl<H> EvalContext<'_, H> {
+    pub fn new() -> Self {
+        Self {
+            dynamics: Scratch::new(),
+            matched: Vec::new(),
+            candidates: Vec::new(),
         }
     }
 }
 
+impl<H> Default for EvalContext<'_, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Pointer {
     Constant(usize),
@@ -52,26 +188,427 @@ pub enum Pointer {
 #[derive(Clone, Debug)]
 pub enum Instruction<H: Hash> {
     Nothing,
-    SkipIfTrue { check: Pointer, forward: usize },
-    SkipIfFalse { check: Pointer, forward: usize },
-    RaiseOutput { boolean: Pointer, id: H },
-    AddStringString { left: Pointer, right: Pointer },
-    AddUint64Uint64 { left: Pointer, right: Pointer },
-    BothBoolBool { left: Pointer, right: Pointer },
-    EitherBoolBool { left: Pointer, right: Pointer },
-    EqualsBoolBool { left: Pointer, right: Pointer },
-    EqualsStringString { left: Pointer, right: Pointer },
-    EqualsUint64Uint64 { left: Pointer, right: Pointer },
-    EqualsInt64Int64 { left: Pointer, right: Pointer },
-    EqualsIpIP { left: Pointer, right: Pointer },
+    SkipIfTrue {
+        check: Pointer,
+        forward: usize,
+    },
+    SkipIfFalse {
+        check: Pointer,
+        forward: usize,
+    },
+    RaiseOutput {
+        boolean: Pointer,
+        id: H,
+    },
+    AddStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    AddUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    BothBoolBool {
+        left: Pointer,
+        right: Pointer,
+    },
+    EitherBoolBool {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsBoolBool {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsIpIP {
+        left: Pointer,
+        right: Pointer,
+    },
     NegativeUint64(Pointer),
+    NegativeInt64(Pointer),
+    CastUint64Int64(Pointer),
+    AddInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    SubtractInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
     NotBool(Pointer),
-    SubtractUint64Uint64 { left: Pointer, right: Pointer },
-    WithinIpCidr { left: Pointer, right: Pointer },
-    MatchesStringRegex { left: Pointer, right: Pointer },
+    SubtractUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    MultiplyUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    DivideUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ModuloUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ExponentUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    MultiplyInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    DivideInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ModuloInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ExponentInt64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    WithinIpCidr {
+        left: Pointer,
+        right: Pointer,
+    },
+    MatchesStringRegex {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    AddFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    SubtractFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    MultiplyFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    DivideFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ModuloFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    ExponentFloat64Float64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    NegativeFloat64(Pointer),
+    CastUint64Float64(Pointer),
+    CastInt64Float64(Pointer),
+    EqualsDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    AddDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    SubtractDurationDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    MultiplyDurationUint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    DivideDurationUint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    EqualsTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    AddTimestampDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    SubtractTimestampDuration {
+        left: Pointer,
+        right: Pointer,
+    },
+    SubtractTimestampTimestamp {
+        left: Pointer,
+        right: Pointer,
+    },
+    Now,
+    NotEqualsBoolBool {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    NotEqualsIpIp {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanIpIp {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    LessThanOrEqualsIpIp {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanIpIp {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsStringString {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsUint64Uint64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsInt64Int64 {
+        left: Pointer,
+        right: Pointer,
+    },
+    GreaterThanOrEqualsIpIp {
+        left: Pointer,
+        right: Pointer,
+    },
+    BuildCidrList(Vec<Pointer>),
+    BuildInt64List(Vec<Pointer>),
+    BuildIpList(Vec<Pointer>),
+    BuildStringList(Vec<Pointer>),
+    BuildUint64List(Vec<Pointer>),
+    WithinIpCidrList {
+        left: Pointer,
+        right: Pointer,
+    },
+    WithinIpIpList {
+        left: Pointer,
+        right: Pointer,
+    },
+    WithinInt64Int64List {
+        left: Pointer,
+        right: Pointer,
+    },
+    WithinStringStringList {
+        left: Pointer,
+        right: Pointer,
+    },
+    WithinUint64Uint64List {
+        left: Pointer,
+        right: Pointer,
+    },
+    LengthCidrList(Pointer),
+    LengthInt64List(Pointer),
+    LengthIpList(Pointer),
+    LengthStringList(Pointer),
+    LengthUint64List(Pointer),
+    /// Start an `any` (or `all`) loop: the output is false (or true) until an element's
+    /// predicate is true (or false), and `counter` is the index of the next element
+    StartQuantifier {
+        counter: usize,
+        all: bool,
+    },
+    /// Load the next element of `list` in to the output, or skip `exit` instructions to leave the
+    /// loop if there are none left
+    NextCidrList {
+        list: Pointer,
+        counter: usize,
+        exit: usize,
+    },
+    NextInt64List {
+        list: Pointer,
+        counter: usize,
+        exit: usize,
+    },
+    NextIpList {
+        list: Pointer,
+        counter: usize,
+        exit: usize,
+    },
+    NextStringList {
+        list: Pointer,
+        counter: usize,
+        exit: usize,
+    },
+    NextUint64List {
+        list: Pointer,
+        counter: usize,
+        exit: usize,
+    },
+    /// Leave the loop with the output decided if `predicate` isn't `all`, otherwise go `back`
+    /// instructions to the next element
+    EndQuantifier {
+        predicate: Pointer,
+        all: bool,
+        back: usize,
+    },
 }
 
-#[derive(Debug)]
+impl<H: Hash> Instruction<H> {
+    /// Whether this can fail rather than write its output, which stops the expression it
+    /// belongs to from matching
+    fn is_fallible(&self) -> bool {
+        matches!(
+            self,
+            Self::AddDurationDuration { .. }
+                | Self::AddInt64Int64 { .. }
+                | Self::AddTimestampDuration { .. }
+                | Self::AddUint64Uint64 { .. }
+                | Self::CastUint64Int64(_)
+                | Self::DivideDurationUint64 { .. }
+                | Self::DivideInt64Int64 { .. }
+                | Self::DivideUint64Uint64 { .. }
+                | Self::ExponentInt64Uint64 { .. }
+                | Self::ExponentUint64Uint64 { .. }
+                | Self::ModuloInt64Int64 { .. }
+                | Self::ModuloUint64Uint64 { .. }
+                | Self::MultiplyDurationUint64 { .. }
+                | Self::MultiplyInt64Int64 { .. }
+                | Self::MultiplyUint64Uint64 { .. }
+                | Self::NegativeInt64(_)
+                | Self::NegativeUint64(_)
+                | Self::SubtractDurationDuration { .. }
+                | Self::SubtractInt64Int64 { .. }
+                | Self::SubtractTimestampDuration { .. }
+                | Self::SubtractTimestampTimestamp { .. }
+                | Self::SubtractUint64Uint64 { .. }
+        )
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
 pub enum Error {
     VariableNotFound {
         name: String,
@@ -82,9 +619,130 @@ pub enum Error {
     },
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VariableNotFound { name } => write!(f, "unknown variable `{name}`"),
+            Self::VariableTypeMismatch { name, expected } => {
+                write!(f, "variable `{name}` is not of type {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Something suspicious found while constant folding an expression
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+    Overflow,
+    DivisionByZero,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "constant arithmetic overflows"),
+            Self::DivisionByZero => write!(f, "constant division by zero"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub overflow: Overflow,
+}
+
+/// The variables an expression can read, and which optional ones it has read
+struct Scope<'a, T> {
+    variables: &'a HashMap<String, (usize, Variable<T>)>,
+    /// Boolean dynamics holding whether each optional variable has a value
+    exists: &'a HashMap<String, usize>,
+    read: Vec<usize>,
+    /// Variables the expression reads, so only they are loaded on eval
+    used: HashSet<String>,
+    /// Elements named by the `any` and `all` being compiled, innermost last, with the dynamic
+    /// each is loaded in to
+    elements: Vec<(String, Type, usize)>,
+    /// How many times each boolean subtree appears across every expression, outside of `any`
+    /// and `all`
+    occurrences: &'a HashMap<&'a NodeBoolean, usize>,
+    /// Where earlier expressions evaluate repeated subtrees in to, on every path through them
+    shared: &'a HashMap<NodeBoolean, Pointer>,
+    /// Like `shared`, but from earlier expressions dispatched on the same `variable ==
+    /// constant` as this one, which are evaluated whenever it is
+    shared_dispatched: &'a HashMap<NodeBoolean, Pointer>,
+    /// Repeated subtrees this expression evaluates on every path through it, to share with
+    /// later expressions
+    sharing: Vec<(NodeBoolean, Pointer)>,
+    /// How many right hand sides of `&&` and `||`, which aren't always evaluated, deep the
+    /// node being compiled is
+    conditional: usize,
+}
+
+impl<'a, T> Scope<'a, T> {
+    /// Where the variable or element called `name` is loaded in to, and its type
+    fn get(&mut self, name: &str) -> Option<(usize, Type)> {
+        if let Some((_, type_, index)) = self
+            .elements
+            .iter()
+            .rev()
+            .find(|(element, ..)| element == name)
+        {
+            return Some((*index, *type_));
+        }
+        if let Some(exists) = self.exists.get(name) {
+            if !self.read.contains(exists) {
+                self.read.push(*exists);
+            }
+        }
+        let (index, variable) = self.variables.get(name)?;
+        self.used.insert(name.to_owned());
+        Some((*index, variable.type_of()))
+    }
+
+    /// The boolean dynamic holding whether `name` has a value, or `None` if it always does
+    fn exists(&mut self, name: &str) -> Result<Option<usize>, Error> {
+        if self.elements.iter().any(|(element, ..)| element == name) {
+            Ok(None)
+        } else if let Some(exists) = self.exists.get(name) {
+            self.used.insert(name.to_owned());
+            Ok(Some(*exists))
+        } else if self.variables.contains_key(name) {
+            Ok(None)
+        } else {
+            Err(Error::VariableNotFound {
+                name: name.to_owned(),
+            })
+        }
+    }
+}
+
+/// Fold a constant operation's `outcome` in to `constants`. `None` if there's no value to fold,
+/// in which case the operation is left to fail at runtime
+fn fold<V>(
+    outcome: Outcome<V>,
+    constants: &mut Vec<V>,
+    warnings: &mut Vec<Warning>,
+) -> Option<Pointer> {
+    let value = match outcome {
+        Outcome::Value(value) => value,
+        Outcome::Overflow(value) => {
+            warnings.push(Warning::Overflow);
+            value?
+        }
+        Outcome::DivisionByZero => {
+            warnings.push(Warning::DivisionByZero);
+            return None;
+        }
+    };
+    constants.push(value);
+    Some(Pointer::Constant(constants.len() - 1))
+}
+
 fn compile_ip<T>(
     node: &NodeIp,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
 ) -> Result<Pointer, Error> {
     Ok(match node {
@@ -94,7 +752,7 @@ fn compile_ip<T>(
         }
         NodeIp::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Ip(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Ip)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
@@ -107,7 +765,7 @@ fn compile_ip<T>(
 
 fn compile_cidr<T>(
     node: &NodeCidr,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
 ) -> Result<Pointer, Error> {
     Ok(match node {
@@ -117,7 +775,7 @@ fn compile_cidr<T>(
         }
         NodeCidr::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Cidr(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Cidr)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
@@ -128,21 +786,196 @@ fn compile_cidr<T>(
     })
 }
 
+/// Count how many times `node` and each boolean subtree of it appear in to `occurrences`. The
+/// predicates of `any` and `all` aren't counted, as their elements mean something different
+/// each time
+fn count_subtrees<'a>(node: &'a NodeBoolean, occurrences: &mut HashMap<&'a NodeBoolean, usize>) {
+    *occurrences.entry(node).or_default() += 1;
+    match node {
+        NodeBoolean::Not(NodeBooleanNot::Boolean(node)) => count_subtrees(node, occurrences),
+        NodeBoolean::Both(NodeBooleanBoth::BooleanBoolean { left, right })
+        | NodeBoolean::Either(NodeBooleanEither::BooleanBoolean { left, right }) => {
+            count_subtrees(left, occurrences);
+            count_subtrees(right, occurrences);
+        }
+        _ => {}
+    }
+}
+
+/// A constant that a variable is compared to
+#[derive(Clone, Eq, Hash, PartialEq)]
+enum DispatchKey {
+    String(String),
+    Uint64(u64),
+    Ip(IpAddr),
+}
+
+/// The first `variable == constant` that `node` can't match without, i.e. that isn't under
+/// anything but `&&`
+fn dispatch_key(node: &NodeBoolean) -> Option<(&str, DispatchKey)> {
+    match node {
+        NodeBoolean::Both(NodeBooleanBoth::BooleanBoolean { left, right }) => {
+            dispatch_key(left).or_else(|| dispatch_key(right))
+        }
+        NodeBoolean::Equals(NodeBooleanEquals::StringString { left, right }) => {
+            match (left, right) {
+                (NodeString::Variable { name }, NodeString::Constant(value))
+                | (NodeString::Constant(value), NodeString::Variable { name }) => {
+                    Some((name, DispatchKey::String(value.clone())))
+                }
+                _ => None,
+            }
+        }
+        NodeBoolean::Equals(NodeBooleanEquals::Uint64Uint64 { left, right }) => {
+            match (left, right) {
+                (NodeUint64::Variable { name }, NodeUint64::Constant(value))
+                | (NodeUint64::Constant(value), NodeUint64::Variable { name }) => {
+                    Some((name, DispatchKey::Uint64(*value)))
+                }
+                _ => None,
+            }
+        }
+        NodeBoolean::Equals(NodeBooleanEquals::IpIp { left, right }) => match (left, right) {
+            (NodeIp::Variable { name }, NodeIp::Constant(value))
+            | (NodeIp::Constant(value), NodeIp::Variable { name }) => {
+                Some((name, DispatchKey::Ip(*value)))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Compile a boolean node, or point at where an earlier expression already evaluated it.
+/// Subtrees that appear more than once are shared with later expressions when they're
+/// evaluated on every path through this one and can't fail or warn
 fn compile_boolean<T, H: Hash>(
     node: &NodeBoolean,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    let repeated = variables.elements.is_empty()
+        && variables
+            .occurrences
+            .get(node)
+            .is_some_and(|count| *count > 1);
+    if repeated {
+        if let Some(pointer) = variables
+            .shared
+            .get(node)
+            .or_else(|| variables.shared_dispatched.get(node))
+        {
+            return Ok(pointer.clone());
+        }
+    }
+
+    let start = operations.len();
+    let warned = warnings.len();
+    let pointer = compile_boolean_guarded(
+        node, variables, constants, dynamics, operations, overflow, warnings,
+    )?;
+    if repeated
+        && variables.conditional == 0
+        && warnings.len() == warned
+        && operations.len() > start
+        && !operations[start..]
+            .iter()
+            .any(|(_, instruction)| instruction.is_fallible())
+    {
+        variables.sharing.push((node.clone(), pointer.clone()));
+    }
+    Ok(pointer)
+}
+
+/// Compile a boolean node. Comparisons (and bare boolean variables) that read an optional
+/// variable without a value are false, without evaluating either side; `!`, `&&` and `||` then
+/// apply as normal, so `!(x == 1)` is true when `x` is missing
+fn compile_boolean_guarded<T, H: Hash>(
+    node: &NodeBoolean,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    if let NodeBoolean::Constant(_)
+    | NodeBoolean::Exists { .. }
+    | NodeBoolean::Not(_)
+    | NodeBoolean::Both(_)
+    | NodeBoolean::Either(_) = node
+    {
+        return compile_boolean_inner(
+            node, variables, constants, dynamics, operations, overflow, warnings,
+        );
+    }
+
+    let outer_read = std::mem::take(&mut variables.read);
+    let sharing = variables.sharing.len();
+    let start = operations.len();
+    let pointer = compile_boolean_inner(
+        node, variables, constants, dynamics, operations, overflow, warnings,
+    );
+    let read = std::mem::replace(&mut variables.read, outer_read);
+    let pointer = pointer?;
+    let Some(last) = read.last() else {
+        return Ok(pointer);
+    };
+    // nothing in the body is evaluated on every path any more
+    variables.sharing.truncate(sharing);
+
+    // check every optional variable exists before evaluating anything that reads them
+    let output = dynamics.boolean.len();
+    dynamics.boolean.push(false);
+    let body = operations.len() - start;
+    operations.push((
+        output,
+        Instruction::BothBoolBool {
+            left: Pointer::Dynamic(*last),
+            right: pointer,
+        },
+    ));
+    let guards = read.iter().enumerate().map(|(i, exists)| {
+        (
+            output,
+            Instruction::SkipIfFalse {
+                check: Pointer::Dynamic(*exists),
+                forward: read.len() - i + body,
+            },
+        )
+    });
+    operations.splice(start..start, guards.collect::<Vec<_>>());
+    Ok(Pointer::Dynamic(output))
+}
+
+fn compile_boolean_inner<T, H: Hash>(
+    node: &NodeBoolean,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
     dynamics: &mut Scratch,
     operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
 ) -> Result<Pointer, Error> {
     Ok(match node {
         NodeBoolean::Constant(value) => {
             constants.boolean.push(*value);
             Pointer::Constant(constants.boolean.len() - 1)
         }
+        NodeBoolean::Exists { name } => match variables.exists(name)? {
+            Some(exists) => Pointer::Dynamic(exists),
+            None => {
+                constants.boolean.push(true);
+                Pointer::Constant(constants.boolean.len() - 1)
+            }
+        },
         NodeBoolean::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Boolean(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Boolean)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
@@ -152,7 +985,9 @@ fn compile_boolean<T, H: Hash>(
         },
         NodeBoolean::Not(node) => match node {
             NodeBooleanNot::Boolean(node) => {
-                let child = compile_boolean(node, variables, constants, dynamics, operations)?;
+                let child = compile_boolean(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 dynamics.boolean.push(false);
                 let index = dynamics.boolean.len() - 1;
                 operations.push((index, Instruction::NotBool(child)));
@@ -161,10 +996,17 @@ fn compile_boolean<T, H: Hash>(
         },
         NodeBoolean::Both(node) => match node {
             NodeBooleanBoth::BooleanBoolean { left, right } => {
-                let left = compile_boolean(left, variables, constants, dynamics, operations)?;
+                let left = compile_boolean(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 let jump_insert = operations.len();
                 operations.push((0, Instruction::Nothing));
-                let right = compile_boolean(right, variables, constants, dynamics, operations)?;
+                variables.conditional += 1;
+                let right = compile_boolean(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                );
+                variables.conditional -= 1;
+                let right = right?;
                 let output = dynamics.boolean.len();
                 dynamics.boolean.push(false);
                 operations[jump_insert] = (
@@ -180,10 +1022,17 @@ fn compile_boolean<T, H: Hash>(
         },
         NodeBoolean::Either(node) => match node {
             NodeBooleanEither::BooleanBoolean { left, right } => {
-                let left = compile_boolean(left, variables, constants, dynamics, operations)?;
+                let left = compile_boolean(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 let jump_insert = operations.len();
                 operations.push((0, Instruction::Nothing));
-                let right = compile_boolean(right, variables, constants, dynamics, operations)?;
+                variables.conditional += 1;
+                let right = compile_boolean(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                );
+                variables.conditional -= 1;
+                let right = right?;
                 let output = dynamics.boolean.len();
                 dynamics.boolean.push(false);
                 operations[jump_insert] = (
@@ -206,11 +1055,224 @@ fn compile_boolean<T, H: Hash>(
                 operations.push((index, Instruction::WithinIpCidr { left, right }));
                 Pointer::Dynamic(index)
             }
+            NodeBooleanWithin::IpCidrList { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_cidr_list(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::WithinIpCidrList { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanWithin::IpIpList { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip_list(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::WithinIpIpList { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanWithin::Int64Int64List { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64_list(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::WithinInt64Int64List { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanWithin::StringStringList { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string_list(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::WithinStringStringList { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanWithin::Uint64Uint64List { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64_list(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::WithinUint64Uint64List { left, right }));
+                Pointer::Dynamic(index)
+            }
         },
+        NodeBoolean::Any(NodeBooleanAny::CidrList {
+            list,
+            element,
+            predicate,
+        })
+        | NodeBoolean::All(NodeBooleanAll::CidrList {
+            list,
+            element,
+            predicate,
+        }) => {
+            let list = compile_cidr_list(list, variables, constants, dynamics, operations)?;
+            dynamics
+                .cidr
+                .push(IpCidr::V4(Ipv4Cidr::new_host(Ipv4Addr::from(0))));
+            let slot = dynamics.cidr.len() - 1;
+            compile_quantifier(
+                matches!(node, NodeBoolean::All(_)),
+                list,
+                |list, counter, exit| Instruction::NextCidrList {
+                    list,
+                    counter,
+                    exit,
+                },
+                (element, Type::Cidr, slot),
+                predicate,
+                variables,
+                constants,
+                dynamics,
+                operations,
+                overflow,
+                warnings,
+            )?
+        }
+        NodeBoolean::Any(NodeBooleanAny::Int64List {
+            list,
+            element,
+            predicate,
+        })
+        | NodeBoolean::All(NodeBooleanAll::Int64List {
+            list,
+            element,
+            predicate,
+        }) => {
+            let list = compile_int64_list(
+                list, variables, constants, dynamics, operations, overflow, warnings,
+            )?;
+            dynamics.int64.push(0);
+            let slot = dynamics.int64.len() - 1;
+            compile_quantifier(
+                matches!(node, NodeBoolean::All(_)),
+                list,
+                |list, counter, exit| Instruction::NextInt64List {
+                    list,
+                    counter,
+                    exit,
+                },
+                (element, Type::Int64, slot),
+                predicate,
+                variables,
+                constants,
+                dynamics,
+                operations,
+                overflow,
+                warnings,
+            )?
+        }
+        NodeBoolean::Any(NodeBooleanAny::IpList {
+            list,
+            element,
+            predicate,
+        })
+        | NodeBoolean::All(NodeBooleanAll::IpList {
+            list,
+            element,
+            predicate,
+        }) => {
+            let list = compile_ip_list(list, variables, constants, dynamics, operations)?;
+            dynamics.ip.push(IpAddr::V4(Ipv4Addr::from(0)));
+            let slot = dynamics.ip.len() - 1;
+            compile_quantifier(
+                matches!(node, NodeBoolean::All(_)),
+                list,
+                |list, counter, exit| Instruction::NextIpList {
+                    list,
+                    counter,
+                    exit,
+                },
+                (element, Type::Ip, slot),
+                predicate,
+                variables,
+                constants,
+                dynamics,
+                operations,
+                overflow,
+                warnings,
+            )?
+        }
+        NodeBoolean::Any(NodeBooleanAny::StringList {
+            list,
+            element,
+            predicate,
+        })
+        | NodeBoolean::All(NodeBooleanAll::StringList {
+            list,
+            element,
+            predicate,
+        }) => {
+            let list = compile_string_list(list, variables, constants, dynamics, operations)?;
+            dynamics.string.push(Cow::Borrowed(""));
+            let slot = dynamics.string.len() - 1;
+            compile_quantifier(
+                matches!(node, NodeBoolean::All(_)),
+                list,
+                |list, counter, exit| Instruction::NextStringList {
+                    list,
+                    counter,
+                    exit,
+                },
+                (element, Type::String, slot),
+                predicate,
+                variables,
+                constants,
+                dynamics,
+                operations,
+                overflow,
+                warnings,
+            )?
+        }
+        NodeBoolean::Any(NodeBooleanAny::Uint64List {
+            list,
+            element,
+            predicate,
+        })
+        | NodeBoolean::All(NodeBooleanAll::Uint64List {
+            list,
+            element,
+            predicate,
+        }) => {
+            let list = compile_uint64_list(
+                list, variables, constants, dynamics, operations, overflow, warnings,
+            )?;
+            dynamics.uint64.push(0);
+            let slot = dynamics.uint64.len() - 1;
+            compile_quantifier(
+                matches!(node, NodeBoolean::All(_)),
+                list,
+                |list, counter, exit| Instruction::NextUint64List {
+                    list,
+                    counter,
+                    exit,
+                },
+                (element, Type::Uint64, slot),
+                predicate,
+                variables,
+                constants,
+                dynamics,
+                operations,
+                overflow,
+                warnings,
+            )?
+        }
         NodeBoolean::Equals(node) => match node {
             NodeBooleanEquals::BooleanBoolean { left, right } => {
-                let left = compile_boolean(left, variables, constants, dynamics, operations)?;
-                let right = compile_boolean(right, variables, constants, dynamics, operations)?;
+                let left = compile_boolean(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_boolean(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 dynamics.boolean.push(false);
                 let index = dynamics.boolean.len() - 1;
                 operations.push((index, Instruction::EqualsBoolBool { left, right }));
@@ -225,16 +1287,24 @@ fn compile_boolean<T, H: Hash>(
                 Pointer::Dynamic(index)
             }
             NodeBooleanEquals::Uint64Uint64 { left, right } => {
-                let left = compile_uint64(left, variables, constants, dynamics, operations)?;
-                let right = compile_uint64(right, variables, constants, dynamics, operations)?;
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 dynamics.boolean.push(false);
                 let index = dynamics.boolean.len() - 1;
                 operations.push((index, Instruction::EqualsUint64Uint64 { left, right }));
                 Pointer::Dynamic(index)
             }
             NodeBooleanEquals::Int64Int64 { left, right } => {
-                let left = compile_int64(left, variables, constants, dynamics, operations)?;
-                let right = compile_int64(right, variables, constants, dynamics, operations)?;
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
                 dynamics.boolean.push(false);
                 let index = dynamics.boolean.len() - 1;
                 operations.push((index, Instruction::EqualsInt64Int64 { left, right }));
@@ -248,94 +1318,1319 @@ fn compile_boolean<T, H: Hash>(
                 operations.push((index, Instruction::EqualsIpIP { left, right }));
                 Pointer::Dynamic(index)
             }
+            NodeBooleanEquals::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::EqualsFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanEquals::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::EqualsDurationDuration { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanEquals::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::EqualsTimestampTimestamp { left, right }));
+                Pointer::Dynamic(index)
+            }
         },
-        NodeBoolean::Matches(node) => match node {
-            NodeBooleanMatches::StringRegex { left, right } => {
+        NodeBoolean::NotEquals(node) => match node {
+            NodeBooleanNotEquals::BooleanBoolean { left, right } => {
+                let left = compile_boolean(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_boolean(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::NotEqualsBoolBool { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::StringString { left, right } => {
                 let left = compile_string(left, variables, constants, dynamics, operations)?;
-                let right = compile_regex(right, variables, constants)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
                 dynamics.boolean.push(false);
                 let index = dynamics.boolean.len() - 1;
-                operations.push((index, Instruction::MatchesStringRegex { left, right }));
+                operations.push((index, Instruction::NotEqualsStringString { left, right }));
                 Pointer::Dynamic(index)
             }
-        },
-    })
-}
-
-fn compile_string<T, H: Hash>(
-    node: &NodeString,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
-    constants: &mut Scratch,
-    dynamics: &mut Scratch,
-    operations: &mut Vec<(usize, Instruction<H>)>,
-) -> Result<Pointer, Error> {
-    Ok(match node {
-        NodeString::Constant(value) => {
-            constants.string.push(value.clone());
+            NodeBooleanNotEquals::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::NotEqualsUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::NotEqualsInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::IpIp { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::NotEqualsIpIp { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::NotEqualsFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::NotEqualsDurationDuration { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanNotEquals::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::NotEqualsTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeBoolean::LessThan(node) => match node {
+            NodeBooleanLessThan::StringString { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanStringString { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::IpIp { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanIpIp { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanDurationDuration { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThan::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeBoolean::LessThanOrEquals(node) => match node {
+            NodeBooleanLessThanOrEquals::StringString { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsStringString { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsUint64Uint64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsInt64Int64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::IpIp { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::LessThanOrEqualsIpIp { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsFloat64Float64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsDurationDuration { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanLessThanOrEquals::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::LessThanOrEqualsTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeBoolean::GreaterThan(node) => match node {
+            NodeBooleanGreaterThan::StringString { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::GreaterThanStringString { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::GreaterThanUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::GreaterThanInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::IpIp { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::GreaterThanIpIp { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanFloat64Float64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanDurationDuration { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThan::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeBoolean::GreaterThanOrEquals(node) => match node {
+            NodeBooleanGreaterThanOrEquals::StringString { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsStringString { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsUint64Uint64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsInt64Int64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::IpIp { left, right } => {
+                let left = compile_ip(left, variables, constants)?;
+                let right = compile_ip(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::GreaterThanOrEqualsIpIp { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsFloat64Float64 { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsDurationDuration { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+            NodeBooleanGreaterThanOrEquals::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::GreaterThanOrEqualsTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeBoolean::Matches(node) => match node {
+            NodeBooleanMatches::StringRegex { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_regex(right, variables, constants)?;
+                dynamics.boolean.push(false);
+                let index = dynamics.boolean.len() - 1;
+                operations.push((index, Instruction::MatchesStringRegex { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
+fn compile_string<T, H: Hash>(
+    node: &NodeString,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeString::Constant(value) => {
+            constants.string.push(Cow::Owned(value.clone()));
             Pointer::Constant(constants.string.len() - 1)
         }
-        NodeString::Variable { name } => match variables.get(name.as_str()) {
+        NodeString::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::String)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "string",
+                })
+            }
+        },
+        NodeString::Add(node) => match node {
+            NodeStringAdd::StringString { left, right } => {
+                let left = compile_string(left, variables, constants, dynamics, operations)?;
+                let right = compile_string(right, variables, constants, dynamics, operations)?;
+                dynamics.string.push(Cow::Borrowed(""));
+                let index = dynamics.string.len() - 1;
+                operations.push((index, Instruction::AddStringString { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
+fn compile_duration<T, H: Hash>(
+    node: &NodeDuration,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeDuration::Constant(value) => {
+            constants.duration.push(*value);
+            Pointer::Constant(constants.duration.len() - 1)
+        }
+        NodeDuration::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::Duration)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "duration",
+                })
+            }
+        },
+        NodeDuration::Add(node) => match node {
+            NodeDurationAdd::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .add_duration(constants.duration[*left], constants.duration[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((index, Instruction::AddDurationDuration { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeDuration::Subtract(node) => match node {
+            NodeDurationSubtract::DurationDuration { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .subtract_duration(constants.duration[*left], constants.duration[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((index, Instruction::SubtractDurationDuration { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeDurationSubtract::TimestampTimestamp { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow.subtract_timestamps(
+                        constants.timestamp[*left],
+                        constants.timestamp[*right],
+                    );
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::SubtractTimestampTimestamp { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeDuration::Multiply(node) => match node {
+            NodeDurationMultiply::DurationUint64 { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .multiply_duration(constants.duration[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((index, Instruction::MultiplyDurationUint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeDurationMultiply::Uint64Duration { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .multiply_duration(constants.duration[*right], constants.uint64[*left]);
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::MultiplyDurationUint64 {
+                        left: right,
+                        right: left,
+                    },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeDuration::Divide(node) => match node {
+            NodeDurationDivide::DurationUint64 { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .divide_duration(constants.duration[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.duration, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.duration.push(Duration::ZERO);
+                let index = dynamics.duration.len() - 1;
+                operations.push((index, Instruction::DivideDurationUint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
+fn compile_float64<T, H: Hash>(
+    node: &NodeFloat64,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeFloat64::Constant(value) => {
+            constants.float64.push(*value);
+            Pointer::Constant(constants.float64.len() - 1)
+        }
+        NodeFloat64::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::Float64)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "float64",
+                })
+            }
+        },
+        NodeFloat64::Negative(node) => match node {
+            NodeFloat64Negative::Float64(node) => {
+                let child = compile_float64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::NegativeFloat64(child)));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Cast(node) => match node {
+            NodeFloat64Cast::Uint64(node) => {
+                let child = compile_uint64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::CastUint64Float64(child)));
+                Pointer::Dynamic(index)
+            }
+            NodeFloat64Cast::Int64(node) => {
+                let child = compile_int64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::CastInt64Float64(child)));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Add(node) => match node {
+            NodeFloat64Add::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::AddFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Subtract(node) => match node {
+            NodeFloat64Subtract::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::SubtractFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Multiply(node) => match node {
+            NodeFloat64Multiply::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::MultiplyFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Divide(node) => match node {
+            NodeFloat64Divide::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::DivideFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Modulo(node) => match node {
+            NodeFloat64Modulo::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::ModuloFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeFloat64::Exponent(node) => match node {
+            NodeFloat64Exponent::Float64Float64 { left, right } => {
+                let left = compile_float64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_float64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                dynamics.float64.push(0.0);
+                let index = dynamics.float64.len() - 1;
+                operations.push((index, Instruction::ExponentFloat64Float64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
+fn compile_int64<T, H: Hash>(
+    node: &NodeInt64,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeInt64::Constant(value) => {
+            constants.int64.push(*value);
+            Pointer::Constant(constants.int64.len() - 1)
+        }
+        NodeInt64::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::Int64)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "int64",
+                })
+            }
+        },
+        NodeInt64::Negative(node) => match node {
+            NodeInt64Negative::Uint64(node) => {
+                let child = compile_uint64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let Pointer::Constant(child) = &child {
+                    let outcome = overflow.negative_uint64(constants.uint64[*child]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::NegativeUint64(child)));
+                Pointer::Dynamic(index)
+            }
+            NodeInt64Negative::Int64(node) => {
+                let child = compile_int64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let Pointer::Constant(child) = &child {
+                    let outcome = overflow.negative_int64(constants.int64[*child]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::NegativeInt64(child)));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Cast(node) => match node {
+            NodeInt64Cast::Uint64(node) => {
+                let child = compile_uint64(
+                    node, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let Pointer::Constant(child) = &child {
+                    let outcome = overflow.cast_uint64_int64(constants.uint64[*child]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::CastUint64Int64(child)));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Add(node) => match node {
+            NodeInt64Add::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.add_int64(constants.int64[*left], constants.int64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::AddInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Subtract(node) => match node {
+            NodeInt64Subtract::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.subtract_int64(constants.int64[*left], constants.int64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::SubtractInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Multiply(node) => match node {
+            NodeInt64Multiply::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.multiply_int64(constants.int64[*left], constants.int64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::MultiplyInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Divide(node) => match node {
+            NodeInt64Divide::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.divide_int64(constants.int64[*left], constants.int64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::DivideInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Modulo(node) => match node {
+            NodeInt64Modulo::Int64Int64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_int64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.modulo_int64(constants.int64[*left], constants.int64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::ModuloInt64Int64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeInt64::Exponent(node) => match node {
+            NodeInt64Exponent::Int64Uint64 { left, right } => {
+                let left = compile_int64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.exponent_int64(constants.int64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.int64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.int64.push(0);
+                let index = dynamics.int64.len() - 1;
+                operations.push((index, Instruction::ExponentInt64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
+/// A list literal out of its compiled `elements`, folded in to a constant if every element is
+/// constant
+fn build_list<E: Clone + Into<V>, V: Clone, H: Hash>(
+    elements: Vec<Pointer>,
+    constant_elements: &[E],
+    constant_lists: &mut Vec<Cow<[V]>>,
+    dynamic_lists: &mut Vec<Cow<[V]>>,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    build: fn(Vec<Pointer>) -> Instruction<H>,
+) -> Pointer {
+    let values = elements
+        .iter()
+        .map(|element| match element {
+            Pointer::Constant(i) => Some(constant_elements[*i].clone().into()),
+            Pointer::Dynamic(_) => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if let Some(values) = values {
+        constant_lists.push(Cow::Owned(values));
+        Pointer::Constant(constant_lists.len() - 1)
+    } else {
+        dynamic_lists.push(Cow::Borrowed(&[]));
+        let index = dynamic_lists.len() - 1;
+        operations.push((index, build(elements)));
+        Pointer::Dynamic(index)
+    }
+}
+
+fn compile_cidr_list<T, H: Hash>(
+    node: &NodeListCidr,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeListCidr::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::CidrList)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "[cidr]",
+                })
+            }
+        },
+        NodeListCidr::Literal(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| compile_cidr(element, variables, constants))
+                .collect::<Result<Vec<_>, _>>()?;
+            build_list(
+                elements,
+                &constants.cidr,
+                &mut constants.cidr_list,
+                &mut dynamics.cidr_list,
+                operations,
+                Instruction::BuildCidrList,
+            )
+        }
+    })
+}
+
+fn compile_int64_list<T, H: Hash>(
+    node: &NodeListInt64,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeListInt64::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::String(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Int64List)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
-                    expected: "string",
+                    expected: "[int64]",
                 })
             }
         },
-        NodeString::Add(node) => match node {
-            NodeStringAdd::StringString { left, right } => {
-                let left = compile_string(left, variables, constants, dynamics, operations)?;
-                let right = compile_string(right, variables, constants, dynamics, operations)?;
-                dynamics.string.push("".to_string());
-                let index = dynamics.string.len() - 1;
-                operations.push((index, Instruction::AddStringString { left, right }));
-                Pointer::Dynamic(index)
+        NodeListInt64::Literal(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| {
+                    compile_int64(
+                        element, variables, constants, dynamics, operations, overflow, warnings,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            build_list(
+                elements,
+                &constants.int64,
+                &mut constants.int64_list,
+                &mut dynamics.int64_list,
+                operations,
+                Instruction::BuildInt64List,
+            )
+        }
+    })
+}
+
+fn compile_ip_list<T, H: Hash>(
+    node: &NodeListIp,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeListIp::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::IpList)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "[ip]",
+                })
             }
         },
+        NodeListIp::Literal(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| compile_ip(element, variables, constants))
+                .collect::<Result<Vec<_>, _>>()?;
+            build_list(
+                elements,
+                &constants.ip,
+                &mut constants.ip_list,
+                &mut dynamics.ip_list,
+                operations,
+                Instruction::BuildIpList,
+            )
+        }
     })
 }
 
-fn compile_int64<T, H: Hash>(
-    node: &NodeInt64,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+fn compile_string_list<T, H: Hash>(
+    node: &NodeListString,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
     dynamics: &mut Scratch,
     operations: &mut Vec<(usize, Instruction<H>)>,
 ) -> Result<Pointer, Error> {
     Ok(match node {
-        NodeInt64::Variable { name } => match variables.get(name.as_str()) {
+        NodeListString::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Int64(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::StringList)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
-                    expected: "int64",
+                    expected: "[string]",
                 })
             }
         },
-        NodeInt64::Negative(node) => match node {
-            NodeInt64Negative::Uint64(node) => {
-                let child = compile_uint64(node, variables, constants, dynamics, operations)?;
-                dynamics.int64.push(0);
-                let index = dynamics.int64.len() - 1;
-                operations.push((index, Instruction::NegativeUint64(child)));
-                Pointer::Dynamic(index)
+        NodeListString::Literal(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| compile_string(element, variables, constants, dynamics, operations))
+                .collect::<Result<Vec<_>, _>>()?;
+            build_list(
+                elements,
+                &constants.string,
+                &mut constants.string_list,
+                &mut dynamics.string_list,
+                operations,
+                Instruction::BuildStringList,
+            )
+        }
+    })
+}
+
+fn compile_uint64_list<T, H: Hash>(
+    node: &NodeListUint64,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeListUint64::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::Uint64List)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "[uint64]",
+                })
             }
         },
+        NodeListUint64::Literal(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| {
+                    compile_uint64(
+                        element, variables, constants, dynamics, operations, overflow, warnings,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            build_list(
+                elements,
+                &constants.uint64,
+                &mut constants.uint64_list,
+                &mut dynamics.uint64_list,
+                operations,
+                Instruction::BuildUint64List,
+            )
+        }
     })
 }
 
+/// Compile `any` (or `all`) as a loop over `list`, loading each element in to the dynamic
+/// `element` and evaluating `predicate` against it until one is true (or false)
+#[allow(clippy::too_many_arguments)]
+fn compile_quantifier<T, H: Hash>(
+    all: bool,
+    list: Pointer,
+    next: fn(Pointer, usize, usize) -> Instruction<H>,
+    (name, type_, element): (&str, Type, usize),
+    predicate: &NodeBoolean,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    let output = dynamics.boolean.len();
+    dynamics.boolean.push(false);
+    let counter = dynamics.uint64.len();
+    dynamics.uint64.push(0);
+    operations.push((output, Instruction::StartQuantifier { counter, all }));
+
+    let start = operations.len();
+    operations.push((element, Instruction::Nothing));
+    variables.elements.push((name.to_owned(), type_, element));
+    let predicate = compile_boolean(
+        predicate, variables, constants, dynamics, operations, overflow, warnings,
+    );
+    variables.elements.pop();
+    let predicate = predicate?;
+
+    let body = operations.len() - start - 1;
+    operations[start] = (element, next(list, counter, body + 1));
+    operations.push((
+        output,
+        Instruction::EndQuantifier {
+            predicate,
+            all,
+            back: body + 2,
+        },
+    ));
+    Ok(Pointer::Dynamic(output))
+}
+
 fn compile_regex<T>(
     node: &NodeRegex,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
 ) -> Result<Pointer, Error> {
     Ok(match node {
         NodeRegex::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Regex(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Regex)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
@@ -344,18 +2639,123 @@ fn compile_regex<T>(
             }
         },
         NodeRegex::Constant(value) => {
-            constants.regex.push(value.clone());
+            constants.regex.push(Cow::Owned(value.regex().clone()));
             Pointer::Constant(constants.regex.len() - 1)
         }
     })
 }
 
+fn compile_timestamp<T, H: Hash>(
+    node: &NodeTimestamp,
+    variables: &mut Scope<T>,
+    constants: &mut Scratch,
+    dynamics: &mut Scratch,
+    operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
+) -> Result<Pointer, Error> {
+    Ok(match node {
+        NodeTimestamp::Constant(value) => {
+            constants.timestamp.push(*value);
+            Pointer::Constant(constants.timestamp.len() - 1)
+        }
+        NodeTimestamp::Variable { name } => match variables.get(name.as_str()) {
+            None => return Err(Error::VariableNotFound { name: name.clone() }),
+            Some((index, Type::Timestamp)) => Pointer::Dynamic(index),
+            _ => {
+                return Err(Error::VariableTypeMismatch {
+                    name: name.clone(),
+                    expected: "timestamp",
+                })
+            }
+        },
+        NodeTimestamp::Now => {
+            dynamics.timestamp.push(UNIX_EPOCH);
+            let index = dynamics.timestamp.len() - 1;
+            operations.push((index, Instruction::Now));
+            Pointer::Dynamic(index)
+        }
+        NodeTimestamp::Add(node) => match node {
+            NodeTimestampAdd::TimestampDuration { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .add_timestamp(constants.timestamp[*left], constants.duration[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.timestamp, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.timestamp.push(UNIX_EPOCH);
+                let index = dynamics.timestamp.len() - 1;
+                operations.push((index, Instruction::AddTimestampDuration { left, right }));
+                Pointer::Dynamic(index)
+            }
+            NodeTimestampAdd::DurationTimestamp { left, right } => {
+                let left = compile_duration(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_timestamp(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .add_timestamp(constants.timestamp[*right], constants.duration[*left]);
+                    if let Some(pointer) = fold(outcome, &mut constants.timestamp, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.timestamp.push(UNIX_EPOCH);
+                let index = dynamics.timestamp.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::AddTimestampDuration {
+                        left: right,
+                        right: left,
+                    },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeTimestamp::Subtract(node) => match node {
+            NodeTimestampSubtract::TimestampDuration { left, right } => {
+                let left = compile_timestamp(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_duration(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome = overflow
+                        .subtract_timestamp(constants.timestamp[*left], constants.duration[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.timestamp, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.timestamp.push(UNIX_EPOCH);
+                let index = dynamics.timestamp.len() - 1;
+                operations.push((
+                    index,
+                    Instruction::SubtractTimestampDuration { left, right },
+                ));
+                Pointer::Dynamic(index)
+            }
+        },
+    })
+}
+
 fn compile_uint64<T, H: Hash>(
     node: &NodeUint64,
-    variables: &HashMap<&'static str, (usize, Variable<T>)>,
+    variables: &mut Scope<T>,
     constants: &mut Scratch,
     dynamics: &mut Scratch,
     operations: &mut Vec<(usize, Instruction<H>)>,
+    overflow: Overflow,
+    warnings: &mut Vec<Warning>,
 ) -> Result<Pointer, Error> {
     Ok(match node {
         NodeUint64::Constant(value) => {
@@ -364,7 +2764,7 @@ fn compile_uint64<T, H: Hash>(
         }
         NodeUint64::Variable { name } => match variables.get(name.as_str()) {
             None => return Err(Error::VariableNotFound { name: name.clone() }),
-            Some((index, Variable::Uint64(_))) => Pointer::Dynamic(*index),
+            Some((index, Type::Uint64)) => Pointer::Dynamic(index),
             _ => {
                 return Err(Error::VariableTypeMismatch {
                     name: name.clone(),
@@ -372,10 +2772,78 @@ fn compile_uint64<T, H: Hash>(
                 })
             }
         },
+        NodeUint64::Length(node) => {
+            let (list, instruction, length): (_, fn(Pointer) -> Instruction<H>, _) = match node {
+                NodeUint64Length::CidrList(node) => {
+                    let list = compile_cidr_list(node, variables, constants, dynamics, operations)?;
+                    let length = match list {
+                        Pointer::Constant(i) => Some(constants.cidr_list[i].len()),
+                        Pointer::Dynamic(_) => None,
+                    };
+                    (list, Instruction::LengthCidrList, length)
+                }
+                NodeUint64Length::Int64List(node) => {
+                    let list = compile_int64_list(
+                        node, variables, constants, dynamics, operations, overflow, warnings,
+                    )?;
+                    let length = match list {
+                        Pointer::Constant(i) => Some(constants.int64_list[i].len()),
+                        Pointer::Dynamic(_) => None,
+                    };
+                    (list, Instruction::LengthInt64List, length)
+                }
+                NodeUint64Length::IpList(node) => {
+                    let list = compile_ip_list(node, variables, constants, dynamics, operations)?;
+                    let length = match list {
+                        Pointer::Constant(i) => Some(constants.ip_list[i].len()),
+                        Pointer::Dynamic(_) => None,
+                    };
+                    (list, Instruction::LengthIpList, length)
+                }
+                NodeUint64Length::StringList(node) => {
+                    let list =
+                        compile_string_list(node, variables, constants, dynamics, operations)?;
+                    let length = match list {
+                        Pointer::Constant(i) => Some(constants.string_list[i].len()),
+                        Pointer::Dynamic(_) => None,
+                    };
+                    (list, Instruction::LengthStringList, length)
+                }
+                NodeUint64Length::Uint64List(node) => {
+                    let list = compile_uint64_list(
+                        node, variables, constants, dynamics, operations, overflow, warnings,
+                    )?;
+                    let length = match list {
+                        Pointer::Constant(i) => Some(constants.uint64_list[i].len()),
+                        Pointer::Dynamic(_) => None,
+                    };
+                    (list, Instruction::LengthUint64List, length)
+                }
+            };
+            if let Some(length) = length {
+                constants.uint64.push(length as u64);
+                return Ok(Pointer::Constant(constants.uint64.len() - 1));
+            }
+            dynamics.uint64.push(0);
+            let index = dynamics.uint64.len() - 1;
+            operations.push((index, instruction(list)));
+            Pointer::Dynamic(index)
+        }
         NodeUint64::Add(node) => match node {
             NodeUint64Add::Uint64Uint64 { left, right } => {
-                let left = compile_uint64(left, variables, constants, dynamics, operations)?;
-                let right = compile_uint64(right, variables, constants, dynamics, operations)?;
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.add_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
                 dynamics.uint64.push(0);
                 let index = dynamics.uint64.len() - 1;
                 operations.push((index, Instruction::AddUint64Uint64 { left, right }));
@@ -384,129 +2852,527 @@ fn compile_uint64<T, H: Hash>(
         },
         NodeUint64::Subtract(node) => match node {
             NodeUint64Subtract::Uint64Uint64 { left, right } => {
-                let left = compile_uint64(left, variables, constants, dynamics, operations)?;
-                let right = compile_uint64(right, variables, constants, dynamics, operations)?;
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.subtract_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
                 dynamics.uint64.push(0);
                 let index = dynamics.uint64.len() - 1;
                 operations.push((index, Instruction::SubtractUint64Uint64 { left, right }));
                 Pointer::Dynamic(index)
             }
         },
+        NodeUint64::Multiply(node) => match node {
+            NodeUint64Multiply::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.multiply_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.uint64.push(0);
+                let index = dynamics.uint64.len() - 1;
+                operations.push((index, Instruction::MultiplyUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeUint64::Divide(node) => match node {
+            NodeUint64Divide::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.divide_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.uint64.push(0);
+                let index = dynamics.uint64.len() - 1;
+                operations.push((index, Instruction::DivideUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeUint64::Modulo(node) => match node {
+            NodeUint64Modulo::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.modulo_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.uint64.push(0);
+                let index = dynamics.uint64.len() - 1;
+                operations.push((index, Instruction::ModuloUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
+        NodeUint64::Exponent(node) => match node {
+            NodeUint64Exponent::Uint64Uint64 { left, right } => {
+                let left = compile_uint64(
+                    left, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                let right = compile_uint64(
+                    right, variables, constants, dynamics, operations, overflow, warnings,
+                )?;
+                if let (Pointer::Constant(left), Pointer::Constant(right)) = (&left, &right) {
+                    let outcome =
+                        overflow.exponent_uint64(constants.uint64[*left], constants.uint64[*right]);
+                    if let Some(pointer) = fold(outcome, &mut constants.uint64, warnings) {
+                        return Ok(pointer);
+                    }
+                }
+                dynamics.uint64.push(0);
+                let index = dynamics.uint64.len() - 1;
+                operations.push((index, Instruction::ExponentUint64Uint64 { left, right }));
+                Pointer::Dynamic(index)
+            }
+        },
     })
 }
 
+/// Constants that a variable has to equal for expressions to match, and those expressions
+#[derive(Clone, Debug)]
+enum DispatchKeys {
+    String(HashMap<String, Vec<usize>>),
+    Uint64(HashMap<u64, Vec<usize>>),
+    Ip(HashMap<IpAddr, Vec<usize>>),
+}
+
+/// Expressions that can only match when one variable equals a constant, so that evaluating
+/// looks the variable's value up once rather than evaluating every one of them
+#[derive(Clone, Debug)]
+struct Dispatch {
+    variable: usize,
+    exists: Option<usize>,
+    keys: DispatchKeys,
+}
+
+/// Gets the schema variables are from
+type SchemaOf<T> = fn(&T) -> &Schema;
+
 #[derive(Clone, Debug)]
 pub struct Engine<T, H: Hash> {
     operations: Vec<(usize, Instruction<H>)>,
-    constants: Scratch,
-    reference_dynamics: Scratch,
-    variables: HashMap<&'static str, (usize, Variable<T>)>,
+    /// The operations each expression compiled in to
+    expressions: Vec<Range<usize>>,
+    /// Expressions evaluated no matter the variables
+    always: Vec<usize>,
+    /// Expressions evaluated only when a variable equals a given constant
+    dispatch: Vec<Dispatch>,
+    overflow: Overflow,
+    warnings: Vec<(usize, Warning)>,
+    constants: Scratch<'static>,
+    reference_dynamics: Scratch<'static>,
+    /// Where each variable that's read is loaded in to, and where whether it exists is loaded in
+    /// to if it's optional
+    variables: Vec<(usize, Option<usize>, Variable<T>)>,
+    /// The schema that variables have to be from, and how to get theirs, when compiled with one
+    schema: Option<(Schema, SchemaOf<T>)>,
 }
 
 impl<T, H: Hash> Engine<T, H> {
-    fn make_scratch(&self) -> Scratch {
-        self.reference_dynamics.clone()
+    /// Warnings from compilation, keyed by the index of the expression they came from
+    pub fn warnings(&self) -> &[(usize, Warning)] {
+        &self.warnings
+    }
+
+    /// How many instructions every expression compiled in to, with subexpressions that are
+    /// shared between expressions only counted once. Only for tests to check what's shared;
+    /// how expressions compile isn't part of the API
+    #[doc(hidden)]
+    pub fn instructions(&self) -> usize {
+        self.operations.len()
     }
 
-    fn resolve_uint64<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &u64 {
+    fn resolve_uint64<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a u64 {
         match pointer {
             Pointer::Constant(i) => &self.constants.uint64[*i],
             Pointer::Dynamic(i) => &dynamics.uint64[*i],
         }
     }
 
-    fn resolve_int64<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &i64 {
+    fn resolve_duration<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a Duration {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.duration[*i],
+            Pointer::Dynamic(i) => &dynamics.duration[*i],
+        }
+    }
+
+    fn resolve_float64<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a f64 {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.float64[*i],
+            Pointer::Dynamic(i) => &dynamics.float64[*i],
+        }
+    }
+
+    fn resolve_int64<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a i64 {
         match pointer {
             Pointer::Constant(i) => &self.constants.int64[*i],
             Pointer::Dynamic(i) => &dynamics.int64[*i],
         }
     }
 
-    fn resolve_cidr<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &IpCidr {
-        match pointer {
-            Pointer::Constant(i) => &self.constants.cidr[*i],
-            Pointer::Dynamic(i) => &dynamics.cidr[*i],
-        }
+    fn resolve_cidr<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a IpCidr {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.cidr[*i],
+            Pointer::Dynamic(i) => &dynamics.cidr[*i],
+        }
+    }
+
+    fn resolve_ip<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a IpAddr {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.ip[*i],
+            Pointer::Dynamic(i) => &dynamics.ip[*i],
+        }
+    }
+
+    fn resolve_regex<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a Regex {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.regex[*i],
+            Pointer::Dynamic(i) => &dynamics.regex[*i],
+        }
+    }
+
+    fn resolve_string<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a str {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.string[*i],
+            Pointer::Dynamic(i) => &dynamics.string[*i],
+        }
+    }
+
+    fn resolve_timestamp<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a SystemTime {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.timestamp[*i],
+            Pointer::Dynamic(i) => &dynamics.timestamp[*i],
+        }
+    }
+
+    fn resolve_boolean<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a bool {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.boolean[*i],
+            Pointer::Dynamic(i) => &dynamics.boolean[*i],
+        }
+    }
+
+    fn resolve_cidr_list<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a [IpCidr] {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.cidr_list[*i],
+            Pointer::Dynamic(i) => &dynamics.cidr_list[*i],
+        }
+    }
+
+    fn resolve_int64_list<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a [i64] {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.int64_list[*i],
+            Pointer::Dynamic(i) => &dynamics.int64_list[*i],
+        }
+    }
+
+    fn resolve_ip_list<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a [IpAddr] {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.ip_list[*i],
+            Pointer::Dynamic(i) => &dynamics.ip_list[*i],
+        }
+    }
+
+    fn resolve_string_list<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a [String] {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.string_list[*i],
+            Pointer::Dynamic(i) => &dynamics.string_list[*i],
+        }
+    }
+
+    fn resolve_uint64_list<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &'a [u64] {
+        match pointer {
+            Pointer::Constant(i) => &self.constants.uint64_list[*i],
+            Pointer::Dynamic(i) => &dynamics.uint64_list[*i],
+        }
+    }
+
+    pub fn eval(&self, variables: &T) -> Vec<&H> {
+        let mut context = EvalContext::new();
+        self.eval_inner(&mut context, variables, None);
+        context.matched
+    }
+
+    /// Like `eval`, but with `now` bound to the given time rather than the current time
+    pub fn eval_at(&self, variables: &T, now: SystemTime) -> Vec<&H> {
+        let mut context = EvalContext::new();
+        self.eval_inner(&mut context, variables, Some(now));
+        context.matched
+    }
+
+    /// Like `eval`, but evaluating in to `context`'s buffers, which after the first evaluation
+    /// means not allocating unless an expression builds a string or list
+    pub fn eval_with<'e, 'c>(
+        &'e self,
+        context: &'c mut EvalContext<'e, H>,
+        variables: &T,
+    ) -> &'c [&'e H] {
+        self.eval_inner(context, variables, None);
+        &context.matched
     }
 
-    fn resolve_ip<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &IpAddr {
-        match pointer {
-            Pointer::Constant(i) => &self.constants.ip[*i],
-            Pointer::Dynamic(i) => &dynamics.ip[*i],
-        }
+    /// Like `eval_with`, but with `now` bound to the given time rather than the current time
+    pub fn eval_at_with<'e, 'c>(
+        &'e self,
+        context: &'c mut EvalContext<'e, H>,
+        variables: &T,
+        now: SystemTime,
+    ) -> &'c [&'e H] {
+        self.eval_inner(context, variables, Some(now));
+        &context.matched
     }
 
-    fn resolve_regex<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &Regex {
-        match pointer {
-            Pointer::Constant(i) => &self.constants.regex[*i],
-            Pointer::Dynamic(i) => &dynamics.regex[*i],
+    /// Records index their values by position, so one from another schema would otherwise be
+    /// read as the wrong variables
+    fn check_schema(&self, variables: &T) -> Result<(), SchemaError> {
+        match &self.schema {
+            Some((schema, schema_of)) if schema_of(variables) != schema => {
+                Err(SchemaError::SchemaMismatch)
+            }
+            _ => Ok(()),
         }
     }
 
-    fn resolve_string<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &String {
-        match pointer {
-            Pointer::Constant(i) => &self.constants.string[*i],
-            Pointer::Dynamic(i) => &dynamics.string[*i],
+    fn eval_inner<'e>(
+        &'e self,
+        context: &mut EvalContext<'e, H>,
+        variables: &T,
+        mut now: Option<SystemTime>,
+    ) {
+        if let Err(error) = self.check_schema(variables) {
+            panic!("{error}");
         }
-    }
 
-    fn resolve_boolean<'a>(&'a self, dynamics: &'a Scratch, pointer: &Pointer) -> &bool {
-        match pointer {
-            Pointer::Constant(i) => &self.constants.boolean[*i],
-            Pointer::Dynamic(i) => &dynamics.boolean[*i],
+        let mut dynamics = std::mem::replace(&mut context.dynamics, Scratch::new());
+        dynamics.reset(&self.reference_dynamics);
+        let matched = &mut context.matched;
+        matched.clear();
+
+        for (index, exists, field) in self.variables.iter() {
+            let found = match field {
+                Variable::Boolean(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.boolean[*index] = *value),
+                Variable::Cidr(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.cidr[*index] = *value),
+                Variable::Duration(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.duration[*index] = *value),
+                Variable::Float64(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.float64[*index] = *value),
+                Variable::Int64(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.int64[*index] = *value),
+                Variable::Ip(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.ip[*index] = *value),
+                Variable::String(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.string[*index] = Cow::Borrowed(value)),
+                Variable::Timestamp(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.timestamp[*index] = *value),
+                Variable::Uint64(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.uint64[*index] = *value),
+                Variable::Regex(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.regex[*index] = Cow::Borrowed(value)),
+                Variable::CidrList(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.cidr_list[*index] = Cow::Borrowed(value)),
+                Variable::Int64List(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.int64_list[*index] = Cow::Borrowed(value)),
+                Variable::IpList(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.ip_list[*index] = Cow::Borrowed(value)),
+                Variable::StringList(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.string_list[*index] = Cow::Borrowed(value)),
+                Variable::Uint64List(field) => field
+                    .get(variables)
+                    .map(|value| dynamics.uint64_list[*index] = Cow::Borrowed(value)),
+            }
+            .is_some();
+            if let Some(exists) = exists {
+                dynamics.boolean[*exists] = found;
+            }
         }
-    }
 
-    pub fn eval(&self, variables: &T) -> Vec<&H> {
-        let mut dynamics = self.make_scratch();
-
-        for (_, (index, field)) in self.variables.iter() {
-            match field {
-                Variable::Boolean(field) => dynamics.boolean[*index] = *(*field)(variables),
-                Variable::Cidr(field) => dynamics.cidr[*index] = *(*field)(variables),
-                Variable::Int64(field) => dynamics.int64[*index] = *(*field)(variables),
-                Variable::Ip(field) => dynamics.ip[*index] = *(*field)(variables),
-                Variable::String(field) => dynamics.string[*index] = (*field)(variables).to_owned(),
-                Variable::Uint64(field) => dynamics.uint64[*index] = *(*field)(variables),
-                Variable::Regex(field) => dynamics.regex[*index] = (*field)(variables).clone(),
+        // only evaluate expressions that could match, in the order they were given in
+        let candidates = &mut context.candidates;
+        candidates.clear();
+        candidates.extend_from_slice(&self.always);
+        for dispatch in &self.dispatch {
+            if dispatch
+                .exists
+                .is_some_and(|exists| !dynamics.boolean[exists])
+            {
+                continue;
+            }
+            let found = match &dispatch.keys {
+                DispatchKeys::String(keys) => keys.get(dynamics.string[dispatch.variable].as_ref()),
+                DispatchKeys::Uint64(keys) => keys.get(&dynamics.uint64[dispatch.variable]),
+                DispatchKeys::Ip(keys) => keys.get(&dynamics.ip[dispatch.variable]),
             };
+            if let Some(found) = found {
+                candidates.extend_from_slice(found);
+            }
         }
+        if !self.dispatch.is_empty() {
+            candidates.sort_unstable();
+        }
+        let mut candidates = candidates.iter();
 
-        let mut matched = Vec::new();
-        let mut instructions = self.operations.iter();
-        while let Some((output, instruction)) = instructions.next() {
+        // set when an instruction has no defined result (e.g. division by zero, or overflow
+        // under `Overflow::Checked`), which stops the expression it belongs to from matching
+        let mut failed = false;
+        // index of the next instruction, and of the first after the expression it's in; jumps
+        // are relative to the one after the jump, and never leave the expression
+        let (mut pc, mut end) = (0, 0);
+        loop {
+            if pc == end {
+                let Some(expression) = candidates.next() else {
+                    break;
+                };
+                Range { start: pc, end } = self.expressions[*expression].clone();
+            }
+            let (output, instruction) = &self.operations[pc];
+            pc += 1;
             match instruction {
                 Instruction::Nothing => {}
                 Instruction::SkipIfTrue { check, forward } => {
                     let check = *self.resolve_boolean(&dynamics, check);
                     dynamics.boolean[*output] = check;
                     if check {
-                        for _ in 0..*forward {
-                            instructions.next();
-                        }
+                        pc += forward;
                     }
                 }
                 Instruction::SkipIfFalse { check, forward } => {
                     let check = *self.resolve_boolean(&dynamics, check);
                     dynamics.boolean[*output] = check;
                     if !check {
-                        for _ in 0..*forward {
-                            instructions.next();
-                        }
+                        pc += forward;
                     }
                 }
                 Instruction::RaiseOutput { boolean, id } => {
-                    if *self.resolve_boolean(&dynamics, boolean) {
+                    if !failed && *self.resolve_boolean(&dynamics, boolean) {
                         matched.push(id);
                     }
+                    failed = false;
                 }
                 Instruction::AddUint64Uint64 { left, right } => {
-                    dynamics.uint64[*output] =
-                        self.resolve_uint64(&dynamics, left) + self.resolve_uint64(&dynamics, right)
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.add_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
                 }
                 Instruction::SubtractUint64Uint64 { left, right } => {
-                    dynamics.uint64[*output] = self.resolve_uint64(&dynamics, left)
-                        - self.resolve_uint64(&dynamics, right);
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.subtract_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::MultiplyUint64Uint64 { left, right } => {
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.multiply_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::MultiplyInt64Int64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_int64(&dynamics, right);
+                    match self.overflow.multiply_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::DivideUint64Uint64 { left, right } => {
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.divide_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::DivideInt64Int64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_int64(&dynamics, right);
+                    match self.overflow.divide_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::ModuloUint64Uint64 { left, right } => {
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.modulo_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::ModuloInt64Int64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_int64(&dynamics, right);
+                    match self.overflow.modulo_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::ExponentUint64Uint64 { left, right } => {
+                    let left = *self.resolve_uint64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.exponent_uint64(left, right).value() {
+                        Some(value) => dynamics.uint64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::ExponentInt64Uint64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.exponent_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
                 }
                 Instruction::EqualsUint64Uint64 { left, right } => {
                     dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
@@ -527,8 +3393,10 @@ impl<T, H: Hash> Engine<T, H> {
                         .is_match(self.resolve_string(&dynamics, left));
                 }
                 Instruction::AddStringString { left, right } => {
-                    dynamics.string[*output] = self.resolve_string(&dynamics, left).clone()
-                        + self.resolve_string(&dynamics, right)
+                    dynamics.string[*output] = Cow::Owned(
+                        self.resolve_string(&dynamics, left).to_owned()
+                            + self.resolve_string(&dynamics, right),
+                    )
                 }
                 Instruction::BothBoolBool { left, right } => {
                     dynamics.boolean[*output] = *self.resolve_boolean(&dynamics, left)
@@ -547,8 +3415,206 @@ impl<T, H: Hash> Engine<T, H> {
                         == self.resolve_string(&dynamics, right);
                 }
                 Instruction::NegativeUint64(child) => {
-                    // will happily overflow. perhaps we should emit warnings about this stuff at compiletime
-                    dynamics.int64[*output] = -(*self.resolve_uint64(&dynamics, child) as i64);
+                    let child = *self.resolve_uint64(&dynamics, child);
+                    match self.overflow.negative_uint64(child).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::AddInt64Int64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_int64(&dynamics, right);
+                    match self.overflow.add_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::SubtractInt64Int64 { left, right } => {
+                    let left = *self.resolve_int64(&dynamics, left);
+                    let right = *self.resolve_int64(&dynamics, right);
+                    match self.overflow.subtract_int64(left, right).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::NegativeInt64(child) => {
+                    let child = *self.resolve_int64(&dynamics, child);
+                    match self.overflow.negative_int64(child).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::CastUint64Int64(child) => {
+                    let child = *self.resolve_uint64(&dynamics, child);
+                    match self.overflow.cast_uint64_int64(child).value() {
+                        Some(value) => dynamics.int64[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::EqualsFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        == self.resolve_float64(&dynamics, right);
+                }
+                Instruction::NotEqualsFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        != self.resolve_float64(&dynamics, right);
+                }
+                Instruction::LessThanFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        < self.resolve_float64(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        <= self.resolve_float64(&dynamics, right);
+                }
+                Instruction::GreaterThanFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        > self.resolve_float64(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsFloat64Float64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_float64(&dynamics, left)
+                        >= self.resolve_float64(&dynamics, right);
+                }
+                Instruction::AddFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self.resolve_float64(&dynamics, left)
+                        + self.resolve_float64(&dynamics, right);
+                }
+                Instruction::SubtractFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self.resolve_float64(&dynamics, left)
+                        - self.resolve_float64(&dynamics, right);
+                }
+                Instruction::MultiplyFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self.resolve_float64(&dynamics, left)
+                        * self.resolve_float64(&dynamics, right);
+                }
+                Instruction::DivideFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self.resolve_float64(&dynamics, left)
+                        / self.resolve_float64(&dynamics, right);
+                }
+                Instruction::ModuloFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self.resolve_float64(&dynamics, left)
+                        % self.resolve_float64(&dynamics, right);
+                }
+                Instruction::ExponentFloat64Float64 { left, right } => {
+                    dynamics.float64[*output] = self
+                        .resolve_float64(&dynamics, left)
+                        .powf(*self.resolve_float64(&dynamics, right));
+                }
+                Instruction::NegativeFloat64(child) => {
+                    dynamics.float64[*output] = -self.resolve_float64(&dynamics, child);
+                }
+                Instruction::CastUint64Float64(child) => {
+                    dynamics.float64[*output] = *self.resolve_uint64(&dynamics, child) as f64;
+                }
+                Instruction::CastInt64Float64(child) => {
+                    dynamics.float64[*output] = *self.resolve_int64(&dynamics, child) as f64;
+                }
+                Instruction::EqualsDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        == self.resolve_duration(&dynamics, right);
+                }
+                Instruction::NotEqualsDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        != self.resolve_duration(&dynamics, right);
+                }
+                Instruction::LessThanDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        < self.resolve_duration(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        <= self.resolve_duration(&dynamics, right);
+                }
+                Instruction::GreaterThanDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        > self.resolve_duration(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsDurationDuration { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_duration(&dynamics, left)
+                        >= self.resolve_duration(&dynamics, right);
+                }
+                Instruction::AddDurationDuration { left, right } => {
+                    let left = *self.resolve_duration(&dynamics, left);
+                    let right = *self.resolve_duration(&dynamics, right);
+                    match self.overflow.add_duration(left, right).value() {
+                        Some(value) => dynamics.duration[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::SubtractDurationDuration { left, right } => {
+                    let left = *self.resolve_duration(&dynamics, left);
+                    let right = *self.resolve_duration(&dynamics, right);
+                    match self.overflow.subtract_duration(left, right).value() {
+                        Some(value) => dynamics.duration[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::MultiplyDurationUint64 { left, right } => {
+                    let left = *self.resolve_duration(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.multiply_duration(left, right).value() {
+                        Some(value) => dynamics.duration[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::DivideDurationUint64 { left, right } => {
+                    let left = *self.resolve_duration(&dynamics, left);
+                    let right = *self.resolve_uint64(&dynamics, right);
+                    match self.overflow.divide_duration(left, right).value() {
+                        Some(value) => dynamics.duration[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::EqualsTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        == self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::NotEqualsTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        != self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::LessThanTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        < self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        <= self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::GreaterThanTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        > self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsTimestampTimestamp { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_timestamp(&dynamics, left)
+                        >= self.resolve_timestamp(&dynamics, right);
+                }
+                Instruction::AddTimestampDuration { left, right } => {
+                    let left = *self.resolve_timestamp(&dynamics, left);
+                    let right = *self.resolve_duration(&dynamics, right);
+                    match self.overflow.add_timestamp(left, right).value() {
+                        Some(value) => dynamics.timestamp[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::SubtractTimestampDuration { left, right } => {
+                    let left = *self.resolve_timestamp(&dynamics, left);
+                    let right = *self.resolve_duration(&dynamics, right);
+                    match self.overflow.subtract_timestamp(left, right).value() {
+                        Some(value) => dynamics.timestamp[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::SubtractTimestampTimestamp { left, right } => {
+                    let left = *self.resolve_timestamp(&dynamics, left);
+                    let right = *self.resolve_timestamp(&dynamics, right);
+                    match self.overflow.subtract_timestamps(left, right).value() {
+                        Some(value) => dynamics.duration[*output] = value,
+                        None => failed = true,
+                    }
+                }
+                Instruction::Now => {
+                    dynamics.timestamp[*output] = *now.get_or_insert_with(SystemTime::now);
                 }
                 Instruction::NotBool(child) => {
                     dynamics.boolean[*output] = !self.resolve_boolean(&dynamics, child);
@@ -557,10 +3623,309 @@ impl<T, H: Hash> Engine<T, H> {
                     dynamics.boolean[*output] =
                         self.resolve_ip(&dynamics, left) == self.resolve_ip(&dynamics, right);
                 }
+                Instruction::NotEqualsBoolBool { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_boolean(&dynamics, left)
+                        != self.resolve_boolean(&dynamics, right);
+                }
+                Instruction::NotEqualsStringString { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_string(&dynamics, left)
+                        != self.resolve_string(&dynamics, right);
+                }
+                Instruction::NotEqualsUint64Uint64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
+                        != self.resolve_uint64(&dynamics, right);
+                }
+                Instruction::NotEqualsInt64Int64 { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_int64(&dynamics, left) != self.resolve_int64(&dynamics, right);
+                }
+                Instruction::NotEqualsIpIp { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_ip(&dynamics, left) != self.resolve_ip(&dynamics, right);
+                }
+                Instruction::LessThanStringString { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_string(&dynamics, left)
+                        < self.resolve_string(&dynamics, right);
+                }
+                Instruction::LessThanUint64Uint64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
+                        < self.resolve_uint64(&dynamics, right);
+                }
+                Instruction::LessThanInt64Int64 { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_int64(&dynamics, left) < self.resolve_int64(&dynamics, right);
+                }
+                Instruction::LessThanIpIp { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_ip(&dynamics, left) < self.resolve_ip(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsStringString { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_string(&dynamics, left)
+                        <= self.resolve_string(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsUint64Uint64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
+                        <= self.resolve_uint64(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsInt64Int64 { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_int64(&dynamics, left) <= self.resolve_int64(&dynamics, right);
+                }
+                Instruction::LessThanOrEqualsIpIp { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_ip(&dynamics, left) <= self.resolve_ip(&dynamics, right);
+                }
+                Instruction::GreaterThanStringString { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_string(&dynamics, left)
+                        > self.resolve_string(&dynamics, right);
+                }
+                Instruction::GreaterThanUint64Uint64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
+                        > self.resolve_uint64(&dynamics, right);
+                }
+                Instruction::GreaterThanInt64Int64 { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_int64(&dynamics, left) > self.resolve_int64(&dynamics, right);
+                }
+                Instruction::GreaterThanIpIp { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_ip(&dynamics, left) > self.resolve_ip(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsStringString { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_string(&dynamics, left)
+                        >= self.resolve_string(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsUint64Uint64 { left, right } => {
+                    dynamics.boolean[*output] = self.resolve_uint64(&dynamics, left)
+                        >= self.resolve_uint64(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsInt64Int64 { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_int64(&dynamics, left) >= self.resolve_int64(&dynamics, right);
+                }
+                Instruction::GreaterThanOrEqualsIpIp { left, right } => {
+                    dynamics.boolean[*output] =
+                        self.resolve_ip(&dynamics, left) >= self.resolve_ip(&dynamics, right);
+                }
+                Instruction::BuildCidrList(elements) => {
+                    let list = elements
+                        .iter()
+                        .map(|element| *self.resolve_cidr(&dynamics, element))
+                        .collect();
+                    dynamics.cidr_list[*output] = list;
+                }
+                Instruction::BuildInt64List(elements) => {
+                    let list = elements
+                        .iter()
+                        .map(|element| *self.resolve_int64(&dynamics, element))
+                        .collect();
+                    dynamics.int64_list[*output] = list;
+                }
+                Instruction::BuildIpList(elements) => {
+                    let list = elements
+                        .iter()
+                        .map(|element| *self.resolve_ip(&dynamics, element))
+                        .collect();
+                    dynamics.ip_list[*output] = list;
+                }
+                Instruction::BuildStringList(elements) => {
+                    let list = elements
+                        .iter()
+                        .map(|element| self.resolve_string(&dynamics, element).to_owned())
+                        .collect();
+                    dynamics.string_list[*output] = list;
+                }
+                Instruction::BuildUint64List(elements) => {
+                    let list = elements
+                        .iter()
+                        .map(|element| *self.resolve_uint64(&dynamics, element))
+                        .collect();
+                    dynamics.uint64_list[*output] = list;
+                }
+                Instruction::WithinIpCidrList { left, right } => {
+                    let left = self.resolve_ip(&dynamics, left);
+                    dynamics.boolean[*output] = self
+                        .resolve_cidr_list(&dynamics, right)
+                        .iter()
+                        .any(|cidr| cidr.contains(left));
+                }
+                Instruction::WithinIpIpList { left, right } => {
+                    dynamics.boolean[*output] = self
+                        .resolve_ip_list(&dynamics, right)
+                        .contains(self.resolve_ip(&dynamics, left));
+                }
+                Instruction::WithinInt64Int64List { left, right } => {
+                    dynamics.boolean[*output] = self
+                        .resolve_int64_list(&dynamics, right)
+                        .contains(self.resolve_int64(&dynamics, left));
+                }
+                Instruction::WithinStringStringList { left, right } => {
+                    let left = self.resolve_string(&dynamics, left);
+                    dynamics.boolean[*output] = self
+                        .resolve_string_list(&dynamics, right)
+                        .iter()
+                        .any(|string| string == left);
+                }
+                Instruction::WithinUint64Uint64List { left, right } => {
+                    dynamics.boolean[*output] = self
+                        .resolve_uint64_list(&dynamics, right)
+                        .contains(self.resolve_uint64(&dynamics, left));
+                }
+                Instruction::LengthCidrList(list) => {
+                    dynamics.uint64[*output] = self.resolve_cidr_list(&dynamics, list).len() as u64;
+                }
+                Instruction::LengthInt64List(list) => {
+                    dynamics.uint64[*output] =
+                        self.resolve_int64_list(&dynamics, list).len() as u64;
+                }
+                Instruction::LengthIpList(list) => {
+                    dynamics.uint64[*output] = self.resolve_ip_list(&dynamics, list).len() as u64;
+                }
+                Instruction::LengthStringList(list) => {
+                    dynamics.uint64[*output] =
+                        self.resolve_string_list(&dynamics, list).len() as u64;
+                }
+                Instruction::LengthUint64List(list) => {
+                    dynamics.uint64[*output] =
+                        self.resolve_uint64_list(&dynamics, list).len() as u64;
+                }
+                Instruction::StartQuantifier { counter, all } => {
+                    dynamics.boolean[*output] = *all;
+                    dynamics.uint64[*counter] = 0;
+                }
+                Instruction::NextCidrList {
+                    list,
+                    counter,
+                    exit,
+                } => {
+                    let next = dynamics.uint64[*counter] as usize;
+                    match self.resolve_cidr_list(&dynamics, list).get(next).copied() {
+                        Some(element) => {
+                            dynamics.cidr[*output] = element;
+                            dynamics.uint64[*counter] += 1;
+                        }
+                        None => pc += exit,
+                    }
+                }
+                Instruction::NextInt64List {
+                    list,
+                    counter,
+                    exit,
+                } => {
+                    let next = dynamics.uint64[*counter] as usize;
+                    match self.resolve_int64_list(&dynamics, list).get(next).copied() {
+                        Some(element) => {
+                            dynamics.int64[*output] = element;
+                            dynamics.uint64[*counter] += 1;
+                        }
+                        None => pc += exit,
+                    }
+                }
+                Instruction::NextIpList {
+                    list,
+                    counter,
+                    exit,
+                } => {
+                    let next = dynamics.uint64[*counter] as usize;
+                    match self.resolve_ip_list(&dynamics, list).get(next).copied() {
+                        Some(element) => {
+                            dynamics.ip[*output] = element;
+                            dynamics.uint64[*counter] += 1;
+                        }
+                        None => pc += exit,
+                    }
+                }
+                Instruction::NextStringList {
+                    list,
+                    counter,
+                    exit,
+                } => {
+                    let next = dynamics.uint64[*counter] as usize;
+                    // borrow the element rather than copy it, unless the list was built by
+                    // this evaluation
+                    let element = match list {
+                        Pointer::Constant(i) => self.constants.string_list[*i]
+                            .get(next)
+                            .map(|element| Cow::Borrowed(element.as_str())),
+                        Pointer::Dynamic(i) => match &dynamics.string_list[*i] {
+                            Cow::Borrowed(list) => {
+                                let list = *list;
+                                list.get(next)
+                                    .map(|element| Cow::Borrowed(element.as_str()))
+                            }
+                            Cow::Owned(list) => list.get(next).cloned().map(Cow::Owned),
+                        },
+                    };
+                    match element {
+                        Some(element) => {
+                            dynamics.string[*output] = element;
+                            dynamics.uint64[*counter] += 1;
+                        }
+                        None => pc += exit,
+                    }
+                }
+                Instruction::NextUint64List {
+                    list,
+                    counter,
+                    exit,
+                } => {
+                    let next = dynamics.uint64[*counter] as usize;
+                    match self.resolve_uint64_list(&dynamics, list).get(next).copied() {
+                        Some(element) => {
+                            dynamics.uint64[*output] = element;
+                            dynamics.uint64[*counter] += 1;
+                        }
+                        None => pc += exit,
+                    }
+                }
+                Instruction::EndQuantifier {
+                    predicate,
+                    all,
+                    back,
+                } => {
+                    if self.resolve_boolean(&dynamics, predicate) != all {
+                        dynamics.boolean[*output] = !all;
+                    } else {
+                        pc -= back;
+                    }
+                }
             };
         }
 
-        matched
+        context.dynamics = dynamics.detach();
+    }
+}
+
+/// Evaluating an engine compiled with [`compile_schema`] against a record from another schema
+/// panics, these refuse it instead
+impl<H: Hash> Engine<Record, H> {
+    pub fn try_eval(&self, record: &Record) -> Result<Vec<&H>, SchemaError> {
+        self.check_schema(record)?;
+        Ok(self.eval(record))
+    }
+
+    pub fn try_eval_at(&self, record: &Record, now: SystemTime) -> Result<Vec<&H>, SchemaError> {
+        self.check_schema(record)?;
+        Ok(self.eval_at(record, now))
+    }
+
+    pub fn try_eval_with<'e, 'c>(
+        &'e self,
+        context: &'c mut EvalContext<'e, H>,
+        record: &Record,
+    ) -> Result<&'c [&'e H], SchemaError> {
+        self.check_schema(record)?;
+        Ok(self.eval_with(context, record))
+    }
+
+    pub fn try_eval_at_with<'e, 'c>(
+        &'e self,
+        context: &'c mut EvalContext<'e, H>,
+        record: &Record,
+        now: SystemTime,
+    ) -> Result<&'c [&'e H], SchemaError> {
+        self.check_schema(record)?;
+        Ok(self.eval_at_with(context, record, now))
     }
 }
 
@@ -570,23 +3935,95 @@ where
     H: Hash,
     I: IntoIterator<Item = (H, Ast<T, NodeBoolean>)>,
 {
-    let expressions = expressions.into_iter().map(|(id, ast)| (id, ast.root));
+    let expressions = expressions
+        .into_iter()
+        .map(|(id, ast)| (id, ast.into_root()));
     compile_unsafe(expressions)
 }
 
-pub fn compile_unsafe<'a, T, H, N, I>(expressions: I) -> Result<Engine<T, H>, Error>
+pub fn compile_unsafe<T, H, N, I>(expressions: I) -> Result<Engine<T, H>, Error>
+where
+    T: Variables,
+    H: Hash,
+    N: Borrow<NodeBoolean>,
+    I: IntoIterator<Item = (H, N)>,
+{
+    compile_unsafe_with_options(expressions, &Options::default())
+}
+
+pub fn compile_with_options<T, H, I>(
+    expressions: I,
+    options: &Options,
+) -> Result<Engine<T, H>, Error>
+where
+    T: Variables,
+    H: Hash,
+    I: IntoIterator<Item = (H, Ast<T, NodeBoolean>)>,
+{
+    let expressions = expressions
+        .into_iter()
+        .map(|(id, ast)| (id, ast.into_root()));
+    compile_unsafe_with_options(expressions, options)
+}
+
+pub fn compile_unsafe_with_options<T, H, N, I>(
+    expressions: I,
+    options: &Options,
+) -> Result<Engine<T, H>, Error>
 where
     T: Variables,
     H: Hash,
     N: Borrow<NodeBoolean>,
     I: IntoIterator<Item = (H, N)>,
 {
+    let mut variables = T::variables().into_iter().collect::<Vec<_>>();
+    variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+    compile_variables(variables, expressions, options)
+}
+
+/// Compile expressions parsed with [`crate::parse_with_schema`], to be evaluated against
+/// [`Record`]s of the same `schema`
+pub fn compile_schema<H, I>(schema: &Schema, expressions: I) -> Result<Engine<Record, H>, Error>
+where
+    H: Hash,
+    I: IntoIterator<Item = (H, Ast<Record, NodeBoolean>)>,
+{
+    compile_schema_with_options(schema, expressions, &Options::default())
+}
+
+pub fn compile_schema_with_options<H, I>(
+    schema: &Schema,
+    expressions: I,
+    options: &Options,
+) -> Result<Engine<Record, H>, Error>
+where
+    H: Hash,
+    I: IntoIterator<Item = (H, Ast<Record, NodeBoolean>)>,
+{
+    let expressions = expressions
+        .into_iter()
+        .map(|(id, ast)| (id, ast.into_root()));
+    let mut engine = compile_variables(schema.variables(), expressions, options)?;
+    engine.schema = Some((schema.clone(), Record::schema));
+    Ok(engine)
+}
+
+fn compile_variables<T, H, N, I>(
+    variables_unindexed: Vec<(String, Variable<T>)>,
+    expressions: I,
+    options: &Options,
+) -> Result<Engine<T, H>, Error>
+where
+    H: Hash,
+    N: Borrow<NodeBoolean>,
+    I: IntoIterator<Item = (H, N)>,
+{
+    let mut warnings = Vec::new();
     let mut constants = Scratch::new();
     let mut initial_dynamics = Scratch::new();
-
-    let mut variables_unindexed = T::variables().into_iter().collect::<Vec<_>>();
-    variables_unindexed.sort_by_key(|(k, _)| *k);
     let mut variables = HashMap::new();
+    let mut exists = HashMap::new();
+    let mut used = HashSet::new();
 
     let placeholder_ip = IpAddr::V4(Ipv4Addr::from(0));
     let placeholder_cidr = IpCidr::V4(Ipv4Cidr::new_host(Ipv4Addr::from(0)));
@@ -601,6 +4038,14 @@ where
                 initial_dynamics.cidr.push(placeholder_cidr);
                 initial_dynamics.cidr.len() - 1
             }
+            Variable::Duration(_) => {
+                initial_dynamics.duration.push(Duration::ZERO);
+                initial_dynamics.duration.len() - 1
+            }
+            Variable::Float64(_) => {
+                initial_dynamics.float64.push(0.0);
+                initial_dynamics.float64.len() - 1
+            }
             Variable::Int64(_) => {
                 initial_dynamics.int64.push(0);
                 initial_dynamics.int64.len() - 1
@@ -610,57 +4055,172 @@ where
                 initial_dynamics.ip.len() - 1
             }
             Variable::String(_) => {
-                initial_dynamics.string.push(String::new());
+                initial_dynamics.string.push(Cow::Borrowed(""));
                 initial_dynamics.string.len() - 1
             }
+            Variable::Timestamp(_) => {
+                initial_dynamics.timestamp.push(UNIX_EPOCH);
+                initial_dynamics.timestamp.len() - 1
+            }
             Variable::Uint64(_) => {
                 initial_dynamics.uint64.push(0);
                 initial_dynamics.uint64.len() - 1
             }
             Variable::Regex(_) => {
-                initial_dynamics.regex.push(Regex::new("").unwrap());
+                initial_dynamics
+                    .regex
+                    .push(Cow::Borrowed(placeholder_regex()));
                 initial_dynamics.regex.len() - 1
             }
+            Variable::CidrList(_) => {
+                initial_dynamics.cidr_list.push(Cow::Borrowed(&[]));
+                initial_dynamics.cidr_list.len() - 1
+            }
+            Variable::Int64List(_) => {
+                initial_dynamics.int64_list.push(Cow::Borrowed(&[]));
+                initial_dynamics.int64_list.len() - 1
+            }
+            Variable::IpList(_) => {
+                initial_dynamics.ip_list.push(Cow::Borrowed(&[]));
+                initial_dynamics.ip_list.len() - 1
+            }
+            Variable::StringList(_) => {
+                initial_dynamics.string_list.push(Cow::Borrowed(&[]));
+                initial_dynamics.string_list.len() - 1
+            }
+            Variable::Uint64List(_) => {
+                initial_dynamics.uint64_list.push(Cow::Borrowed(&[]));
+                initial_dynamics.uint64_list.len() - 1
+            }
         };
+        if field.is_optional() {
+            initial_dynamics.boolean.push(false);
+            exists.insert(name.clone(), initial_dynamics.boolean.len() - 1);
+        }
         variables.insert(name, (index, field));
     }
 
+    let (ids, expressions): (Vec<_>, Vec<_>) = expressions.into_iter().unzip();
+    let mut occurrences = HashMap::new();
+    for expression in &expressions {
+        count_subtrees(expression.borrow(), &mut occurrences);
+    }
+    let mut shared = HashMap::new();
+    // what's shared by the expressions dispatched on each `variable == constant`
+    let mut shared_dispatched = HashMap::<_, HashMap<_, _>>::new();
+    let mut dispatch = HashMap::new();
+    let mut always = Vec::new();
+
     let mut max_size_dynamics = initial_dynamics.clone();
     let mut operations = Vec::new();
-    for (id, expression) in expressions {
+    let mut ranges = Vec::new();
+    for (i, (id, expression)) in ids.into_iter().zip(&expressions).enumerate() {
         let expression = expression.borrow();
         let mut dynamics = initial_dynamics.clone();
+        let mut expression_warnings = Vec::new();
+        let start = operations.len();
+        let key = dispatch_key(expression);
+        let bucket = key.as_ref().map(|(name, key)| (*name, key.clone()));
+        let mut bucket_shared = bucket
+            .as_ref()
+            .and_then(|bucket| shared_dispatched.remove(bucket))
+            .unwrap_or_default();
 
-        compile_boolean(
+        let mut scope = Scope {
+            variables: &variables,
+            exists: &exists,
+            read: Vec::new(),
+            used: HashSet::new(),
+            elements: Vec::new(),
+            occurrences: &occurrences,
+            shared: &shared,
+            shared_dispatched: &bucket_shared,
+            sharing: Vec::new(),
+            conditional: 0,
+        };
+        let boolean = compile_boolean(
             expression,
-            &variables,
+            &mut scope,
             &mut constants,
             &mut dynamics,
             &mut operations,
+            options.overflow,
+            &mut expression_warnings,
         )?;
-        operations.push((
-            0,
-            Instruction::RaiseOutput {
-                boolean: Pointer::Dynamic(dynamics.boolean.len() - 1),
-                id,
-            },
-        ));
+        warnings.extend(expression_warnings.into_iter().map(|warning| (i, warning)));
+        used.extend(scope.used);
+        let sharing = scope.sharing;
+        if !sharing.is_empty() {
+            match &bucket {
+                None => shared.extend(sharing),
+                // an expression that's only evaluated for some values of a variable can only
+                // share what it evaluates with expressions evaluated for the same value
+                Some(_) => bucket_shared.extend(sharing),
+            }
+            // later expressions can't reuse the dynamics shared values are in
+            initial_dynamics = dynamics.clone();
+        }
+        if let Some(bucket) = bucket {
+            shared_dispatched.insert(bucket, bucket_shared);
+        }
+        operations.push((0, Instruction::RaiseOutput { boolean, id }));
+        ranges.push(start..operations.len());
+
+        match key {
+            None => always.push(i),
+            Some((name, key)) => {
+                let dispatch = dispatch.entry(name).or_insert_with(|| Dispatch {
+                    variable: variables[name].0,
+                    exists: exists.get(name).copied(),
+                    keys: match key {
+                        DispatchKey::String(_) => DispatchKeys::String(HashMap::new()),
+                        DispatchKey::Uint64(_) => DispatchKeys::Uint64(HashMap::new()),
+                        DispatchKey::Ip(_) => DispatchKeys::Ip(HashMap::new()),
+                    },
+                });
+                let expressions = match (&mut dispatch.keys, key) {
+                    (DispatchKeys::String(keys), DispatchKey::String(key)) => {
+                        keys.entry(key).or_default()
+                    }
+                    (DispatchKeys::Uint64(keys), DispatchKey::Uint64(key)) => {
+                        keys.entry(key).or_default()
+                    }
+                    (DispatchKeys::Ip(keys), DispatchKey::Ip(key)) => keys.entry(key).or_default(),
+                    _ => unreachable!("a variable only has one type"),
+                };
+                expressions.push(i);
+            }
+        }
         let Scratch {
             boolean,
             cidr,
+            duration,
+            float64,
             int64,
             ip,
             string,
+            timestamp,
             uint64,
             regex,
+            cidr_list,
+            int64_list,
+            ip_list,
+            string_list,
+            uint64_list,
         } = dynamics;
         //TODO: this sucks, do better than this
         if boolean.len() > max_size_dynamics.boolean.len() {
             max_size_dynamics.boolean = boolean;
         }
-        if cidr.len() > max_size_dynamics.boolean.len() {
+        if cidr.len() > max_size_dynamics.cidr.len() {
             max_size_dynamics.cidr = cidr;
         }
+        if duration.len() > max_size_dynamics.duration.len() {
+            max_size_dynamics.duration = duration;
+        }
+        if float64.len() > max_size_dynamics.float64.len() {
+            max_size_dynamics.float64 = float64;
+        }
         if int64.len() > max_size_dynamics.int64.len() {
             max_size_dynamics.int64 = int64;
         }
@@ -670,18 +4230,47 @@ where
         if string.len() > max_size_dynamics.string.len() {
             max_size_dynamics.string = string;
         }
+        if timestamp.len() > max_size_dynamics.timestamp.len() {
+            max_size_dynamics.timestamp = timestamp;
+        }
         if uint64.len() > max_size_dynamics.uint64.len() {
             max_size_dynamics.uint64 = uint64;
         }
         if regex.len() > max_size_dynamics.regex.len() {
             max_size_dynamics.regex = regex;
         }
+        if cidr_list.len() > max_size_dynamics.cidr_list.len() {
+            max_size_dynamics.cidr_list = cidr_list;
+        }
+        if int64_list.len() > max_size_dynamics.int64_list.len() {
+            max_size_dynamics.int64_list = int64_list;
+        }
+        if ip_list.len() > max_size_dynamics.ip_list.len() {
+            max_size_dynamics.ip_list = ip_list;
+        }
+        if string_list.len() > max_size_dynamics.string_list.len() {
+            max_size_dynamics.string_list = string_list;
+        }
+        if uint64_list.len() > max_size_dynamics.uint64_list.len() {
+            max_size_dynamics.uint64_list = uint64_list;
+        }
     }
 
     Ok(Engine {
         operations,
+        expressions: ranges,
+        always,
+        dispatch: dispatch.into_values().collect(),
+        overflow: options.overflow,
+        warnings,
         constants,
         reference_dynamics: max_size_dynamics,
-        variables,
+        // variables no expression reads are never loaded
+        variables: variables
+            .into_iter()
+            .filter(|(name, _)| used.contains(name))
+            .map(|(name, (index, field))| (index, exists.get(&name).copied(), field))
+            .collect(),
+        schema: None,
     })
 }
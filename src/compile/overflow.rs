@@ -0,0 +1,258 @@
+use std::time::{Duration, SystemTime};
+
+/// What an `Engine` does when integer arithmetic overflows
+///
+/// Timestamps don't have a boundary to wrap or clamp to, so an expression that overflows a
+/// timestamp never matches, whatever the policy
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Overflow {
+    /// The expression containing the overflowing operation doesn't match
+    #[default]
+    Checked,
+    /// Wrap around at the boundary of the type. Durations can't be negative
+    /// and don't wrap, so they saturate instead
+    Wrapping,
+    /// Clamp to the boundary of the type
+    Saturating,
+}
+
+pub(crate) enum Outcome<T> {
+    Value(T),
+    /// The operation overflowed; `None` if the policy was `Overflow::Checked`
+    Overflow(Option<T>),
+    DivisionByZero,
+}
+
+impl<T> Outcome<T> {
+    pub(crate) fn value(self) -> Option<T> {
+        match self {
+            Self::Value(value) | Self::Overflow(Some(value)) => Some(value),
+            Self::Overflow(None) | Self::DivisionByZero => None,
+        }
+    }
+}
+
+macro_rules! binary_operation {
+    ($name:ident, $type:ty, $checked:ident, $wrapping:ident, $saturating:ident) => {
+        pub(crate) fn $name(&self, left: $type, right: $type) -> Outcome<$type> {
+            match left.$checked(right) {
+                Some(value) => Outcome::Value(value),
+                None => Outcome::Overflow(match self {
+                    Self::Checked => None,
+                    Self::Wrapping => Some(left.$wrapping(right)),
+                    Self::Saturating => Some(left.$saturating(right)),
+                }),
+            }
+        }
+    };
+}
+
+macro_rules! integer_operations {
+    ($type:ty, $multiply:ident, $divide:ident, $modulo:ident, $exponent:ident) => {
+        binary_operation!($multiply, $type, checked_mul, wrapping_mul, saturating_mul);
+
+        pub(crate) fn $divide(&self, left: $type, right: $type) -> Outcome<$type> {
+            if right == 0 {
+                Outcome::DivisionByZero
+            } else {
+                // only `MIN / -1` can overflow
+                match left.checked_div(right) {
+                    Some(value) => Outcome::Value(value),
+                    None => Outcome::Overflow(match self {
+                        Self::Checked => None,
+                        Self::Wrapping => Some(left.wrapping_div(right)),
+                        Self::Saturating => Some(left.saturating_div(right)),
+                    }),
+                }
+            }
+        }
+
+        pub(crate) fn $modulo(&self, left: $type, right: $type) -> Outcome<$type> {
+            if right == 0 {
+                Outcome::DivisionByZero
+            } else {
+                // `MIN % -1` is 0, even though `checked_rem` considers it an overflow
+                Outcome::Value(left.wrapping_rem(right))
+            }
+        }
+
+        pub(crate) fn $exponent(&self, left: $type, right: u64) -> Outcome<$type> {
+            // exponentiation by squaring, as `pow` only takes a `u32` exponent
+            let mut base = left;
+            let mut exponent = right;
+            let mut value: $type = 1;
+            let mut wrapped: $type = 1;
+            let mut overflowed = false;
+            loop {
+                if exponent & 1 == 1 {
+                    wrapped = wrapped.wrapping_mul(base);
+                    match value.checked_mul(base) {
+                        Some(next) => value = next,
+                        None => overflowed = true,
+                    }
+                }
+                exponent >>= 1;
+                if exponent == 0 {
+                    break;
+                }
+                // a higher bit is set, so this square will be multiplied in later
+                overflowed |= base.checked_mul(base).is_none();
+                base = base.wrapping_mul(base);
+            }
+
+            if !overflowed {
+                Outcome::Value(value)
+            } else {
+                Outcome::Overflow(match self {
+                    Self::Checked => None,
+                    Self::Wrapping => Some(wrapped),
+                    #[allow(unused_comparisons)]
+                    Self::Saturating => Some(if left < 0 && right % 2 == 1 {
+                        <$type>::MIN
+                    } else {
+                        <$type>::MAX
+                    }),
+                })
+            }
+        }
+    };
+}
+
+impl Overflow {
+    binary_operation!(add_uint64, u64, checked_add, wrapping_add, saturating_add);
+    binary_operation!(
+        subtract_uint64,
+        u64,
+        checked_sub,
+        wrapping_sub,
+        saturating_sub
+    );
+    integer_operations!(
+        u64,
+        multiply_uint64,
+        divide_uint64,
+        modulo_uint64,
+        exponent_uint64
+    );
+    binary_operation!(add_int64, i64, checked_add, wrapping_add, saturating_add);
+    binary_operation!(
+        subtract_int64,
+        i64,
+        checked_sub,
+        wrapping_sub,
+        saturating_sub
+    );
+    integer_operations!(
+        i64,
+        multiply_int64,
+        divide_int64,
+        modulo_int64,
+        exponent_int64
+    );
+
+    pub(crate) fn negative_int64(&self, value: i64) -> Outcome<i64> {
+        match value.checked_neg() {
+            Some(value) => Outcome::Value(value),
+            None => Outcome::Overflow(match self {
+                Self::Checked => None,
+                Self::Wrapping => Some(value.wrapping_neg()),
+                Self::Saturating => Some(value.saturating_neg()),
+            }),
+        }
+    }
+
+    pub(crate) fn cast_uint64_int64(&self, value: u64) -> Outcome<i64> {
+        match i64::try_from(value) {
+            Ok(value) => Outcome::Value(value),
+            Err(_) => Outcome::Overflow(match self {
+                Self::Checked => None,
+                Self::Wrapping => Some(value as i64),
+                Self::Saturating => Some(i64::MAX),
+            }),
+        }
+    }
+
+    pub(crate) fn negative_uint64(&self, value: u64) -> Outcome<i64> {
+        if value <= i64::MAX as u64 {
+            Outcome::Value(-(value as i64))
+        } else if value == i64::MIN.unsigned_abs() {
+            Outcome::Value(i64::MIN)
+        } else {
+            Outcome::Overflow(match self {
+                Self::Checked => None,
+                Self::Wrapping => Some((value as i64).wrapping_neg()),
+                Self::Saturating => Some(i64::MIN),
+            })
+        }
+    }
+
+    fn duration(&self, value: Option<Duration>, saturated: Duration) -> Outcome<Duration> {
+        match value {
+            Some(value) => Outcome::Value(value),
+            None => Outcome::Overflow(match self {
+                Self::Checked => None,
+                Self::Wrapping | Self::Saturating => Some(saturated),
+            }),
+        }
+    }
+
+    pub(crate) fn add_duration(&self, left: Duration, right: Duration) -> Outcome<Duration> {
+        self.duration(left.checked_add(right), Duration::MAX)
+    }
+
+    pub(crate) fn subtract_duration(&self, left: Duration, right: Duration) -> Outcome<Duration> {
+        self.duration(left.checked_sub(right), Duration::ZERO)
+    }
+
+    pub(crate) fn multiply_duration(&self, left: Duration, right: u64) -> Outcome<Duration> {
+        // `Duration::checked_mul` only takes a `u32`
+        let value = left
+            .as_nanos()
+            .checked_mul(right as u128)
+            .and_then(|nanos| {
+                let seconds = u64::try_from(nanos / 1_000_000_000).ok()?;
+                Some(Duration::new(seconds, (nanos % 1_000_000_000) as u32))
+            });
+        self.duration(value, Duration::MAX)
+    }
+
+    pub(crate) fn divide_duration(&self, left: Duration, right: u64) -> Outcome<Duration> {
+        if right == 0 {
+            Outcome::DivisionByZero
+        } else {
+            let nanos = left.as_nanos() / right as u128;
+            // can't overflow, as `right` is at least 1
+            Outcome::Value(Duration::new(
+                (nanos / 1_000_000_000) as u64,
+                (nanos % 1_000_000_000) as u32,
+            ))
+        }
+    }
+
+    pub(crate) fn add_timestamp(&self, left: SystemTime, right: Duration) -> Outcome<SystemTime> {
+        match left.checked_add(right) {
+            Some(value) => Outcome::Value(value),
+            None => Outcome::Overflow(None),
+        }
+    }
+
+    pub(crate) fn subtract_timestamp(
+        &self,
+        left: SystemTime,
+        right: Duration,
+    ) -> Outcome<SystemTime> {
+        match left.checked_sub(right) {
+            Some(value) => Outcome::Value(value),
+            None => Outcome::Overflow(None),
+        }
+    }
+
+    pub(crate) fn subtract_timestamps(
+        &self,
+        left: SystemTime,
+        right: SystemTime,
+    ) -> Outcome<Duration> {
+        // a later `right` would be a negative duration
+        self.duration(left.duration_since(right).ok(), Duration::ZERO)
+    }
+}
@@ -1,33 +1,63 @@
 pub mod compile;
-pub mod lex;
 pub mod parse;
 pub mod variables;
 
-pub use crate::compile::{compile, compile_unsafe, Engine};
-pub use crate::parse::{nodes::boolean::NodeBoolean, Ast};
-pub use chert_derive::Variables;
+pub use crate::compile::{compile, compile_schema, compile_unsafe, Engine, EvalContext};
+pub use crate::parse::{format::format, nodes::boolean::NodeBoolean, Ast};
+pub use crate::variables::dynamic::{Record, Schema};
+pub use crate::variables::Type;
+pub use chert_derive::{expr, Variables};
+pub use chert_parse::{diagnostic, lex, ParseError};
 
-#[derive(Debug)]
-pub enum ParseError {
-    Lex(crate::lex::Error),
-    Parse(crate::parse::Error),
+/// Any error that can occur between an expression string and a compiled [`Engine`]
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Parse(ParseError),
+    Compile(crate::compile::Error),
 }
 
-impl From<crate::lex::Error> for ParseError {
-    fn from(value: crate::lex::Error) -> Self {
-        Self::Lex(value)
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(_) => write!(f, "failed to parse expression"),
+            Self::Compile(_) => write!(f, "failed to compile expression"),
+        }
     }
 }
 
-impl From<crate::parse::Error> for ParseError {
-    fn from(value: crate::parse::Error) -> Self {
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::Compile(error) => Some(error),
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
         Self::Parse(value)
     }
 }
 
+impl From<crate::compile::Error> for Error {
+    fn from(value: crate::compile::Error) -> Self {
+        Self::Compile(value)
+    }
+}
+
 pub fn parse<T: crate::variables::Variables>(
     expression: &str,
 ) -> Result<Ast<T, NodeBoolean>, ParseError> {
     let tokens = crate::lex::lex(expression)?;
     Ok(crate::parse::parse_boolean::<T>(tokens)?)
 }
+
+/// Like [`parse`], but with variables looked up in a schema defined at runtime
+pub fn parse_with_schema(
+    expression: &str,
+    schema: &Schema,
+) -> Result<Ast<Record, NodeBoolean>, ParseError> {
+    let tokens = crate::lex::lex(expression)?;
+    Ok(crate::parse::parse_boolean_with_schema(tokens, schema)?)
+}
@@ -1,250 +0,0 @@
-use super::nodes::boolean::{
-    NodeBoolean, NodeBooleanBoth, NodeBooleanEither, NodeBooleanEquals, NodeBooleanMatches,
-    NodeBooleanNot, NodeBooleanWithin,
-};
-use super::nodes::int64::{NodeInt64, NodeInt64Negative};
-use super::nodes::string::{NodeString, NodeStringAdd};
-use super::nodes::uint64::{NodeUint64, NodeUint64Add, NodeUint64Subtract};
-use super::nodes::Node;
-
-#[derive(Debug)]
-pub enum UnaryOperator {
-    Negative,
-    Not,
-    Positive,
-}
-
-#[derive(Debug)]
-pub enum BinaryOperator {
-    Exponent,
-    Multiply,
-    Divide,
-    Modulo,
-    Both,
-    Either,
-    Add,
-    Subtract,
-    Within,
-    Equals,
-    Matches,
-}
-
-#[derive(Debug)]
-pub enum ScopeOperator {
-    Open(char),
-    Close,
-}
-
-#[derive(Debug)]
-pub enum Operator {
-    Unary(UnaryOperator),
-    Binary(BinaryOperator),
-    Scope(ScopeOperator),
-}
-
-#[derive(Debug)]
-pub enum Error {
-    UnknownOperator,
-    BadOperands,
-}
-
-impl UnaryOperator {
-    pub(crate) fn parse(token: &str) -> Option<Self> {
-        Some(match token {
-            "!" => Self::Not,
-            "+" => Self::Positive,
-            "-" => Self::Negative,
-            _ => {
-                return None;
-            }
-        })
-    }
-
-    pub(crate) fn to_node(&self, node: Node) -> Result<Node, Node> {
-        Ok(match self {
-            Self::Not => match node {
-                Node::Boolean(node) => {
-                    Node::Boolean(NodeBoolean::Not(NodeBooleanNot::Boolean(Box::new(node))))
-                }
-                node => {
-                    return Err(node);
-                }
-            },
-            Self::Positive => match node {
-                Node::Uint64(node) => Node::Uint64(node),
-                node => {
-                    return Err(node);
-                }
-            },
-            Self::Negative => match node {
-                Node::Uint64(node) => Node::Int64(NodeInt64::Negative(NodeInt64Negative::Uint64(
-                    Box::new(node),
-                ))),
-                node => {
-                    return Err(node);
-                }
-            },
-        })
-    }
-}
-
-impl BinaryOperator {
-    pub(crate) fn parse(token: &str) -> Option<Self> {
-        Some(match token {
-            "**" => Self::Exponent,
-            "&&" => Self::Both,
-            "||" => Self::Either,
-            "==" => Self::Equals,
-            "+" => Self::Add,
-            "-" => Self::Subtract,
-            "~" => Self::Matches,
-            _ => {
-                return None;
-            }
-        })
-    }
-
-    pub(crate) fn to_node(&self, left: Node, right: Node) -> Result<Node, (Node, Node)> {
-        Ok(match self {
-            Self::Both => match (left, right) {
-                (Node::Boolean(left), Node::Boolean(right)) => {
-                    Node::Boolean(NodeBoolean::Both(NodeBooleanBoth::BooleanBoolean {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Either => match (left, right) {
-                (Node::Boolean(left), Node::Boolean(right)) => {
-                    Node::Boolean(NodeBoolean::Either(NodeBooleanEither::BooleanBoolean {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Equals => match (left, right) {
-                (Node::Boolean(left), Node::Boolean(right)) => {
-                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::BooleanBoolean {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (Node::Uint64(left), Node::Uint64(right)) => {
-                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::Uint64Uint64 {
-                        left,
-                        right,
-                    }))
-                }
-                (Node::Int64(left), Node::Int64(right)) => {
-                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::Int64Int64 {
-                        left,
-                        right,
-                    }))
-                }
-                (Node::Ip(left), Node::Ip(right)) => {
-                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::IpIp { left, right }))
-                }
-                (Node::String(left), Node::String(right)) => {
-                    Node::Boolean(NodeBoolean::Equals(NodeBooleanEquals::StringString {
-                        left,
-                        right,
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Add => match (left, right) {
-                (Node::String(left), Node::String(right)) => {
-                    Node::String(NodeString::Add(NodeStringAdd::StringString {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (Node::Uint64(left), Node::Uint64(right)) => {
-                    Node::Uint64(NodeUint64::Add(NodeUint64Add::Uint64Uint64 {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Subtract => match (left, right) {
-                (Node::Uint64(left), Node::Uint64(right)) => {
-                    Node::Uint64(NodeUint64::Subtract(NodeUint64Subtract::Uint64Uint64 {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Within => match (left, right) {
-                (Node::Ip(left), Node::Cidr(right)) => {
-                    Node::Boolean(NodeBoolean::Within(NodeBooleanWithin::IpCidr {
-                        left,
-                        right,
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            Self::Matches => match (left, right) {
-                (Node::String(left), Node::Regex(right)) => {
-                    Node::Boolean(NodeBoolean::Matches(NodeBooleanMatches::StringRegex {
-                        left,
-                        right,
-                    }))
-                }
-                (left, right) => {
-                    return Err((left, right));
-                }
-            },
-            _ => unreachable!(),
-        })
-    }
-}
-
-#[derive(Eq, PartialEq)]
-pub enum Associativity {
-    Left,
-    Right,
-}
-
-impl Operator {
-    pub(crate) fn associativity(&self) -> Associativity {
-        match self {
-            Self::Unary(_) => Associativity::Right,
-            Self::Scope(_) => Associativity::Left,
-            Self::Binary(operator) => match operator {
-                BinaryOperator::Exponent => Associativity::Right,
-                _ => Associativity::Left,
-            },
-        }
-    }
-
-    pub(crate) fn specificity(&self) -> u8 {
-        match self {
-            Self::Scope(_) => 0,
-            Self::Binary(operator) => match operator {
-                BinaryOperator::Either => 2,
-                BinaryOperator::Both => 3,
-                BinaryOperator::Equals | BinaryOperator::Matches | BinaryOperator::Within => 4,
-                BinaryOperator::Add | BinaryOperator::Subtract => 5,
-                BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 6,
-                BinaryOperator::Exponent => 7,
-            },
-            Self::Unary(_) => 255,
-        }
-    }
-}
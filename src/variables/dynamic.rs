@@ -0,0 +1,369 @@
+//! Variables defined at runtime, for when there's no Rust struct to derive `Variables` on
+
+use super::{Accessor, Type, Variable};
+use cidr::{IpCidr, Ipv4Cidr};
+use regex::Regex;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    DuplicateVariable {
+        name: String,
+    },
+    UnknownVariable {
+        name: String,
+    },
+    NotOptional {
+        name: String,
+    },
+    TypeMismatch {
+        name: String,
+        expected: Type,
+        found: Type,
+    },
+    SchemaMismatch,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateVariable { name } => write!(f, "variable `{name}` is defined twice"),
+            Self::UnknownVariable { name } => write!(f, "unknown variable `{name}`"),
+            Self::NotOptional { name } => write!(f, "variable `{name}` isn't optional"),
+            Self::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(f, "variable `{name}` is of type {expected}, not {found}"),
+            Self::SchemaMismatch => {
+                write!(
+                    f,
+                    "record doesn't match the schema the engine was compiled with"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug)]
+struct SchemaInner {
+    variables: Vec<(String, Type)>,
+    indices: HashMap<String, usize>,
+    /// Whether records can leave variables unset, which `exists` then tells apart
+    optional: bool,
+}
+
+/// A set of named, typed variables. Cheap to clone
+#[derive(Clone, Debug)]
+pub struct Schema(Arc<SchemaInner>);
+
+/// A schema is only equal to itself and its clones, not to another schema that happens to define
+/// the same variables, so comparing schemas doesn't depend on how many variables they have
+impl PartialEq for Schema {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Schema {}
+
+impl Schema {
+    pub fn new<S: Into<String>, I: IntoIterator<Item = (S, Type)>>(
+        variables: I,
+    ) -> Result<Self, Error> {
+        Self::new_inner(variables, false)
+    }
+
+    /// Like `new`, but every variable is optional; records start with them all unset, and
+    /// `Record::unset` unsets them again
+    pub fn new_optional<S: Into<String>, I: IntoIterator<Item = (S, Type)>>(
+        variables: I,
+    ) -> Result<Self, Error> {
+        Self::new_inner(variables, true)
+    }
+
+    fn new_inner<S: Into<String>, I: IntoIterator<Item = (S, Type)>>(
+        variables: I,
+        optional: bool,
+    ) -> Result<Self, Error> {
+        let mut inner = SchemaInner {
+            variables: Vec::new(),
+            indices: HashMap::new(),
+            optional,
+        };
+        for (name, type_) in variables {
+            let name = name.into();
+            if inner.indices.contains_key(&name) {
+                return Err(Error::DuplicateVariable { name });
+            }
+            inner.indices.insert(name.clone(), inner.variables.len());
+            inner.variables.push((name, type_));
+        }
+        Ok(Self(Arc::new(inner)))
+    }
+
+    /// The type of the variable called `name`, if there is one
+    pub fn get(&self, name: &str) -> Option<Type> {
+        self.0
+            .indices
+            .get(name)
+            .map(|index| self.0.variables[*index].1)
+    }
+
+    /// Variables in the order they were defined
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Type)> {
+        self.0
+            .variables
+            .iter()
+            .map(|(name, type_)| (name.as_str(), *type_))
+    }
+
+    /// Whether every variable is optional, as with `new_optional`
+    pub fn is_optional(&self) -> bool {
+        self.0.optional
+    }
+
+    /// A record for this schema, with every variable set to its type's default, or unset if
+    /// variables are optional
+    pub fn record(&self) -> Record {
+        Record {
+            schema: self.clone(),
+            values: self
+                .0
+                .variables
+                .iter()
+                .map(|(_, type_)| (!self.0.optional).then(|| Value::default_of(*type_)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn variables(&self) -> Vec<(String, Variable<Record>)> {
+        let optional = self.0.optional;
+        self.0
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, (name, type_))| {
+                fn accessor<V: ?Sized>(
+                    entry: fn(&Record, usize) -> Option<&V>,
+                    index: usize,
+                    optional: bool,
+                ) -> Accessor<Record, V> {
+                    match optional {
+                        true => Accessor::OptionalIndex(entry, index),
+                        false => Accessor::Index(entry, index),
+                    }
+                }
+                let variable = match type_ {
+                    Type::Boolean => Variable::Boolean(accessor(boolean, index, optional)),
+                    Type::Cidr => Variable::Cidr(accessor(cidr, index, optional)),
+                    Type::Duration => Variable::Duration(accessor(duration, index, optional)),
+                    Type::Float64 => Variable::Float64(accessor(float64, index, optional)),
+                    Type::Int64 => Variable::Int64(accessor(int64, index, optional)),
+                    Type::Ip => Variable::Ip(accessor(ip, index, optional)),
+                    Type::Regex => Variable::Regex(accessor(regex, index, optional)),
+                    Type::String => Variable::String(accessor(string, index, optional)),
+                    Type::Timestamp => Variable::Timestamp(accessor(timestamp, index, optional)),
+                    Type::Uint64 => Variable::Uint64(accessor(uint64, index, optional)),
+                    Type::CidrList => Variable::CidrList(accessor(cidr_list, index, optional)),
+                    Type::Int64List => Variable::Int64List(accessor(int64_list, index, optional)),
+                    Type::IpList => Variable::IpList(accessor(ip_list, index, optional)),
+                    Type::StringList => {
+                        Variable::StringList(accessor(string_list, index, optional))
+                    }
+                    Type::Uint64List => {
+                        Variable::Uint64List(accessor(uint64_list, index, optional))
+                    }
+                };
+                (name.clone(), variable)
+            })
+            .collect()
+    }
+}
+
+/// A value for one variable in a [`Record`]
+#[derive(Clone, Debug)]
+pub enum Value {
+    Boolean(bool),
+    Cidr(IpCidr),
+    Duration(Duration),
+    Float64(f64),
+    Int64(i64),
+    Ip(IpAddr),
+    Regex(Regex),
+    String(String),
+    Timestamp(SystemTime),
+    Uint64(u64),
+    CidrList(Vec<IpCidr>),
+    Int64List(Vec<i64>),
+    IpList(Vec<IpAddr>),
+    StringList(Vec<String>),
+    Uint64List(Vec<u64>),
+}
+
+impl Value {
+    fn default_of(type_: Type) -> Self {
+        match type_ {
+            Type::Boolean => Self::Boolean(false),
+            Type::Cidr => Self::Cidr(IpCidr::V4(Ipv4Cidr::new_host(Ipv4Addr::from(0)))),
+            Type::Duration => Self::Duration(Duration::ZERO),
+            Type::Float64 => Self::Float64(0.0),
+            Type::Int64 => Self::Int64(0),
+            Type::Ip => Self::Ip(IpAddr::V4(Ipv4Addr::from(0))),
+            Type::Regex => Self::Regex(Regex::new("").unwrap()),
+            Type::String => Self::String(String::new()),
+            Type::Timestamp => Self::Timestamp(UNIX_EPOCH),
+            Type::Uint64 => Self::Uint64(0),
+            Type::CidrList => Self::CidrList(Vec::new()),
+            Type::Int64List => Self::Int64List(Vec::new()),
+            Type::IpList => Self::IpList(Vec::new()),
+            Type::StringList => Self::StringList(Vec::new()),
+            Type::Uint64List => Self::Uint64List(Vec::new()),
+        }
+    }
+
+    pub fn type_of(&self) -> Type {
+        match self {
+            Self::Boolean(_) => Type::Boolean,
+            Self::Cidr(_) => Type::Cidr,
+            Self::Duration(_) => Type::Duration,
+            Self::Float64(_) => Type::Float64,
+            Self::Int64(_) => Type::Int64,
+            Self::Ip(_) => Type::Ip,
+            Self::Regex(_) => Type::Regex,
+            Self::String(_) => Type::String,
+            Self::Timestamp(_) => Type::Timestamp,
+            Self::Uint64(_) => Type::Uint64,
+            Self::CidrList(_) => Type::CidrList,
+            Self::Int64List(_) => Type::Int64List,
+            Self::IpList(_) => Type::IpList,
+            Self::StringList(_) => Type::StringList,
+            Self::Uint64List(_) => Type::Uint64List,
+        }
+    }
+}
+
+macro_rules! value_from {
+    ($type:ty, $variant:ident) => {
+        impl From<$type> for Value {
+            fn from(value: $type) -> Self {
+                Self::$variant(value)
+            }
+        }
+    };
+}
+
+value_from!(bool, Boolean);
+value_from!(IpCidr, Cidr);
+value_from!(Duration, Duration);
+value_from!(f64, Float64);
+value_from!(i64, Int64);
+value_from!(IpAddr, Ip);
+value_from!(Regex, Regex);
+value_from!(String, String);
+value_from!(SystemTime, Timestamp);
+value_from!(u64, Uint64);
+value_from!(Vec<IpCidr>, CidrList);
+value_from!(Vec<i64>, Int64List);
+value_from!(Vec<IpAddr>, IpList);
+value_from!(Vec<String>, StringList);
+value_from!(Vec<u64>, Uint64List);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+/// Values for every variable in a [`Schema`], which an `Engine` compiled with that schema can be
+/// evaluated against. A record from any other schema is refused by `Engine::try_eval`, and
+/// panics `Engine::eval`
+#[derive(Clone, Debug)]
+pub struct Record {
+    schema: Schema,
+    values: Vec<Option<Value>>,
+}
+
+impl Record {
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// `None` if there's no variable called `name`, or if it's unset
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.schema
+            .0
+            .indices
+            .get(name)
+            .and_then(|index| self.values[*index].as_ref())
+    }
+
+    /// Set the variable called `name`, which must exist in the schema with the same type as `value`
+    pub fn set<V: Into<Value>>(&mut self, name: &str, value: V) -> Result<(), Error> {
+        let value = value.into();
+        let Some(index) = self.schema.0.indices.get(name) else {
+            return Err(Error::UnknownVariable {
+                name: name.to_owned(),
+            });
+        };
+        let expected = self.schema.0.variables[*index].1;
+        if value.type_of() != expected {
+            return Err(Error::TypeMismatch {
+                name: name.to_owned(),
+                expected,
+                found: value.type_of(),
+            });
+        }
+        self.values[*index] = Some(value);
+        Ok(())
+    }
+
+    /// Unset the variable called `name`, which must exist in the schema, and the schema's
+    /// variables must be optional
+    pub fn unset(&mut self, name: &str) -> Result<(), Error> {
+        let Some(index) = self.schema.0.indices.get(name) else {
+            return Err(Error::UnknownVariable {
+                name: name.to_owned(),
+            });
+        };
+        if !self.schema.0.optional {
+            return Err(Error::NotOptional {
+                name: name.to_owned(),
+            });
+        }
+        self.values[*index] = None;
+        Ok(())
+    }
+}
+
+macro_rules! record_entry {
+    ($name:ident, $type:ty, $variant:ident) => {
+        fn $name(record: &Record, index: usize) -> Option<&$type> {
+            match record.values.get(index) {
+                Some(Some(Value::$variant(value))) => Some(value),
+                _ => None,
+            }
+        }
+    };
+}
+
+record_entry!(boolean, bool, Boolean);
+record_entry!(cidr, IpCidr, Cidr);
+record_entry!(duration, Duration, Duration);
+record_entry!(float64, f64, Float64);
+record_entry!(int64, i64, Int64);
+record_entry!(ip, IpAddr, Ip);
+record_entry!(regex, Regex, Regex);
+record_entry!(string, str, String);
+record_entry!(timestamp, SystemTime, Timestamp);
+record_entry!(uint64, u64, Uint64);
+record_entry!(cidr_list, [IpCidr], CidrList);
+record_entry!(int64_list, [i64], Int64List);
+record_entry!(ip_list, [IpAddr], IpList);
+record_entry!(string_list, [String], StringList);
+record_entry!(uint64_list, [u64], Uint64List);
@@ -0,0 +1,218 @@
+//! Variables bound to [JSON pointers](https://www.rfc-editor.org/rfc/rfc6901) in a
+//! `serde_json::Value`, for evaluating against events without a struct per event type.
+//!
+//! JSON values are coerced to a variable's type as follows, anything else is
+//! [`Error::WrongType`]:
+//!
+//! - `boolean`: `true` or `false`
+//! - `uint64`, `int64`: an integer in range; `1.0` is a float, not an integer
+//! - `float64`: any number
+//! - `string`: a string
+//! - `ip`, `cidr`: a string in standard notation, e.g. `"10.0.0.1"` or `"10.0.0.0/8"`
+//! - `duration`: a string in expression syntax (e.g. `"1h30m"`) or a non-negative number of
+//!   seconds
+//! - `timestamp`: an RFC 3339 string (e.g. `"2024-01-01T00:00:00Z"`) or a number of seconds
+//!   since the Unix epoch
+//! - `[cidr]`, `[int64]`, `[ip]`, `[string]`, `[uint64]`: an array, each element coerced as above
+//!
+//! Regexes can't be read from JSON, because they'd have to be compiled again for every event;
+//! binding a `regex` variable is [`Error::UnsupportedType`].
+//!
+//! Every variable is optional. A pointer that resolves to nothing, or to `null`, leaves its
+//! variable missing, so `exists(count)` is false and comparisons reading `count` are false too.
+//! There's no default to fall back on, because a default would let rules like `count == 0` match
+//! events that don't have a count at all.
+
+use super::dynamic::{Record, Schema, Value};
+use super::Type;
+use crate::lex::Token;
+use cidr::IpCidr;
+use std::net::IpAddr;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    Schema(super::dynamic::Error),
+    UnsupportedType {
+        name: String,
+        type_: Type,
+    },
+    WrongType {
+        name: String,
+        pointer: String,
+        expected: Type,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Schema(error) => write!(f, "{error}"),
+            Self::UnsupportedType { name, type_ } => {
+                write!(
+                    f,
+                    "variable `{name}` is {type_}, which can't be read from JSON"
+                )
+            }
+            Self::WrongType {
+                name,
+                pointer,
+                expected,
+            } => write!(
+                f,
+                "variable `{name}` can't be read as {expected} (at `{pointer}`)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Schema(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<super::dynamic::Error> for Error {
+    fn from(error: super::dynamic::Error) -> Self {
+        Self::Schema(error)
+    }
+}
+
+/// A [`Schema`] where each variable is read from a JSON pointer
+#[derive(Clone, Debug)]
+pub struct Bindings {
+    schema: Schema,
+    pointers: Vec<String>,
+}
+
+impl Bindings {
+    /// `variables` are `(name, pointer, type)`, e.g. `("src", "/source/ip", Type::Ip)`
+    pub fn new<S: Into<String>, P: Into<String>, I: IntoIterator<Item = (S, P, Type)>>(
+        variables: I,
+    ) -> Result<Self, Error> {
+        let mut names = Vec::new();
+        let mut pointers = Vec::new();
+        for (name, pointer, type_) in variables {
+            let name = name.into();
+            if type_ == Type::Regex {
+                return Err(Error::UnsupportedType { name, type_ });
+            }
+            names.push((name, type_));
+            pointers.push(pointer.into());
+        }
+        let schema = Schema::new_optional(names)?;
+        Ok(Self { schema, pointers })
+    }
+
+    /// The schema to parse and compile expressions with
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Read every variable out of `json`, leaving those that are missing or `null` unset and
+    /// failing on the first that can't be coerced to its type
+    pub fn record(&self, json: &serde_json::Value) -> Result<Record, Error> {
+        let mut record = self.schema.record();
+        for ((name, type_), pointer) in self.schema.iter().zip(&self.pointers) {
+            let value = match json.pointer(pointer) {
+                None | Some(serde_json::Value::Null) => continue,
+                Some(value) => coerce(value, type_).ok_or_else(|| Error::WrongType {
+                    name: name.to_owned(),
+                    pointer: pointer.clone(),
+                    expected: type_,
+                })?,
+            };
+            // can't fail; the value was coerced to the schema's type
+            record.set(name, value).unwrap();
+        }
+        Ok(record)
+    }
+}
+
+/// Lex `value` as a single literal token, e.g. a duration or a timestamp
+fn literal(value: &str) -> Option<Token> {
+    let mut tokens = crate::lex::lex(value).ok()?;
+    match tokens.pop() {
+        Some((token, span)) if tokens.is_empty() && span == (0..value.len()) => Some(token),
+        _ => None,
+    }
+}
+
+fn coerce(json: &serde_json::Value, type_: Type) -> Option<Value> {
+    use serde_json::Value as Json;
+
+    Some(match (type_, json) {
+        (Type::Boolean, Json::Bool(value)) => Value::Boolean(*value),
+        (Type::Uint64, Json::Number(value)) => Value::Uint64(value.as_u64()?),
+        (Type::Int64, Json::Number(value)) => Value::Int64(value.as_i64()?),
+        (Type::Float64, Json::Number(value)) => Value::Float64(value.as_f64()?),
+        (Type::String, Json::String(value)) => Value::String(value.clone()),
+        (Type::Ip, Json::String(value)) => Value::Ip(value.parse::<IpAddr>().ok()?),
+        (Type::Cidr, Json::String(value)) => Value::Cidr(value.parse::<IpCidr>().ok()?),
+        (Type::Duration, Json::String(value)) => match literal(value)? {
+            Token::Duration(value) => Value::Duration(value),
+            _ => return None,
+        },
+        (Type::Duration, Json::Number(value)) => {
+            Value::Duration(Duration::try_from_secs_f64(value.as_f64()?).ok()?)
+        }
+        (Type::Timestamp, Json::String(value)) => match literal(value)? {
+            Token::Timestamp(value) => Value::Timestamp(value),
+            _ => return None,
+        },
+        (Type::Timestamp, Json::Number(value)) => {
+            let seconds = value.as_f64()?;
+            let offset = Duration::try_from_secs_f64(seconds.abs()).ok()?;
+            Value::Timestamp(if seconds < 0.0 {
+                UNIX_EPOCH.checked_sub(offset)?
+            } else {
+                UNIX_EPOCH.checked_add(offset)?
+            })
+        }
+        (Type::CidrList, Json::Array(values)) => {
+            Value::CidrList(coerce_list(values, Type::Cidr, |value| match value {
+                Value::Cidr(value) => Some(value),
+                _ => None,
+            })?)
+        }
+        (Type::Int64List, Json::Array(values)) => {
+            Value::Int64List(coerce_list(values, Type::Int64, |value| match value {
+                Value::Int64(value) => Some(value),
+                _ => None,
+            })?)
+        }
+        (Type::IpList, Json::Array(values)) => {
+            Value::IpList(coerce_list(values, Type::Ip, |value| match value {
+                Value::Ip(value) => Some(value),
+                _ => None,
+            })?)
+        }
+        (Type::StringList, Json::Array(values)) => {
+            Value::StringList(coerce_list(values, Type::String, |value| match value {
+                Value::String(value) => Some(value),
+                _ => None,
+            })?)
+        }
+        (Type::Uint64List, Json::Array(values)) => {
+            Value::Uint64List(coerce_list(values, Type::Uint64, |value| match value {
+                Value::Uint64(value) => Some(value),
+                _ => None,
+            })?)
+        }
+        _ => return None,
+    })
+}
+
+fn coerce_list<V>(
+    values: &[serde_json::Value],
+    type_: Type,
+    unwrap: fn(Value) -> Option<V>,
+) -> Option<Vec<V>> {
+    values
+        .iter()
+        .map(|value| unwrap(coerce(value, type_)?))
+        .collect()
+}
@@ -1,53 +1,370 @@
+pub mod dynamic;
+#[cfg(feature = "json")]
+pub mod json;
+
 use cidr::IpCidr;
 use regex::Regex;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+pub use chert_parse::{Type, UnknownType};
 
 pub trait VariableType {
     type AccessedAs: ?Sized;
 
-    fn from_field<T>(field: fn(&T) -> &Self::AccessedAs) -> Variable<T>;
+    /// Read a value of this type as what variables of it are accessed as, which is how
+    /// `#[derive(Variables)]` reads fields, e.g. a `String` as a `str`
+    fn access(&self) -> &Self::AccessedAs;
+
+    fn from_accessor<T>(accessor: Accessor<T, Self::AccessedAs>) -> Variable<T>;
+
+    fn from_field<T>(field: fn(&T) -> &Self::AccessedAs) -> Variable<T> {
+        Self::from_accessor(Accessor::Field(field))
+    }
+
+    /// For `Option<_>` fields, where `None` means the variable is missing
+    fn from_optional_field<T>(field: fn(&T) -> Option<&Self::AccessedAs>) -> Variable<T> {
+        Self::from_accessor(Accessor::OptionalField(field))
+    }
+}
+
+type Path<T, V> = Arc<dyn for<'a> Fn(&'a T) -> Option<&'a V> + Send + Sync>;
+
+/// How to get a variable's value out of a `T`
+#[non_exhaustive]
+pub enum Accessor<T, V: ?Sized> {
+    /// A field of a struct, as generated by `#[derive(Variables)]`
+    Field(fn(&T) -> &V),
+    /// A field of a struct that might not have a value
+    OptionalField(fn(&T) -> Option<&V>),
+    /// An entry in something indexable, e.g. a `dynamic::Record`, which is missing if there's no
+    /// entry of the right type at the index
+    Index(fn(&T, usize) -> Option<&V>, usize),
+    /// An entry in something indexable that might not have a value
+    OptionalIndex(fn(&T, usize) -> Option<&V>, usize),
+    /// Anything else, e.g. a field of a field
+    Path { path: Path<T, V>, optional: bool },
+}
+
+impl<T, V: ?Sized> Accessor<T, V> {
+    /// `None` if the variable is missing, which only optional accessors can be
+    pub fn get<'a>(&self, variables: &'a T) -> Option<&'a V> {
+        match self {
+            Self::Field(field) => Some(field(variables)),
+            Self::OptionalField(field) => field(variables),
+            Self::Index(entry, index) | Self::OptionalIndex(entry, index) => {
+                entry(variables, *index)
+            }
+            Self::Path { path, .. } => path(variables),
+        }
+    }
+
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Self::Field(_) | Self::Index(_, _) => false,
+            Self::OptionalField(_) | Self::OptionalIndex(_, _) => true,
+            Self::Path { optional, .. } => *optional,
+        }
+    }
+}
+
+impl<T: 'static, V: ?Sized + 'static> Accessor<T, V> {
+    /// Reach this accessor's value through `parent`
+    pub fn nest<U: 'static>(self, parent: fn(&U) -> &T) -> Accessor<U, V> {
+        fn path<U, V: ?Sized, F: for<'a> Fn(&'a U) -> Option<&'a V>>(path: F) -> F {
+            path
+        }
+        Accessor::Path {
+            optional: self.is_optional(),
+            path: Arc::new(path(move |variables| self.get(parent(variables)))),
+        }
+    }
+}
+
+// derived `Clone` would need `T` and `V` to be `Clone` too
+impl<T, V: ?Sized> Clone for Accessor<T, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Field(field) => Self::Field(*field),
+            Self::OptionalField(field) => Self::OptionalField(*field),
+            Self::Index(entry, index) => Self::Index(*entry, *index),
+            Self::OptionalIndex(entry, index) => Self::OptionalIndex(*entry, *index),
+            Self::Path { path, optional } => Self::Path {
+                path: path.clone(),
+                optional: *optional,
+            },
+        }
+    }
 }
 
-#[derive(Clone)]
+#[non_exhaustive]
 pub enum Variable<T> {
-    Boolean(fn(&T) -> &bool),
-    Cidr(fn(&T) -> &IpCidr),
-    Int64(fn(&T) -> &i64),
-    Ip(fn(&T) -> &IpAddr),
-    String(fn(&T) -> &str),
-    Uint64(fn(&T) -> &u64),
-    Regex(fn(&T) -> &Regex),
+    Boolean(Accessor<T, bool>),
+    Cidr(Accessor<T, IpCidr>),
+    Duration(Accessor<T, Duration>),
+    Float64(Accessor<T, f64>),
+    Int64(Accessor<T, i64>),
+    Ip(Accessor<T, IpAddr>),
+    String(Accessor<T, str>),
+    Timestamp(Accessor<T, SystemTime>),
+    Uint64(Accessor<T, u64>),
+    Regex(Accessor<T, Regex>),
+    CidrList(Accessor<T, [IpCidr]>),
+    Int64List(Accessor<T, [i64]>),
+    IpList(Accessor<T, [IpAddr]>),
+    StringList(Accessor<T, [String]>),
+    Uint64List(Accessor<T, [u64]>),
+}
+
+impl<T> Clone for Variable<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Boolean(accessor) => Self::Boolean(accessor.clone()),
+            Self::Cidr(accessor) => Self::Cidr(accessor.clone()),
+            Self::Duration(accessor) => Self::Duration(accessor.clone()),
+            Self::Float64(accessor) => Self::Float64(accessor.clone()),
+            Self::Int64(accessor) => Self::Int64(accessor.clone()),
+            Self::Ip(accessor) => Self::Ip(accessor.clone()),
+            Self::String(accessor) => Self::String(accessor.clone()),
+            Self::Timestamp(accessor) => Self::Timestamp(accessor.clone()),
+            Self::Uint64(accessor) => Self::Uint64(accessor.clone()),
+            Self::Regex(accessor) => Self::Regex(accessor.clone()),
+            Self::CidrList(accessor) => Self::CidrList(accessor.clone()),
+            Self::Int64List(accessor) => Self::Int64List(accessor.clone()),
+            Self::IpList(accessor) => Self::IpList(accessor.clone()),
+            Self::StringList(accessor) => Self::StringList(accessor.clone()),
+            Self::Uint64List(accessor) => Self::Uint64List(accessor.clone()),
+        }
+    }
+}
+
+impl<T: 'static> Variable<T> {
+    /// This variable, reached through `parent`; for a struct with a field that's `Variables`
+    /// itself
+    pub fn nest<U: 'static>(self, parent: fn(&U) -> &T) -> Variable<U> {
+        match self {
+            Self::Boolean(accessor) => Variable::Boolean(accessor.nest(parent)),
+            Self::Cidr(accessor) => Variable::Cidr(accessor.nest(parent)),
+            Self::Duration(accessor) => Variable::Duration(accessor.nest(parent)),
+            Self::Float64(accessor) => Variable::Float64(accessor.nest(parent)),
+            Self::Int64(accessor) => Variable::Int64(accessor.nest(parent)),
+            Self::Ip(accessor) => Variable::Ip(accessor.nest(parent)),
+            Self::String(accessor) => Variable::String(accessor.nest(parent)),
+            Self::Timestamp(accessor) => Variable::Timestamp(accessor.nest(parent)),
+            Self::Uint64(accessor) => Variable::Uint64(accessor.nest(parent)),
+            Self::Regex(accessor) => Variable::Regex(accessor.nest(parent)),
+            Self::CidrList(accessor) => Variable::CidrList(accessor.nest(parent)),
+            Self::Int64List(accessor) => Variable::Int64List(accessor.nest(parent)),
+            Self::IpList(accessor) => Variable::IpList(accessor.nest(parent)),
+            Self::StringList(accessor) => Variable::StringList(accessor.nest(parent)),
+            Self::Uint64List(accessor) => Variable::Uint64List(accessor.nest(parent)),
+        }
+    }
+}
+
+impl<T> Variable<T> {
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Self::Boolean(accessor) => accessor.is_optional(),
+            Self::Cidr(accessor) => accessor.is_optional(),
+            Self::Duration(accessor) => accessor.is_optional(),
+            Self::Float64(accessor) => accessor.is_optional(),
+            Self::Int64(accessor) => accessor.is_optional(),
+            Self::Ip(accessor) => accessor.is_optional(),
+            Self::String(accessor) => accessor.is_optional(),
+            Self::Timestamp(accessor) => accessor.is_optional(),
+            Self::Uint64(accessor) => accessor.is_optional(),
+            Self::Regex(accessor) => accessor.is_optional(),
+            Self::CidrList(accessor) => accessor.is_optional(),
+            Self::Int64List(accessor) => accessor.is_optional(),
+            Self::IpList(accessor) => accessor.is_optional(),
+            Self::StringList(accessor) => accessor.is_optional(),
+            Self::Uint64List(accessor) => accessor.is_optional(),
+        }
+    }
+
+    pub fn type_of(&self) -> Type {
+        match self {
+            Self::Boolean(_) => Type::Boolean,
+            Self::Cidr(_) => Type::Cidr,
+            Self::Duration(_) => Type::Duration,
+            Self::Float64(_) => Type::Float64,
+            Self::Int64(_) => Type::Int64,
+            Self::Ip(_) => Type::Ip,
+            Self::String(_) => Type::String,
+            Self::Timestamp(_) => Type::Timestamp,
+            Self::Uint64(_) => Type::Uint64,
+            Self::Regex(_) => Type::Regex,
+            Self::CidrList(_) => Type::CidrList,
+            Self::Int64List(_) => Type::Int64List,
+            Self::IpList(_) => Type::IpList,
+            Self::StringList(_) => Type::StringList,
+            Self::Uint64List(_) => Type::Uint64List,
+        }
+    }
+}
+
+/// The [`Type`] of each `VariableType::AccessedAs`, so `#[derive(Variables)]` can declare
+/// its variables' types in a `const`
+#[doc(hidden)]
+pub trait AccessedType {
+    const TYPE: Type;
 }
 
 macro_rules! simple_field_type {
     ($type:ty, $variant:ident) => {
         impl VariableType for $type {
             type AccessedAs = $type;
-            fn from_field<T>(field: fn(&T) -> &Self::AccessedAs) -> Variable<T> {
-                Variable::$variant(field)
+            fn access(&self) -> &Self::AccessedAs {
+                self
+            }
+            fn from_accessor<T>(accessor: Accessor<T, Self::AccessedAs>) -> Variable<T> {
+                Variable::$variant(accessor)
             }
         }
+
+        impl AccessedType for $type {
+            const TYPE: Type = Type::$variant;
+        }
     };
 }
 
 simple_field_type!(bool, Boolean);
+simple_field_type!(f64, Float64);
 simple_field_type!(i64, Int64);
 simple_field_type!(u64, Uint64);
 simple_field_type!(IpAddr, Ip);
 simple_field_type!(IpCidr, Cidr);
+simple_field_type!(Duration, Duration);
 simple_field_type!(str, String);
+simple_field_type!(SystemTime, Timestamp);
 simple_field_type!(Regex, Regex);
 
-impl VariableType for String {
-    type AccessedAs = str;
-    fn from_field<T>(field: fn(&T) -> &Self::AccessedAs) -> Variable<T> {
-        Variable::String(field)
-    }
+macro_rules! list_field_type {
+    ($type:ty, $variant:ident) => {
+        impl VariableType for [$type] {
+            type AccessedAs = [$type];
+            fn access(&self) -> &Self::AccessedAs {
+                self
+            }
+            fn from_accessor<T>(accessor: Accessor<T, Self::AccessedAs>) -> Variable<T> {
+                Variable::$variant(accessor)
+            }
+        }
+
+        impl VariableType for Vec<$type> {
+            type AccessedAs = [$type];
+            fn access(&self) -> &Self::AccessedAs {
+                self
+            }
+            fn from_accessor<T>(accessor: Accessor<T, Self::AccessedAs>) -> Variable<T> {
+                Variable::$variant(accessor)
+            }
+        }
+
+        impl AccessedType for [$type] {
+            const TYPE: Type = Type::$variant;
+        }
+    };
+}
+
+list_field_type!(IpCidr, CidrList);
+list_field_type!(i64, Int64List);
+list_field_type!(IpAddr, IpList);
+list_field_type!(String, StringList);
+list_field_type!(u64, Uint64List);
+
+macro_rules! string_field_type {
+    ($type:ty) => {
+        impl VariableType for $type {
+            type AccessedAs = str;
+            fn access(&self) -> &Self::AccessedAs {
+                self
+            }
+            fn from_accessor<T>(accessor: Accessor<T, Self::AccessedAs>) -> Variable<T> {
+                Variable::String(accessor)
+            }
+        }
+    };
 }
 
+string_field_type!(String);
+string_field_type!(&str);
+string_field_type!(Cow<'_, str>);
+
 pub trait Variables: Sized + std::fmt::Debug {
-    fn variables() -> HashMap<&'static str, Variable<Self>>;
+    fn variables() -> HashMap<String, Variable<Self>>;
+
+    /// The name and type of every variable, if they're known at compile time, as
+    /// `#[derive(Variables)]` declares them, for `expr!` to check expressions against
+    #[doc(hidden)]
+    const DECLARED: Option<&'static [Declared]> = None;
+}
+
+/// A variable, or a nested struct of variables, as declared by `#[derive(Variables)]`
+#[doc(hidden)]
+pub enum Declared {
+    Variable(&'static str, Type),
+    /// A `#[chert(nested)]` field, whose variables are named `field.variable`
+    Nested(&'static str, Option<&'static [Declared]>),
+}
+
+/// What a variable name refers to in `Variables::DECLARED`
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub enum Declaration {
+    Variable(Type),
+    Missing,
+    /// The variables weren't declared, so the name can only be looked up at runtime
+    Unknown,
+}
+
+/// Whether `name` is the first part of `path`, up to a `.` or the end
+const fn strip_prefix<'a>(path: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    if path.len() < name.len() {
+        return None;
+    }
+    let mut i = 0;
+    while i < name.len() {
+        if path[i] != name[i] {
+            return None;
+        }
+        i += 1;
+    }
+    let (_, rest) = path.split_at(name.len());
+    match rest {
+        [] => Some(rest),
+        [b'.', rest @ ..] => Some(rest),
+        _ => None,
+    }
+}
+
+/// Look `name` up in `declared`, at compile time
+#[doc(hidden)]
+pub const fn declaration(declared: Option<&[Declared]>, name: &[u8]) -> Declaration {
+    let Some(declared) = declared else {
+        return Declaration::Unknown;
+    };
+    let mut i = 0;
+    while i < declared.len() {
+        match &declared[i] {
+            Declared::Variable(variable, type_) => {
+                if let Some([]) = strip_prefix(name, variable.as_bytes()) {
+                    return Declaration::Variable(*type_);
+                }
+            }
+            Declared::Nested(field, nested) => {
+                if let Some(rest @ [_, ..]) = strip_prefix(name, field.as_bytes()) {
+                    return declaration(*nested, rest);
+                }
+            }
+        }
+        i += 1;
+    }
+    Declaration::Missing
 }
 
 impl<T> std::fmt::Debug for Variable<T> {
@@ -58,11 +375,19 @@ impl<T> std::fmt::Debug for Variable<T> {
             match self {
                 Self::Boolean(_) => "Boolean(_)",
                 Self::Cidr(_) => "Cidr(_)",
+                Self::Duration(_) => "Duration(_)",
+                Self::Float64(_) => "Float64(_)",
                 Self::Int64(_) => "Int32(_)",
                 Self::Ip(_) => "Ip(_)",
                 Self::String(_) => "String(_)",
+                Self::Timestamp(_) => "Timestamp(_)",
                 Self::Uint64(_) => "Uint64(_)",
                 Self::Regex(_) => "Regex(_)",
+                Self::CidrList(_) => "CidrList(_)",
+                Self::Int64List(_) => "Int64List(_)",
+                Self::IpList(_) => "IpList(_)",
+                Self::StringList(_) => "StringList(_)",
+                Self::Uint64List(_) => "Uint64List(_)",
             }
         )
     }
@@ -211,3 +211,1100 @@ fn test_skip_either() {
         &[&0]
     );
 }
+
+#[test]
+fn test_relational() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+        b: i64,
+        c: std::net::IpAddr,
+        d: String,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a > 5").unwrap()),
+        (1, chert::parse("a >= 5").unwrap()),
+        (2, chert::parse("a < 5").unwrap()),
+        (3, chert::parse("a <= 5").unwrap()),
+        (4, chert::parse("a != 5").unwrap()),
+        (5, chert::parse("b < -1").unwrap()),
+        (6, chert::parse("c > 10.0.0.1").unwrap()),
+        (7, chert::parse("d >= 'foo'").unwrap()),
+        (8, chert::parse("d != 'foo'").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables {
+            a: 5,
+            b: -1,
+            c: ip("10.0.0.1"),
+            d: String::from("foo"),
+        }),
+        &[&1, &3, &7]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: 6,
+            b: -2,
+            c: ip("10.0.0.2"),
+            d: String::from("bar"),
+        }),
+        &[&0, &1, &4, &5, &6, &8]
+    );
+}
+
+#[test]
+fn test_arithmetic() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+        b: i64,
+        c: i64,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a * 2 + 1 == 7").unwrap()),
+        (1, chert::parse("a / 2 == 1").unwrap()),
+        (2, chert::parse("a % 2 == 1").unwrap()),
+        (3, chert::parse("2 ** a == 8").unwrap()),
+        (4, chert::parse("2 ** 3 ** 2 == 512").unwrap()),
+        (5, chert::parse("b * c == -9").unwrap()),
+        (6, chert::parse("c / b == -1").unwrap()),
+        (7, chert::parse("b % -2 == -1").unwrap()),
+        (8, chert::parse("b ** 3 == -27").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables { a: 3, b: -3, c: 3 }),
+        &[&0, &1, &2, &3, &4, &5, &6, &7, &8]
+    );
+}
+
+#[test]
+fn test_divide_by_zero() {
+    // a division by zero stops the expression it's in from matching, but
+    // doesn't affect any other expression
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+        b: i64,
+        c: i64,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("1 / a == 0 or true").unwrap()),
+        (1, chert::parse("1 % a == 0").unwrap()),
+        (2, chert::parse("a == 0 or 1 / a == 1").unwrap()),
+        (3, chert::parse("c / b == -1").unwrap()),
+        (4, chert::parse("true").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(engine.eval(&Variables { a: 0, b: 0, c: 1 }), &[&2, &4]);
+    assert_eq!(
+        engine.eval(&Variables { a: 1, b: -1, c: 1 }),
+        &[&0, &1, &2, &3, &4]
+    );
+}
+
+#[test]
+fn test_overflow() {
+    use chert::compile::{Options, Overflow, Warning};
+
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+    }
+    let expressions = || {
+        Vec::from([
+            (0, chert::parse("a - 1 == 18446744073709551615").unwrap()),
+            (1, chert::parse("a * 2 == 0").unwrap()),
+            (2, chert::parse("-a == -1").unwrap()),
+            (3, chert::parse("2 ** a == 1").unwrap()),
+            (4, chert::parse("true").unwrap()),
+        ])
+    };
+    let compile = |overflow| {
+        chert::compile::compile_with_options(expressions(), &Options { overflow }).unwrap()
+    };
+
+    let engine = compile(Overflow::Checked);
+    assert_eq!(engine.eval(&Variables { a: 0 }), &[&1, &3, &4]);
+    assert_eq!(engine.eval(&Variables { a: u64::MAX }), &[&4]);
+
+    let engine = compile(Overflow::Wrapping);
+    assert_eq!(engine.eval(&Variables { a: 0 }), &[&0, &1, &3, &4]);
+    assert_eq!(engine.eval(&Variables { a: 1 << 63 }), &[&1, &4]);
+
+    let engine = compile(Overflow::Saturating);
+    assert_eq!(engine.eval(&Variables { a: 0 }), &[&1, &3, &4]);
+    assert_eq!(engine.eval(&Variables { a: u64::MAX }), &[&4]);
+    assert!(engine.warnings().is_empty());
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("18446744073709551615 + 1 == 0").unwrap()),
+        (1, chert::parse("1 / 0 == 0").unwrap()),
+        (2, chert::parse("1 + 1 == 2").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.warnings(),
+        &[(0, Warning::Overflow), (1, Warning::DivisionByZero)]
+    );
+    assert_eq!(engine.eval(&Variables { a: 0 }), &[&2]);
+
+    let engine = chert::compile::compile_with_options(
+        Vec::from([(0, chert::parse("18446744073709551615 + 1 == 0").unwrap())]),
+        &Options {
+            overflow: Overflow::Wrapping,
+        },
+    )
+    .unwrap();
+    assert_eq!(engine.warnings(), &[(0, Warning::Overflow)]);
+    assert_eq!(engine.eval(&Variables { a: 0 }), &[&0]);
+}
+
+#[test]
+fn test_signed_arithmetic() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: i64,
+        b: u64,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a + 1 == 0").unwrap()),
+        (1, chert::parse("a - 1 == -2").unwrap()),
+        (2, chert::parse("a == b").unwrap()),
+        (3, chert::parse("a < b").unwrap()),
+        (4, chert::parse("b + a == 4").unwrap()),
+        (5, chert::parse("-a == 1").unwrap()),
+        (6, chert::parse("a * b < -4").unwrap()),
+        (
+            7,
+            chert::parse("a == -9223372036854775808 + 9223372036854775807").unwrap(),
+        ),
+    ]))
+    .unwrap();
+    assert!(engine.warnings().is_empty());
+    assert_eq!(
+        engine.eval(&Variables { a: -1, b: 5 }),
+        &[&0, &1, &3, &4, &5, &6, &7]
+    );
+    assert_eq!(engine.eval(&Variables { a: 5, b: 5 }), &[&2]);
+    // `b` doesn't fit in an int64, so expressions promoting it don't match
+    assert_eq!(engine.eval(&Variables { a: 5, b: u64::MAX }), &[&0; 0]);
+}
+
+#[test]
+fn test_int64_literals() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: i64,
+    }
+    let ast = chert::parse::<Variables>("a == -1").unwrap();
+    assert_eq!(
+        ast.get_root(),
+        &chert::NodeBoolean::Equals(
+            chert::parse::nodes::boolean::NodeBooleanEquals::Int64Int64 {
+                left: chert::parse::nodes::int64::NodeInt64::Variable {
+                    name: String::from("a")
+                },
+                right: chert::parse::nodes::int64::NodeInt64::Constant(-1),
+            }
+        )
+    );
+}
+
+#[test]
+fn test_float64() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: f64,
+        b: u64,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a > 0.5").unwrap()),
+        (1, chert::parse("a * 2 == 1.5").unwrap()),
+        (2, chert::parse("a + b >= 1.75").unwrap()),
+        (3, chert::parse("a == a").unwrap()),
+        (4, chert::parse("a != a").unwrap()),
+        (5, chert::parse("-a < -.5").unwrap()),
+        (6, chert::parse("a / 0 > 1000").unwrap()),
+        (7, chert::parse("a ** 2 == 0.5625").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables { a: 0.75, b: 1 }),
+        &[&0, &1, &2, &3, &5, &6, &7]
+    );
+    assert_eq!(engine.eval(&Variables { a: f64::NAN, b: 1 }), &[&4]);
+}
+
+#[test]
+fn test_invalid_number() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+    }
+    assert_eq!(
+        chert::parse::<Variables>("a == 18446744073709551616")
+            .unwrap_err()
+            .span(),
+        5..25
+    );
+}
+
+#[test]
+fn test_duration() {
+    use std::time::Duration;
+
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: Duration,
+        b: u64,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a > 1h30m").unwrap()),
+        (1, chert::parse("a + 500ms == 2h1s").unwrap()),
+        (2, chert::parse("a * b == 4h1s").unwrap()),
+        (3, chert::parse("a / b < a").unwrap()),
+        (4, chert::parse("a - 1w > 0s").unwrap()),
+        (5, chert::parse("1s == 1000ms && 1ms == 1000us").unwrap()),
+        (6, chert::parse("2 * 1d == 2d").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables {
+            a: Duration::from_millis(7_200_500),
+            b: 2,
+        }),
+        &[&0, &1, &2, &3, &5, &6]
+    );
+    // dividing by zero doesn't match
+    assert_eq!(
+        engine.eval(&Variables {
+            a: Duration::from_secs(60),
+            b: 0,
+        }),
+        &[&5, &6]
+    );
+}
+
+#[test]
+fn test_timestamp() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: SystemTime,
+        b: Duration,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a > 2023-01-01T00:00:00Z").unwrap()),
+        (1, chert::parse("a == 2023-08-01T13:30:00.5+01:00").unwrap()),
+        (
+            2,
+            chert::parse("a - 2023-08-01T00:00:00Z == 12h30m500ms").unwrap(),
+        ),
+        (3, chert::parse("a + b > now").unwrap()),
+        (4, chert::parse("a > now - 1d").unwrap()),
+        (
+            5,
+            chert::parse("1d + 1970-01-01T00:00:00Z == 1970-01-02T00:00:00z").unwrap(),
+        ),
+        (
+            6,
+            chert::parse("1969-12-31T23:59:59Z < 1970-01-01T00:00:00Z").unwrap(),
+        ),
+    ]))
+    .unwrap();
+
+    let a = UNIX_EPOCH + Duration::from_millis(1_690_893_000_500);
+    assert_eq!(
+        engine.eval_at(
+            &Variables {
+                a,
+                b: Duration::from_secs(60),
+            },
+            a + Duration::from_secs(30),
+        ),
+        &[&0, &1, &2, &3, &4, &5, &6]
+    );
+    assert_eq!(
+        engine.eval_at(
+            &Variables {
+                a,
+                b: Duration::from_secs(60),
+            },
+            a + Duration::from_secs(86_401),
+        ),
+        &[&0, &1, &2, &5, &6]
+    );
+    // the current time is well after `a`
+    assert_eq!(
+        engine.eval(&Variables {
+            a,
+            b: Duration::ZERO
+        }),
+        &[&0, &1, &2, &5, &6]
+    );
+}
+
+#[test]
+fn test_invalid_timestamp() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: std::time::SystemTime,
+    }
+    for expression in [
+        "a == 2023-02-29T00:00:00Z",
+        "a == 2023-13-01T00:00:00Z",
+        "a == 2023-01-01T24:00:00Z",
+        "a == 2023-01-01T00:00:00+24:00",
+    ] {
+        assert_eq!(
+            chert::parse::<Variables>(expression).unwrap_err().span(),
+            5..expression.len()
+        );
+    }
+}
+
+#[test]
+fn test_regex_flags() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: String,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a ~ m/^foo$/").unwrap()),
+        (1, chert::parse("a ~ m/^foo$/i").unwrap()),
+        (2, chert::parse("a ~ m/^bar$/m").unwrap()),
+        (3, chert::parse("a ~ m/foo.bar/s").unwrap()),
+        (4, chert::parse("a ~ m/ f o o /xi").unwrap()),
+        (5, chert::parse("a ~ m/^\\w+$/u").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("foo\nbar")
+        }),
+        &[&2, &3, &4]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("FOO")
+        }),
+        &[&1, &4, &5]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("föö")
+        }),
+        &[&0; 0]
+    );
+}
+
+#[test]
+fn test_string_escapes() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: String,
+    }
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse(r"a == 'it\'s'").unwrap()),
+        (1, chert::parse(r#"a == "it's""#).unwrap()),
+        (2, chert::parse(r"a == r'it'").unwrap()),
+        (3, chert::parse(r"a + '\n' == 'it\x27s\u{a}'").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("it's")
+        }),
+        &[&0, &1, &3]
+    );
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse(r"a == r'\d\t'").unwrap()),
+        (1, chert::parse(r#"a == r"\d'""#).unwrap()),
+        (2, chert::parse(r"a == '\\d\t'").unwrap()),
+        (3, chert::parse("a == '\\u{1F980}\\0'").unwrap()),
+    ]))
+    .unwrap();
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from(r"\d\t")
+        }),
+        &[&0]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("\\d'")
+        }),
+        &[&1]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("\\d\t")
+        }),
+        &[&2]
+    );
+    assert_eq!(
+        engine.eval(&Variables {
+            a: String::from("\u{1f980}\0")
+        }),
+        &[&3]
+    );
+}
+
+#[test]
+fn test_string_escape_round_trip() {
+    for value in ["", "it's", "a\\b", "\"\n\r\t\0", "\u{7}\u{1b}[0m", "🦀"] {
+        let escaped = chert::lex::escape(value);
+        assert_eq!(
+            chert::lex::lex(&escaped).unwrap(),
+            &[(
+                chert::lex::Token::String(value.to_owned()),
+                0..escaped.len()
+            )]
+        );
+    }
+}
+
+#[test]
+fn test_nested() {
+    #[derive(chert::Variables, Debug)]
+    struct Host {
+        name: String,
+        ip: std::net::IpAddr,
+    }
+    #[derive(chert::Variables, Debug)]
+    struct Request {
+        #[chert(nested)]
+        host: Host,
+        port: u64,
+    }
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        #[chert(nested)]
+        request: Request,
+        #[chert(nested)]
+        user: Host,
+    }
+
+    let engine = chert::compile(Vec::from([
+        (
+            0,
+            chert::parse("request.host.ip in 10.0.0.0/8 && request.port == 22").unwrap(),
+        ),
+        (1, chert::parse("user.name == \"x\"").unwrap()),
+    ]))
+    .unwrap();
+
+    let variables = |name: &str, ip: &'static str| Variables {
+        request: Request {
+            host: Host {
+                name: String::new(),
+                ip: self::ip(ip),
+            },
+            port: 22,
+        },
+        user: Host {
+            name: name.to_owned(),
+            ip: self::ip("127.0.0.1"),
+        },
+    };
+    assert_eq!(engine.eval(&variables("x", "10.1.1.1")), &[&0, &1]);
+    assert_eq!(engine.eval(&variables("y", "11.1.1.1")), &[&0; 0]);
+
+    assert!(chert::parse::<Variables>("request.port.x == 1").is_err());
+    assert!(chert::parse::<Variables>("request == 1").is_err());
+}
+
+#[test]
+fn test_optional() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: Option<u64>,
+        b: Option<String>,
+        c: Option<bool>,
+        d: u64,
+    }
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("a == 1").unwrap()),
+        (1, chert::parse("!(a == 1)").unwrap()),
+        (2, chert::parse("exists(a)").unwrap()),
+        (3, chert::parse("a == null").unwrap()),
+        (4, chert::parse("b != null && b == 'x'").unwrap()),
+        (5, chert::parse("c").unwrap()),
+        (6, chert::parse("!c").unwrap()),
+        (7, chert::parse("10 / a == 10 || d == 1").unwrap()),
+        (8, chert::parse("exists(d)").unwrap()),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        engine.eval(&Variables {
+            a: Some(1),
+            b: Some(String::from("x")),
+            c: Some(true),
+            d: 0,
+        }),
+        &[&0, &2, &4, &5, &7, &8]
+    );
+    // comparisons reading a missing variable are false, without evaluating either side (so
+    // `10 / a` isn't a division by zero that stops the whole expression matching)
+    assert_eq!(
+        engine.eval(&Variables {
+            a: None,
+            b: None,
+            c: None,
+            d: 1,
+        }),
+        &[&1, &3, &6, &7, &8]
+    );
+}
+
+#[test]
+fn test_lists() {
+    use std::net::IpAddr;
+    use std::str::FromStr as _;
+
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        tags: Vec<String>,
+        hops: Vec<IpAddr>,
+        ports: Vec<u64>,
+        offsets: Option<Vec<i64>>,
+        nets: Vec<cidr::IpCidr>,
+        ip: IpAddr,
+        port: u64,
+    }
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("'prod' in tags").unwrap()),
+        (1, chert::parse("ip in [10.0.0.1, 10.0.0.2]").unwrap()),
+        (
+            2,
+            chert::parse("ip in [10.0.0.0/8, 192.168.0.0/16]").unwrap(),
+        ),
+        (3, chert::parse("len(tags) == 2").unwrap()),
+        (4, chert::parse("any(tags, t => t ~ m/^pr/)").unwrap()),
+        (5, chert::parse("all(hops, h => h in 10.0.0.0/8)").unwrap()),
+        (6, chert::parse("any(ports, p => p == port + 1)").unwrap()),
+        (7, chert::parse("port in [port, 1]").unwrap()),
+        (8, chert::parse("-1 in offsets").unwrap()),
+        (
+            9,
+            chert::parse("any(tags, t => all(ports, p => p > 10 && t != 'x'))").unwrap(),
+        ),
+        (10, chert::parse("all(offsets, o => o < 0)").unwrap()),
+        (11, chert::parse("any(nets, n => ip in n)").unwrap()),
+        (12, chert::parse("all(nets, n => 10.0.0.1 in n)").unwrap()),
+        (
+            13,
+            chert::parse("any([10.0.0.0/8, 192.168.0.0/16], n => ip in n)").unwrap(),
+        ),
+        (
+            14,
+            chert::parse("all([10.0.0.0/8, 10.0.0.0/16], n => ip in n)").unwrap(),
+        ),
+        // the element shadows the `port` variable
+        (15, chert::parse("any(ports, port => port == 443)").unwrap()),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        engine.eval(&Variables {
+            tags: Vec::from([String::from("prod"), String::from("web")]),
+            hops: Vec::from([
+                IpAddr::from_str("10.0.0.1").unwrap(),
+                IpAddr::from_str("10.1.0.1").unwrap(),
+            ]),
+            ports: Vec::from([80, 443]),
+            offsets: Some(Vec::from([-1, -2])),
+            nets: Vec::from([cidr("10.0.0.0/8"), cidr("172.16.0.0/12")]),
+            ip: IpAddr::from_str("10.0.0.2").unwrap(),
+            port: 79,
+        }),
+        &[&0, &1, &2, &3, &4, &5, &6, &7, &8, &9, &10, &11, &13, &14, &15]
+    );
+    // empty lists have no element for `any`, so nothing fails `all`
+    assert_eq!(
+        engine.eval(&Variables {
+            tags: Vec::new(),
+            hops: Vec::new(),
+            ports: Vec::from([1]),
+            offsets: None,
+            nets: Vec::new(),
+            ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            port: 1,
+        }),
+        &[&2, &5, &7, &12, &13]
+    );
+}
+
+#[test]
+fn test_attributes() {
+    #[derive(chert::Variables, Debug)]
+    #[chert(rename_all = "camelCase")]
+    struct Variables {
+        source_port: u64,
+        #[chert(rename = "dst")]
+        destination_port: u64,
+        #[chert(skip)]
+        _internal: Vec<u8>,
+        #[chert(with = Variables::total, rename = "totalBytes")]
+        bytes: u64,
+        r#type: String,
+        #[chert(with = Variables::name_or_first)]
+        name: Option<String>,
+        names: Vec<String>,
+    }
+
+    impl Variables {
+        fn total(&self) -> &u64 {
+            &self.bytes
+        }
+
+        fn name_or_first(&self) -> Option<&str> {
+            self.name
+                .as_deref()
+                .or(self.names.first().map(String::as_str))
+        }
+    }
+
+    let mut names = <Variables as chert::variables::Variables>::variables()
+        .into_keys()
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        ["dst", "name", "names", "sourcePort", "totalBytes", "type"]
+    );
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("sourcePort == 1 && dst == 2").unwrap()),
+        (1, chert::parse("totalBytes == 3 && type == 'tcp'").unwrap()),
+        (2, chert::parse("name == 'a'").unwrap()),
+    ]))
+    .unwrap();
+    assert!(chert::parse::<Variables>("source_port == 1").is_err());
+    assert!(chert::parse::<Variables>("internal == 1").is_err());
+
+    let variables = Variables {
+        source_port: 1,
+        destination_port: 2,
+        _internal: Vec::new(),
+        bytes: 3,
+        r#type: String::from("tcp"),
+        name: None,
+        names: Vec::from([String::from("a")]),
+    };
+    assert_eq!(engine.eval(&variables), &[&0, &1, &2]);
+}
+
+#[test]
+fn test_borrowed() {
+    use std::borrow::Cow;
+
+    #[derive(chert::Variables, Debug)]
+    struct Event<'a> {
+        nick: &'a str,
+        channel: Cow<'a, str>,
+        account: Option<&'a str>,
+    }
+
+    // an engine over `Event<'a>` can't outlive 'a
+    let line = String::from("jess #chert");
+    let engine = chert::compile(Vec::from([
+        (
+            0,
+            chert::parse("nick == 'jess' && channel == '#chert'").unwrap(),
+        ),
+        (1, chert::parse("exists(account)").unwrap()),
+    ]))
+    .unwrap();
+
+    let (nick, channel) = line.split_once(' ').unwrap();
+    let event = Event {
+        nick,
+        channel: Cow::Borrowed(channel),
+        account: None,
+    };
+    assert_eq!(engine.eval(&event), &[&0]);
+}
+
+#[test]
+fn test_generic() {
+    #[derive(chert::Variables, Debug)]
+    struct Wrapper<T: std::fmt::Debug> {
+        value: T,
+        count: u64,
+    }
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("value == 'a' && count == 1").unwrap()),
+        (1, chert::parse("count == 2").unwrap()),
+    ]))
+    .unwrap();
+    let wrapper = Wrapper {
+        value: String::from("a"),
+        count: 1,
+    };
+    assert_eq!(engine.eval(&wrapper), &[&0]);
+
+    assert!(chert::parse::<Wrapper<u64>>("value == 'a'").is_err());
+    assert!(chert::parse::<Wrapper<u64>>("value == 1").is_ok());
+}
+
+#[test]
+fn test_tuple_struct() {
+    #[derive(chert::Variables, Debug)]
+    struct Packet(
+        #[chert(rename = "source")] std::net::IpAddr,
+        #[chert(skip)] Vec<u8>,
+        #[chert(rename = "port")] u64,
+    );
+
+    let engine = chert::compile(Vec::from([(
+        0,
+        chert::parse("source in 10.0.0.0/8 && port == 22").unwrap(),
+    )]))
+    .unwrap();
+    let packet = Packet(ip("10.1.2.3"), Vec::from([0]), 22);
+    assert_eq!(engine.eval(&packet), &[&0]);
+    assert_eq!(packet.1, [0]);
+    assert!(engine
+        .eval(&Packet(ip("192.168.0.1"), Vec::new(), 22))
+        .is_empty());
+}
+
+#[test]
+fn test_enum() {
+    #[derive(chert::Variables, Debug)]
+    #[chert(rename_all = "snake_case")]
+    enum Command {
+        Privmsg,
+        Notice,
+        #[chert(rename = "JOIN")]
+        Join,
+        TopicChange,
+    }
+
+    #[derive(chert::Variables, Debug)]
+    struct Event {
+        command: Command,
+        reply_to: Option<Command>,
+    }
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("command == 'privmsg'").unwrap()),
+        (1, chert::parse("command == 'JOIN'").unwrap()),
+        (2, chert::parse("command == 'topic_change'").unwrap()),
+        (3, chert::parse("reply_to == 'notice'").unwrap()),
+        (4, chert::parse("reply_to == null").unwrap()),
+    ]))
+    .unwrap();
+
+    let event = |command, reply_to| Event { command, reply_to };
+    assert_eq!(engine.eval(&event(Command::Privmsg, None)), &[&0, &4]);
+    assert_eq!(engine.eval(&event(Command::Join, None)), &[&1, &4]);
+    assert_eq!(
+        engine.eval(&event(Command::TopicChange, Some(Command::Notice))),
+        &[&2, &3]
+    );
+}
+
+#[test]
+fn test_enum_camel_case() {
+    use chert::variables::VariableType as _;
+
+    #[derive(chert::Variables, Debug)]
+    #[chert(rename_all = "camelCase")]
+    enum Greeting {
+        TopicChange,
+        Über,
+        Ärger,
+    }
+
+    assert_eq!(Greeting::TopicChange.access(), "topicChange");
+    // the first character isn't always one byte long
+    assert_eq!(Greeting::Über.access(), "über");
+    assert_eq!(Greeting::Ärger.access(), "ärger");
+}
+
+#[derive(chert::Variables, Debug)]
+struct ExprRequest {
+    ip: std::net::IpAddr,
+}
+
+#[derive(chert::Variables, Debug)]
+struct ExprVariables {
+    nick: String,
+    count: u64,
+    names: Vec<String>,
+    limit: Option<u64>,
+    #[chert(nested)]
+    request: ExprRequest,
+}
+
+#[test]
+fn test_expr() {
+    let engine = chert::compile(Vec::from([
+        (0, chert::expr!(ExprVariables, "nick == 'x' && count > 3")),
+        (1, chert::expr!(ExprVariables, "count == 0",)),
+        (2, chert::expr!(ExprVariables, "any(names, n => n == nick)")),
+        (
+            3,
+            chert::expr!(ExprVariables, "count < limit || limit == null"),
+        ),
+        (4, chert::expr!(ExprVariables, "request.ip in 10.0.0.0/8")),
+        (
+            5,
+            chert::expr!(
+                ExprVariables,
+                "all(names, n => n in [nick, 'y'] && len(names) > -1)"
+            ),
+        ),
+    ]))
+    .unwrap();
+    let variables = ExprVariables {
+        nick: String::from("x"),
+        count: 4,
+        names: Vec::from([String::from("y"), String::from("x")]),
+        limit: Some(3),
+        request: ExprRequest {
+            ip: ip("192.168.0.1"),
+        },
+    };
+    assert_eq!(engine.eval(&variables), &[&0, &2, &5]);
+}
+
+#[test]
+fn test_unread_variables() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        nick: String,
+        #[chert(with = Variables::unreadable)]
+        account: String,
+    }
+
+    impl Variables {
+        fn unreadable(&self) -> &str {
+            panic!("`account` was loaded for {}", self.account)
+        }
+    }
+
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("nick == 'jess'").unwrap()),
+        (
+            1,
+            chert::parse("nick in ['jess', 'x'] && len([nick]) == 1").unwrap(),
+        ),
+    ]))
+    .unwrap();
+    let variables = Variables {
+        nick: String::from("jess"),
+        account: String::from("jess"),
+    };
+    assert_eq!(engine.eval(&variables), &[&0, &1]);
+}
+
+#[test]
+fn test_shared_subexpressions() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        nick: String,
+        count: u64,
+        divisor: u64,
+        tag: String,
+        tags: Vec<String>,
+    }
+
+    let expressions = [
+        "nick == 'meow' && count > 1",
+        "nick == 'meow' && count > 2",
+        "nick == 'meow' || count > 3",
+        // `count > 2` is only evaluated by 1 when `nick == 'meow'`, so isn't reused here
+        "count > 2 && tag == 'x'",
+        "count > 1 || !(count / divisor == 1)",
+        // failed in 4 when `divisor` is 0, so has to fail here too
+        "!(count / divisor == 1)",
+        "tag == 'x' && any(tags, t => t == 'x')",
+    ];
+    let compile = |expressions: &[&str]| {
+        chert::compile(
+            expressions
+                .iter()
+                .enumerate()
+                .map(|(i, expression)| (i, chert::parse(expression).unwrap()))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    };
+    let engine = compile(&expressions);
+
+    // compiled on their own, nothing is shared
+    let unshared = expressions
+        .iter()
+        .map(|expression| compile(&[expression]).instructions())
+        .sum::<usize>();
+    // less the instruction that raises the output
+    let comparison = compile(&["nick == 'meow'"]).instructions() - 1;
+    // 1 reuses `nick == 'meow'` from 0, as they're both only evaluated when it's true. 2 is
+    // evaluated for any `nick`, so can't
+    assert_eq!(engine.instructions(), unshared - comparison);
+
+    let variables = |nick: &str, count, divisor, tag: &str, tags: &[&str]| Variables {
+        nick: nick.to_owned(),
+        count,
+        divisor,
+        tag: tag.to_owned(),
+        tags: tags.iter().map(|tag| String::from(*tag)).collect(),
+    };
+    assert_eq!(
+        engine.eval(&variables("meow", 3, 1, "x", &["y"])),
+        &[&0, &1, &2, &3, &4, &5]
+    );
+    assert_eq!(
+        engine.eval(&variables("woof", 3, 1, "x", &["x"])),
+        &[&3, &4, &5, &6]
+    );
+    assert_eq!(engine.eval(&variables("meow", 1, 0, "y", &[])), &[&2]);
+    assert_eq!(
+        engine.eval(&variables("meow", 1, 2, "y", &[])),
+        &[&2, &4, &5]
+    );
+}
+
+#[test]
+fn test_dispatch_shared_subexpressions() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        nick: String,
+        count: u64,
+        tag: String,
+    }
+
+    let expressions = [
+        "count > 1 && nick == 'meow'",
+        // only evaluated when `nick == 'woof'`, when 0 isn't, so can't reuse `count > 1`
+        "count > 1 && nick == 'woof'",
+        // evaluated for any `nick`, so can't reuse `count > 1` from 0 or 1 either
+        "count > 1 || nick == 'meow'",
+        // only evaluated when `nick == 'meow'`, like 0, so reuses all of 0
+        "count > 1 && nick == 'meow' && tag == 'x'",
+    ];
+    let compile = |expressions: &[&str]| {
+        chert::compile(
+            expressions
+                .iter()
+                .enumerate()
+                .map(|(i, expression)| (i, chert::parse(expression).unwrap()))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    };
+    let engine = compile(&expressions);
+
+    let unshared = expressions
+        .iter()
+        .map(|expression| compile(&[expression]).instructions())
+        .sum::<usize>();
+    // less the instruction that raises the output
+    let reused = compile(&expressions[..1]).instructions() - 1;
+    assert_eq!(engine.instructions(), unshared - reused);
+
+    let variables = |nick: &str, count, tag: &str| Variables {
+        nick: nick.to_owned(),
+        count,
+        tag: tag.to_owned(),
+    };
+    assert_eq!(engine.eval(&variables("meow", 2, "x")), &[&0, &2, &3]);
+    assert_eq!(engine.eval(&variables("meow", 2, "y")), &[&0, &2]);
+    assert_eq!(engine.eval(&variables("meow", 0, "x")), &[&2]);
+    assert_eq!(engine.eval(&variables("woof", 2, "x")), &[&1, &2]);
+    assert_eq!(
+        engine.eval(&variables("woof", 0, "x")),
+        Vec::<&usize>::new()
+    );
+}
+
+#[test]
+fn test_dispatch() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        command: String,
+        count: u64,
+        divisor: u64,
+        source: std::net::IpAddr,
+        account: Option<String>,
+    }
+
+    let mut expressions = Vec::from([
+        (
+            0,
+            chert::parse("command == 'PRIVMSG' && count > 1").unwrap(),
+        ),
+        (1, chert::parse("'NOTICE' == command").unwrap()),
+        (
+            2,
+            chert::parse("count == 3 && command == 'PRIVMSG'").unwrap(),
+        ),
+        (
+            3,
+            chert::parse("command == 'PRIVMSG' || count == 3").unwrap(),
+        ),
+        (
+            4,
+            chert::parse("count > 0 && (source == 10.0.0.1 && command == 'JOIN')").unwrap(),
+        ),
+        (5, chert::parse("account == 'jess'").unwrap()),
+        (
+            6,
+            chert::parse("command == 'PRIVMSG' && count / divisor == 1").unwrap(),
+        ),
+        (7, chert::parse("!(command == 'PRIVMSG')").unwrap()),
+    ]);
+    for i in 8..108 {
+        let expression = format!("command == 'COMMAND{i}' && count == {i}");
+        expressions.push((i, chert::parse(&expression).unwrap()));
+    }
+    let engine = chert::compile(expressions).unwrap();
+
+    let variables = |command: &str, count, divisor, source, account: Option<&str>| Variables {
+        command: command.to_owned(),
+        count,
+        divisor,
+        source: ip(source),
+        account: account.map(str::to_owned),
+    };
+    assert_eq!(
+        engine.eval(&variables("PRIVMSG", 3, 3, "10.0.0.1", Some("jess"))),
+        &[&0, &2, &3, &5, &6]
+    );
+    // 6 divides by zero, which still stops it matching
+    assert_eq!(
+        engine.eval(&variables("PRIVMSG", 1, 0, "10.0.0.1", None)),
+        &[&3]
+    );
+    assert_eq!(
+        engine.eval(&variables("NOTICE", 3, 1, "10.0.0.1", Some("meow"))),
+        &[&1, &3, &7]
+    );
+    assert_eq!(
+        engine.eval(&variables("JOIN", 1, 1, "10.0.0.1", None)),
+        &[&4, &7]
+    );
+    assert_eq!(
+        engine.eval(&variables("JOIN", 1, 1, "10.0.0.2", None)),
+        &[&7]
+    );
+    assert_eq!(
+        engine.eval(&variables("COMMAND50", 50, 1, "10.0.0.1", None)),
+        &[&7, &50]
+    );
+    assert_eq!(
+        engine.eval(&variables("COMMAND50", 51, 1, "10.0.0.1", None)),
+        &[&7]
+    );
+}
@@ -0,0 +1,86 @@
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    a: u64,
+    b: String,
+}
+
+fn error(expression: &str) -> chert::ParseError {
+    chert::parse::<Variables>(expression).unwrap_err()
+}
+
+#[test]
+fn test_unknown_identifier() {
+    let error = error("a == 1 && c");
+    assert_eq!(error.span(), 10..11);
+    assert_eq!(
+        error.render("a == 1 && c"),
+        concat!(
+            "error: unknown identifier `c`\n",
+            " --> 1:11\n",
+            "  |\n",
+            "1 | a == 1 && c\n",
+            "  |           ^\n",
+        )
+    );
+}
+
+#[test]
+fn test_bad_binary_operands() {
+    let error = error("(a + 1) == b");
+    assert_eq!(error.span(), 8..10);
+    assert_eq!(
+        error.render("(a + 1) == b"),
+        concat!(
+            "error: `==` cannot be applied to uint64 and string\n",
+            " --> 1:9\n",
+            "  |\n",
+            "1 | (a + 1) == b\n",
+            "  | ------- ^^ -\n",
+        )
+    );
+}
+
+#[test]
+fn test_lex_error() {
+    let error = error("b == 'foo");
+    assert!(matches!(error, chert::ParseError::Lex(_, _)));
+    assert_eq!(error.span(), 5..9);
+}
+
+#[test]
+fn test_empty() {
+    let error = error("  ");
+    assert_eq!(error.span(), 2..2);
+    assert_eq!(
+        error.render("  "),
+        concat!(
+            "error: expression is empty\n",
+            " --> 1:3\n",
+            "  |\n",
+            "1 |   \n",
+            "  |   ^\n",
+        )
+    );
+}
+
+#[test]
+fn test_multiline() {
+    let expression = "a == 1\n  && b == 1";
+    let error = error(expression);
+    assert_eq!(
+        error.render(expression),
+        concat!(
+            "error: `==` cannot be applied to string and uint64\n",
+            " --> 2:8\n",
+            "  |\n",
+            "2 |   && b == 1\n",
+            "  |      - ^^ -\n",
+        )
+    );
+}
+
+#[test]
+fn test_scopes() {
+    assert_eq!(error("(a == 1").span(), 0..1);
+    assert_eq!(error("a == 1)").span(), 6..7);
+}
@@ -0,0 +1,199 @@
+use std::error::Error as _;
+
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    a: u64,
+}
+
+#[test]
+fn test_equality() {
+    let error = chert::parse::<Variables>("a == b").unwrap_err();
+    assert_eq!(
+        error,
+        chert::ParseError::Parse(chert::parse::Error::UnknownIdentifier {
+            name: String::from("b"),
+            span: 5..6,
+        })
+    );
+    assert_eq!(
+        chert::lex::lex("m/(/").unwrap_err(),
+        chert::lex::lex("m/(/").unwrap_err()
+    );
+}
+
+#[test]
+fn test_invalid_escape() {
+    for (expression, escape, span) in [
+        (r"'a\q'", r"\q", 2..4),
+        (r"'\x80'", r"\x80", 1..5),
+        (r"'\xg'", r"\xg", 1..4),
+        (r"'\u{110000}'", r"\u{110000}", 1..11),
+        (r"'\u{}'", r"\u{}", 1..5),
+        (r"'\u41'", r"\u4", 1..4),
+    ] {
+        let error = chert::lex::lex(expression).unwrap_err();
+        assert_eq!(error.0.to_string(), format!("invalid escape `{escape}`"));
+        assert_eq!(error.1, span);
+    }
+}
+
+#[test]
+fn test_unknown_regex_flag() {
+    let error = chert::parse::<Variables>("a == 1 && 'b' ~ m/b/iq").unwrap_err();
+    assert_eq!(error.to_string(), "unknown regex flag `q`");
+    assert_eq!(error.span(), 21..22);
+}
+
+#[test]
+fn test_display() {
+    let error = chert::parse::<Variables>("a == 'b'").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`==` cannot be applied to uint64 and string"
+    );
+}
+
+#[test]
+fn test_source() {
+    let error = chert::parse::<Variables>("a == 300.1.1.1").unwrap_err();
+    assert_eq!(error.to_string(), "invalid IP address");
+    assert!(error
+        .source()
+        .is_some_and(|source| source.is::<std::net::AddrParseError>()));
+    assert!(error.render("a == 300.1.1.1").starts_with(&format!(
+        "error: invalid IP address: {}\n",
+        error.source().unwrap()
+    )));
+}
+
+#[test]
+fn test_question_mark() {
+    #[derive(chert::Variables, Debug)]
+    struct Other {
+        a: String,
+    }
+
+    fn build() -> Result<chert::Engine<Other, u8>, chert::Error> {
+        let ast = chert::parse::<Variables>("a == 1")?;
+        Ok(chert::compile_unsafe::<Other, _, _, _>([(
+            0,
+            ast.into_root(),
+        )])?)
+    }
+
+    let error = build().unwrap_err();
+    assert_eq!(
+        error,
+        chert::Error::Compile(chert::compile::Error::VariableTypeMismatch {
+            name: String::from("a"),
+            expected: "uint64",
+        })
+    );
+    assert_eq!(error.to_string(), "failed to compile expression");
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "variable `a` is not of type uint64"
+    );
+
+    let boxed: Box<dyn std::error::Error> = Box::new(error);
+    assert!(boxed.source().is_some());
+}
+
+#[test]
+fn test_null() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: Option<u64>,
+    }
+
+    assert!(matches!(
+        chert::parse::<Variables>("null == null"),
+        Err(chert::ParseError::Parse(
+            chert::parse::Error::BadBinaryOperands { .. }
+        ))
+    ));
+    assert!(matches!(
+        chert::parse::<Variables>("a + 1 == null"),
+        Err(chert::ParseError::Parse(
+            chert::parse::Error::BadBinaryOperands { .. }
+        ))
+    ));
+    assert!(matches!(
+        chert::parse::<Variables>("exists(1)"),
+        Err(chert::ParseError::Parse(
+            chert::parse::Error::BadUnaryOperands { .. }
+        ))
+    ));
+    assert!(matches!(
+        chert::parse::<Variables>("null"),
+        Err(chert::ParseError::Parse(
+            chert::parse::Error::NotBoolean { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_lists() {
+    #[derive(chert::Variables, Debug)]
+    struct Variables {
+        a: u64,
+        b: Vec<String>,
+    }
+
+    for (expression, message, span) in [
+        ("a in []", "list is empty", 5..7),
+        ("a in [1, 'x']", "lists cannot mix uint64 and string", 9..12),
+        ("a in [true]", "lists cannot contain boolean", 6..10),
+        ("a == 1, a == 2", "comma outside of a list", 6..7),
+        ("a in [1,]", "operator is missing an operand", 8..9),
+        (
+            "any b",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            0..3,
+        ),
+        (
+            "all(b)",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            0..6,
+        ),
+        (
+            "any(b, x => x)",
+            "expression does not result in a boolean",
+            12..13,
+        ),
+        (
+            "any(a, x => x == 1)",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            4..5,
+        ),
+        // the element has to be named, rather than being whichever identifier isn't a variable
+        (
+            "any(b, x == 'y')",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            0..11,
+        ),
+        (
+            "any(b, true => true)",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            0..11,
+        ),
+        (
+            "any(b, x",
+            "expected `any(list, element => predicate)` or `all(list, element => predicate)`",
+            0..8,
+        ),
+        ("a => 1", "`=>` is not a binary operator", 2..4),
+        (
+            "a in b",
+            "`in` cannot be applied to uint64 and [string]",
+            2..4,
+        ),
+        ("len(a) == 1", "`len` cannot be applied to uint64", 0..3),
+    ] {
+        let Err(chert::ParseError::Parse(error)) = chert::parse::<Variables>(expression) else {
+            panic!("{expression} parsed");
+        };
+        assert_eq!(error.to_string(), message, "{expression}");
+        assert_eq!(error.span(), span, "{expression}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations, so this file has to stay at one test; tests run in parallel
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    nick: String,
+    tags: Vec<String>,
+    pattern: regex::Regex,
+    count: u64,
+}
+
+#[test]
+fn test_eval_with() {
+    let engine = chert::compile(Vec::from([
+        (0, chert::parse("nick == 'jess' && count > 3").unwrap()),
+        (1, chert::parse("'op' in tags || nick ~ pattern").unwrap()),
+        (2, chert::parse("any(tags, tag => tag == nick)").unwrap()),
+        (
+            3,
+            chert::parse("count * 2 == 10 && len(tags) == 2").unwrap(),
+        ),
+    ]))
+    .unwrap();
+
+    let events = [
+        Variables {
+            nick: String::from("jess"),
+            tags: Vec::from([String::from("op"), String::from("jess")]),
+            pattern: regex::Regex::new("^j").unwrap(),
+            count: 5,
+        },
+        Variables {
+            nick: String::from("x"),
+            tags: Vec::new(),
+            pattern: regex::Regex::new("^j").unwrap(),
+            count: 0,
+        },
+    ];
+
+    let mut context = chert::EvalContext::new();
+    // the first evaluations size the context's buffers, and each regex's cache
+    assert_eq!(
+        engine.eval_with(&mut context, &events[0]),
+        &[&0, &1, &2, &3]
+    );
+    assert_eq!(engine.eval_with(&mut context, &events[1]), &[] as &[&i32]);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..100 {
+        for event in &events {
+            let matched = engine.eval_with(&mut context, event).len();
+            assert!(matched <= 4);
+        }
+    }
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+
+    for event in &events {
+        assert_eq!(engine.eval_with(&mut context, event), engine.eval(event));
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::{Duration, SystemTime};
+
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    a: bool,
+    b: bool,
+    c: u64,
+    d: u64,
+    e: i64,
+    f: f64,
+    g: String,
+    h: std::net::IpAddr,
+    i: cidr::IpCidr,
+    j: regex::Regex,
+    k: Duration,
+    l: SystemTime,
+    m: Vec<String>,
+    n: Vec<u64>,
+}
+
+fn round_trip(expression: &str) -> String {
+    let ast = chert::parse::<Variables>(expression).unwrap();
+    let formatted = chert::format(ast.get_root());
+    assert_eq!(
+        chert::parse::<Variables>(&formatted)
+            .unwrap_or_else(|error| panic!("{formatted}: {error}"))
+            .get_root(),
+        ast.get_root(),
+        "{expression} formatted as {formatted}"
+    );
+    formatted
+}
+
+#[test]
+fn test_canonical() {
+    for (expression, expected) in [
+        ("a&&b", "a && b"),
+        ("(a && b) || a", "a && b || a"),
+        ("a && (b || a)", "a && (b || a)"),
+        ("(a || b) || a", "a || b || a"),
+        ("a || (b || a)", "a || (b || a)"),
+        ("((c)) == 1", "c == 1"),
+        ("c - (d - 1) == (c - d) - 1", "c - (d - 1) == c - d - 1"),
+        (
+            "c ** (d ** 2) == (c ** d) ** 2",
+            "c ** d ** 2 == (c ** d) ** 2",
+        ),
+        ("c * (d + 1) / 2 == c % 3", "c * (d + 1) / 2 == c % 3"),
+        ("!(a == b)", "!(a == b)"),
+        ("!a == b", "!a == b"),
+        ("(a == b) == a", "a == b == a"),
+        ("a == (b == a)", "a == (b == a)"),
+        ("-(c + d) < e", "-(c + d) < e"),
+        ("-5 ** 2 == e", "-5 ** 2 == e"),
+        ("e - -5 == -(-e)", "e - -5 == --e"),
+        ("+c == d", "c == d"),
+        ("exists (c) && c == null", "exists(c) && !exists(c)"),
+        ("null != h", "exists(h)"),
+        ("f + 2 > 1.5", "f + 2.0 > 1.5"),
+        ("f == -0.25", "f == -0.25"),
+        (
+            "g + \"it's\\n\" ~ m/a\\/b|c/i",
+            "g + 'it\\'s\\n' ~ m#a\\/b|c#i",
+        ),
+        (
+            "g ~ m/^a b$/xu && g ~ m/^a b$/",
+            "g ~ m/^a b$/xu && g ~ m/^a b$/",
+        ),
+        (
+            "h in 10.0.0.0/8 && h != fe80::1",
+            "h in 10.0.0.0/8 && h != fe80:0:0:0:0:0:0:1",
+        ),
+        ("k + 90m == 1h30m1ns * 2", "k + 1h30m == 1h30m1ns * 2"),
+        ("l - k > now - 0s", "l - k > now - 0s"),
+        (
+            "l == 2023-08-01T13:30:00.5+01:00",
+            "l == 2023-08-01T12:30:00.5Z",
+        ),
+        ("g in ['a',\"b\"]", "g in ['a', 'b']"),
+        ("e in [1, -2] && c in [1]", "e in [1, -2] && c in [1]"),
+        (
+            "h in [10.0.0.0/8,fe80::/10]",
+            "h in [10.0.0.0/8, fe80:0:0:0:0:0:0:0/10]",
+        ),
+        ("len (m) > len([c + 1, d])", "len(m) > len([c + 1, d])"),
+        (
+            "any(m,x=>x ~ j) && all (n, y  =>  y > c)",
+            "any(m, x => x ~ j) && all(n, y => y > c)",
+        ),
+    ] {
+        assert_eq!(round_trip(expression), expected);
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    for expression in [
+        "g ~ j && h in i",
+        "c + e * f / 2 ** 3 > 1",
+        "c ** d == 1 || e ** c == 1",
+        "-c == e && -f < 0.5 && -e == e",
+        "l - 1969-12-31T23:59:59.999999999Z >= 1w2d3h4m5s6ms7us8ns",
+        "2 * k == k / 3 && 1d + l < l + 1s",
+        "g == r'\\d' || g == '\\u{1f980}\\0\\u{7}'",
+        "!!a",
+        "true == !false",
+        "e == -9223372036854775808",
+        "c in n && any(m, x => x == g || x in m) && !exists(m)",
+        "any(n, y => all(m, x => x != g && y > 1))",
+    ] {
+        round_trip(expression);
+    }
+}
+
+#[test]
+fn test_display() {
+    let ast = chert::parse::<Variables>("(a)").unwrap();
+    assert_eq!(ast.get_root().to_string(), "a");
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "json")]
+
+use chert::variables::json::{Bindings, Error};
+use chert::Type;
+use serde_json::json;
+use std::time::{Duration, UNIX_EPOCH};
+
+fn bindings() -> Bindings {
+    Bindings::new([
+        ("src", "/source/ip", Type::Ip),
+        ("port", "/source/port", Type::Uint64),
+        ("user", "/user/name", Type::String),
+        ("elapsed", "/elapsed", Type::Duration),
+        ("at", "/at", Type::Timestamp),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_eval() {
+    let bindings = bindings();
+    let schema = bindings.schema();
+    let engine = chert::compile_schema(
+        schema,
+        [
+            (
+                0,
+                chert::parse_with_schema("src in 10.0.0.0/8 && port == 22", schema).unwrap(),
+            ),
+            (
+                1,
+                chert::parse_with_schema("user == 'root' || elapsed > 1m", schema).unwrap(),
+            ),
+        ],
+    )
+    .unwrap();
+
+    let record = bindings
+        .record(&json!({
+            "source": {"ip": "10.1.2.3", "port": 22},
+            "user": {"name": "jess"},
+            "elapsed": "1m30s",
+            "at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap();
+    assert_eq!(engine.eval(&record), &[&0, &1]);
+}
+
+#[test]
+fn test_coercion() {
+    let bindings = bindings();
+    let record = bindings
+        .record(&json!({
+            "source": {"ip": "::1", "port": 1},
+            "user": {"name": ""},
+            "elapsed": 1.5,
+            "at": 60,
+        }))
+        .unwrap();
+    assert!(matches!(
+        record.get("elapsed"),
+        Some(chert::variables::dynamic::Value::Duration(d)) if *d == Duration::from_millis(1500)
+    ));
+    assert!(matches!(
+        record.get("at"),
+        Some(chert::variables::dynamic::Value::Timestamp(t))
+            if *t == UNIX_EPOCH + Duration::from_secs(60)
+    ));
+}
+
+#[test]
+fn test_missing() {
+    let bindings = bindings();
+    let schema = bindings.schema();
+    let engine = chert::compile_schema(
+        schema,
+        [
+            (0, chert::parse_with_schema("exists(user)", schema).unwrap()),
+            (
+                1,
+                chert::parse_with_schema("user != 'root'", schema).unwrap(),
+            ),
+            (2, chert::parse_with_schema("port == 22", schema).unwrap()),
+        ],
+    )
+    .unwrap();
+
+    // `null` is missing too
+    let record = bindings
+        .record(&json!({
+            "source": {"ip": "10.1.2.3", "port": 22},
+            "user": {"name": null},
+            "elapsed": "1s",
+            "at": 0,
+        }))
+        .unwrap();
+    assert!(record.get("user").is_none());
+    assert_eq!(engine.eval(&record), &[&2]);
+
+    let record = bindings
+        .record(&json!({"source": {"ip": "10.1.2.3"}, "user": {"name": "jess"}}))
+        .unwrap();
+    assert!(record.get("port").is_none());
+    assert_eq!(engine.eval(&record), &[&0, &1]);
+}
+
+#[test]
+fn test_regex() {
+    assert_eq!(
+        Bindings::new([("pattern", "/pattern", Type::Regex)]).unwrap_err(),
+        Error::UnsupportedType {
+            name: String::from("pattern"),
+            type_: Type::Regex,
+        }
+    );
+}
+
+#[test]
+fn test_wrong_type() {
+    for (pointer, value) in [
+        ("/source/ip", json!("10.1.2")),
+        ("/source/port", json!(-1)),
+        ("/source/port", json!(22.0)),
+        ("/source/port", json!("22")),
+        ("/user/name", json!(1)),
+        ("/elapsed", json!("1 s")),
+        ("/elapsed", json!(-1)),
+        ("/at", json!("2024-01-01")),
+    ] {
+        let mut event = json!({
+            "source": {"ip": "10.1.2.3", "port": 22},
+            "user": {"name": "jess"},
+            "elapsed": "1s",
+            "at": 0,
+        });
+        *event.pointer_mut(pointer).unwrap() = value.clone();
+        assert!(
+            matches!(
+                bindings().record(&event),
+                Err(Error::WrongType { pointer: p, .. }) if p == pointer
+            ),
+            "{pointer} = {value}"
+        );
+    }
+}
+
+#[test]
+fn test_lists() {
+    let bindings = Bindings::new([
+        ("tags", "/tags", Type::StringList),
+        ("hops", "/hops", Type::IpList),
+    ])
+    .unwrap();
+    let schema = bindings.schema();
+    let engine = chert::compile_schema(
+        schema,
+        [(
+            0,
+            chert::parse_with_schema("'web' in tags && all(hops, h => h in 10.0.0.0/8)", schema)
+                .unwrap(),
+        )],
+    )
+    .unwrap();
+
+    let record = bindings
+        .record(&json!({"tags": ["web", "prod"], "hops": ["10.0.0.1", "10.2.0.1"]}))
+        .unwrap();
+    assert_eq!(engine.eval(&record), &[&0]);
+
+    assert_eq!(
+        bindings
+            .record(&json!({"tags": ["web", 1], "hops": []}))
+            .unwrap_err(),
+        Error::WrongType {
+            name: String::from("tags"),
+            pointer: String::from("/tags"),
+            expected: Type::StringList,
+        }
+    );
+}
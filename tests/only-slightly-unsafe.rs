@@ -1,3 +1,5 @@
+#![allow(unused_unsafe)]
+
 #[test]
 fn test_serialize() {
     #[derive(chert::Variables, Debug)]
@@ -0,0 +1,201 @@
+use chert::variables::dynamic::{Error, Value};
+use chert::{Schema, Type};
+use std::time::Duration;
+
+fn schema() -> Schema {
+    Schema::new([
+        ("a", Type::Uint64),
+        ("b", Type::String),
+        ("c", Type::Ip),
+        ("d", Type::Duration),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_eval() {
+    let schema = schema();
+    let engine = chert::compile_schema(
+        &schema,
+        [
+            (
+                0,
+                chert::parse_with_schema("a > 3 && b == 'x'", &schema).unwrap(),
+            ),
+            (
+                1,
+                chert::parse_with_schema("c in 10.0.0.0/8", &schema).unwrap(),
+            ),
+            (2, chert::parse_with_schema("d >= 1m", &schema).unwrap()),
+        ],
+    )
+    .unwrap();
+
+    let mut record = schema.record();
+    assert_eq!(engine.eval(&record), &[&0; 0]);
+
+    record.set("a", 4u64).unwrap();
+    record.set("b", "x").unwrap();
+    record
+        .set("c", "10.1.2.3".parse::<std::net::IpAddr>().unwrap())
+        .unwrap();
+    record.set("d", Duration::from_secs(60)).unwrap();
+    assert_eq!(engine.eval(&record), &[&0, &1, &2]);
+}
+
+#[test]
+fn test_defaults() {
+    let record = schema().record();
+    assert!(matches!(record.get("a"), Some(Value::Uint64(0))));
+    assert!(matches!(record.get("b"), Some(Value::String(b)) if b.is_empty()));
+    assert!(record.get("e").is_none());
+}
+
+#[test]
+fn test_parse_errors() {
+    let schema = schema();
+    assert!(matches!(
+        chert::parse_with_schema("e == 1", &schema),
+        Err(chert::ParseError::Parse(
+            chert::parse::Error::UnknownIdentifier { .. }
+        ))
+    ));
+    assert!(chert::parse_with_schema("b + 1 == 2", &schema).is_err());
+}
+
+#[test]
+fn test_set_errors() {
+    let mut record = schema().record();
+    assert_eq!(
+        record.set("e", 1u64),
+        Err(Error::UnknownVariable {
+            name: String::from("e")
+        })
+    );
+    assert_eq!(
+        record.set("a", -1i64),
+        Err(Error::TypeMismatch {
+            name: String::from("a"),
+            expected: Type::Uint64,
+            found: Type::Int64,
+        })
+    );
+}
+
+#[test]
+fn test_duplicate() {
+    assert_eq!(
+        Schema::new([("a", Type::Uint64), ("a", Type::String)]).unwrap_err(),
+        Error::DuplicateVariable {
+            name: String::from("a")
+        }
+    );
+}
+
+#[test]
+fn test_cloned_schema() {
+    let schema = schema();
+    let engine = chert::compile_schema(
+        &schema,
+        [(0, chert::parse_with_schema("a == 1", &schema).unwrap())],
+    )
+    .unwrap();
+    let mut record = schema.clone().record();
+    record.set("a", 1u64).unwrap();
+    assert_eq!(engine.try_eval(&record), Ok(Vec::from([&0])));
+
+    // a different schema defining the same variables is still a different schema
+    let mut record = self::schema().record();
+    record.set("a", 1u64).unwrap();
+    assert_eq!(engine.try_eval(&record), Err(Error::SchemaMismatch));
+}
+
+#[test]
+#[should_panic(expected = "record doesn't match the schema the engine was compiled with")]
+fn test_different_schema() {
+    let schema = Schema::new([("a", Type::Uint64), ("b", Type::Uint64)]).unwrap();
+    let engine = chert::compile_schema(
+        &schema,
+        [(0, chert::parse_with_schema("a == 1", &schema).unwrap())],
+    )
+    .unwrap();
+    // the same types at the same positions, but `a` is second
+    let swapped = Schema::new([("b", Type::Uint64), ("a", Type::Uint64)]).unwrap();
+    let mut record = swapped.record();
+    record.set("b", 1u64).unwrap();
+    engine.eval(&record);
+}
+
+#[test]
+fn test_try_eval_different_schema() {
+    let schema = Schema::new([("a", Type::Uint64), ("b", Type::Uint64)]).unwrap();
+    let engine = chert::compile_schema(
+        &schema,
+        [(0, chert::parse_with_schema("a == 1", &schema).unwrap())],
+    )
+    .unwrap();
+    let swapped = Schema::new([("b", Type::Uint64), ("a", Type::Uint64)]).unwrap();
+    let mut record = swapped.record();
+    record.set("b", 1u64).unwrap();
+    assert_eq!(engine.try_eval(&record), Err(Error::SchemaMismatch));
+    assert_eq!(
+        engine
+            .try_eval_with(&mut chert::EvalContext::new(), &record)
+            .map(<[_]>::to_vec),
+        Err(Error::SchemaMismatch)
+    );
+}
+
+#[test]
+fn test_type_names() {
+    for name in [
+        "boolean",
+        "cidr",
+        "duration",
+        "float64",
+        "int64",
+        "ip",
+        "regex",
+        "string",
+        "timestamp",
+        "uint64",
+        "[cidr]",
+        "[int64]",
+        "[ip]",
+        "[string]",
+        "[uint64]",
+    ] {
+        assert_eq!(name.parse::<Type>().unwrap().name(), name);
+    }
+    assert!("u64".parse::<Type>().is_err());
+}
+
+#[test]
+fn test_optional() {
+    let schema = Schema::new_optional([("a", Type::Uint64), ("b", Type::String)]).unwrap();
+    let engine = chert::compile_schema(
+        &schema,
+        [
+            (0, chert::parse_with_schema("exists(a)", &schema).unwrap()),
+            (1, chert::parse_with_schema("a != 1", &schema).unwrap()),
+            (2, chert::parse_with_schema("b == null", &schema).unwrap()),
+        ],
+    )
+    .unwrap();
+
+    let mut record = schema.record();
+    assert!(record.get("a").is_none());
+    assert_eq!(engine.eval(&record), &[&2]);
+
+    record.set("a", 2u64).unwrap();
+    assert_eq!(engine.eval(&record), &[&0, &1, &2]);
+    record.unset("a").unwrap();
+    assert_eq!(engine.eval(&record), &[&2]);
+
+    assert_eq!(
+        self::schema().record().unset("a"),
+        Err(Error::NotOptional {
+            name: String::from("a")
+        })
+    );
+}
@@ -0,0 +1,205 @@
+//! `expr!` checks expressions with `parse::shape`, so it has to agree with the parser about
+//! every operator and operand
+
+use chert::parse::operators::{BinaryOperator, UnaryOperator};
+use chert::parse::shape::{self, Shape};
+use chert::parse::Error;
+use chert::{Schema, Type};
+
+const TYPES: [Type; 15] = [
+    Type::Boolean,
+    Type::Cidr,
+    Type::Duration,
+    Type::Float64,
+    Type::Int64,
+    Type::Ip,
+    Type::Regex,
+    Type::String,
+    Type::Timestamp,
+    Type::Uint64,
+    Type::CidrList,
+    Type::Int64List,
+    Type::IpList,
+    Type::StringList,
+    Type::Uint64List,
+];
+
+const UNARY: [(UnaryOperator, &str); 5] = [
+    (UnaryOperator::Exists, "exists"),
+    (UnaryOperator::Length, "len"),
+    (UnaryOperator::Negative, "-"),
+    (UnaryOperator::Not, "!"),
+    (UnaryOperator::Positive, "+"),
+];
+
+const BINARY: [BinaryOperator; 16] = [
+    BinaryOperator::Exponent,
+    BinaryOperator::Multiply,
+    BinaryOperator::Divide,
+    BinaryOperator::Modulo,
+    BinaryOperator::Both,
+    BinaryOperator::Either,
+    BinaryOperator::Add,
+    BinaryOperator::Subtract,
+    BinaryOperator::Within,
+    BinaryOperator::Equals,
+    BinaryOperator::NotEquals,
+    BinaryOperator::LessThan,
+    BinaryOperator::LessThanOrEquals,
+    BinaryOperator::GreaterThan,
+    BinaryOperator::GreaterThanOrEquals,
+    BinaryOperator::Matches,
+];
+
+fn variable(type_: Type) -> String {
+    format!("v_{type_:?}").to_lowercase()
+}
+
+fn schema() -> Schema {
+    Schema::new(TYPES.map(|type_| (variable(type_), type_))).unwrap()
+}
+
+/// An operand of every shape
+fn operands() -> Vec<(String, Shape)> {
+    let mut operands = Vec::from([(String::from("null"), Shape::Null)]);
+    for type_ in TYPES {
+        operands.push((variable(type_), Shape::Variable(type_)));
+    }
+    for (operand, shape) in [
+        ("true", Shape::Value(Type::Boolean)),
+        ("10.0.0.0/8", Shape::Value(Type::Cidr)),
+        ("1s", Shape::Value(Type::Duration)),
+        ("1.5", Shape::Value(Type::Float64)),
+        ("-1", Shape::Value(Type::Int64)),
+        ("fe80::1", Shape::Value(Type::Ip)),
+        ("m/a/", Shape::Value(Type::Regex)),
+        ("'a'", Shape::Value(Type::String)),
+        ("now", Shape::Value(Type::Timestamp)),
+        ("1", Shape::Value(Type::Uint64)),
+        ("v_uint64 * 2", Shape::Value(Type::Uint64)),
+        ("-v_int64", Shape::Value(Type::Int64)),
+        ("[10.0.0.0/8]", Shape::ListLiteral(Type::CidrList)),
+        ("[-1]", Shape::ListLiteral(Type::Int64List)),
+        ("[fe80::1]", Shape::ListLiteral(Type::IpList)),
+        ("['a']", Shape::ListLiteral(Type::StringList)),
+        ("[1]", Shape::ListLiteral(Type::Uint64List)),
+        ("[v_uint64]", Shape::ListLiteral(Type::Uint64List)),
+    ] {
+        operands.push((String::from(operand), shape));
+    }
+    operands
+}
+
+fn parse(schema: &Schema, expression: &str) -> Result<Shape, Error> {
+    let tokens = chert::lex::lex(expression).unwrap();
+    chert::parse::parse_with_schema(tokens, schema).map(|ast| Shape::of(ast.get_root()))
+}
+
+#[test]
+fn test_operands() {
+    let schema = schema();
+    for (operand, shape) in operands() {
+        assert_eq!(parse(&schema, &operand), Ok(shape), "{operand}");
+    }
+}
+
+#[test]
+fn test_unary() {
+    let schema = schema();
+    for (operator, token) in UNARY {
+        for (operand, operand_shape) in operands() {
+            let expression = format!("{token}({operand})");
+            assert_eq!(
+                parse(&schema, &expression).ok(),
+                shape::unary(&operator, operand_shape),
+                "{expression}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_binary() {
+    let schema = schema();
+    let operands = operands();
+    for operator in BINARY {
+        for (left, left_shape) in &operands {
+            for (right, right_shape) in &operands {
+                let expression = format!("({left}) {operator} ({right})");
+                assert_eq!(
+                    parse(&schema, &expression).ok(),
+                    shape::binary(&operator, *left_shape, *right_shape),
+                    "{expression}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_list() {
+    let schema = schema();
+    let operands = operands();
+    let mut lists = operands
+        .iter()
+        .map(|operand| Vec::from([operand]))
+        .collect::<Vec<_>>();
+    for _ in 0..2 {
+        lists = lists
+            .iter()
+            .flat_map(|list| {
+                operands.iter().map(|operand| {
+                    let mut list = list.clone();
+                    list.push(operand);
+                    list
+                })
+            })
+            .chain(lists.iter().cloned())
+            .collect();
+    }
+    for list in lists {
+        let expression = format!(
+            "[{}]",
+            list.iter()
+                .map(|(operand, _)| format!("({operand})"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let shapes = list.iter().map(|(_, shape)| *shape).collect::<Vec<_>>();
+        match (parse(&schema, &expression), shape::list(&shapes)) {
+            (Ok(parsed), Ok(shape)) => assert_eq!(parsed, shape, "{expression}"),
+            (Err(Error::BadListElement { .. }), Err(0)) => {}
+            (Err(Error::MixedList { .. }), Err(index)) if index > 0 => {}
+            (parsed, shape) => panic!("{expression}: parsed as {parsed:?}, shaped as {shape:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_quantifier() {
+    let schema = schema();
+    let operands = operands();
+    for (list, list_shape) in &operands {
+        for (operand, operand_shape) in &operands {
+            let expression = format!("any(({list}), x => ({operand}) == x)");
+            let shape = shape::element(*list_shape)
+                .and_then(|element| {
+                    shape::binary(
+                        &BinaryOperator::Equals,
+                        *operand_shape,
+                        Shape::Variable(element),
+                    )
+                })
+                .and_then(shape::quantifier);
+            assert_eq!(parse(&schema, &expression).ok(), shape, "{expression}");
+        }
+    }
+    for (predicate, predicate_shape) in &operands {
+        let expression = format!("all([1], x => ({predicate}))");
+        assert_eq!(
+            parse(&schema, &expression).ok(),
+            shape::quantifier(*predicate_shape),
+            "{expression}"
+        );
+    }
+}
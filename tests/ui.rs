@@ -0,0 +1,5 @@
+#[test]
+fn test_expr() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/expr_*.rs");
+}
@@ -0,0 +1,10 @@
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    count: u64,
+}
+
+fn main() {
+    chert::expr!(Variables, "count > 3 &&");
+    chert::expr!(Variables, "count > 'x");
+    chert::expr!(Variables, "count + 'x' > 3");
+}
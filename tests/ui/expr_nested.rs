@@ -0,0 +1,14 @@
+#[derive(chert::Variables, Debug)]
+struct Request {
+    ip: std::net::IpAddr,
+}
+
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    #[chert(nested)]
+    request: Request,
+}
+
+fn main() {
+    chert::expr!(Variables, "request.ip in 10.0.0.0/8 || request.port == 80");
+}
@@ -0,0 +1,12 @@
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    names: Vec<String>,
+    count: u64,
+}
+
+fn main() {
+    chert::expr!(Variables, "any(count, n => n == 'x')");
+    chert::expr!(Variables, "all(names, n => n > count)");
+    chert::expr!(Variables, "any(names, n => count)");
+    chert::expr!(Variables, "[count, 'x'] == names");
+}
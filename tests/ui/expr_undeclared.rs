@@ -0,0 +1,15 @@
+use chert::variables::{Variable, Variables};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+struct Undeclared;
+
+impl Variables for Undeclared {
+    fn variables() -> HashMap<String, Variable<Self>> {
+        HashMap::new()
+    }
+}
+
+fn main() {
+    chert::expr!(Undeclared, "count > 3");
+}
@@ -0,0 +1,9 @@
+#[derive(chert::Variables, Debug)]
+struct Variables {
+    nick: String,
+    count: u64,
+}
+
+fn main() {
+    chert::expr!(Variables, "count > 3 && nick > 3");
+}